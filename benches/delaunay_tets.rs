@@ -1,3 +1,4 @@
+use cimplex::tetrahedralize::{DelaunayTetsBuilder, SpatialSort};
 use cimplex::vertex::{HasPosition3D, HasVertices};
 use cimplex::Mesh03;
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
@@ -52,9 +53,42 @@ fn delaunay_tets_cospherical(c: &mut Criterion) {
     });
 }
 
+fn delaunay_tets_spatial_sort(c: &mut Criterion) {
+    let mut rng = Pcg64::new(PCG_STATE, PCG_STREAM);
+    let dist = Uniform::new_inclusive(-10.0, 10.0);
+    let data = (0..10000).map(|_| {
+        let vals = dist.sample_iter(&mut rng).take(3).collect::<Vec<_>>();
+        (Pt3::new(vals[0], vals[1], vals[2]), ())
+    });
+
+    let mut mesh = Mesh03::with_defaults(|| (Pt3::origin(), ()));
+    mesh.extend_vertices(data);
+
+    let mut group = c.benchmark_group("delaunay_tets_spatial_sort");
+    for (name, spatial_sort) in &[
+        ("none", SpatialSort::None),
+        ("hilbert", SpatialSort::Hilbert),
+        ("brio", SpatialSort::Brio),
+    ] {
+        group.bench_function(*name, |b| {
+            b.iter_batched(
+                || mesh.clone(),
+                |mesh| {
+                    DelaunayTetsBuilder::new()
+                        .spatial_sort(*spatial_sort)
+                        .build(mesh, || (), || (), || ())
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
 criterion_group!(
     bench_delaunay_tets,
     delaunay_tets_random,
-    delaunay_tets_cospherical
+    delaunay_tets_cospherical,
+    delaunay_tets_spatial_sort
 );
 criterion_main!(bench_delaunay_tets);
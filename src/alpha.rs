@@ -0,0 +1,290 @@
+//! Alpha complexes and alpha shapes of Delaunay tetrahedralizations
+
+use fnv::{FnvHashMap, FnvHashSet};
+use nalgebra::{dimension::U3, Point3};
+
+use crate::edge::EdgeId;
+use crate::tet::{HasTets, TetId};
+use crate::tetrahedralize::{tet_circumcenter, tri_circumcenter};
+use crate::tri::TriId;
+use crate::vertex::{HasPosition3D, Position, VertexId};
+
+/// The alphas at which the simplices of a Delaunay tetrahedralization enter its alpha complex.
+/// Vertices enter at 0.
+/// Both orientations of a triangle or edge that exist in the mesh are included.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlphaFiltration {
+    /// The critical alphas of the tetrahedrons
+    pub tets: FnvHashMap<TetId, f64>,
+    /// The critical alphas of the triangles
+    pub tris: FnvHashMap<TriId, f64>,
+    /// The critical alphas of the edges
+    pub edges: FnvHashMap<EdgeId, f64>,
+}
+
+impl AlphaFiltration {
+    /// Gets the alpha complex for some alpha,
+    /// which has the simplices whose critical alphas are below it.
+    pub fn complex(&self, alpha: f64) -> AlphaComplex {
+        AlphaComplex {
+            tets: self
+                .tets
+                .iter()
+                .filter(|(_, a)| **a < alpha)
+                .map(|(tet, _)| *tet)
+                .collect(),
+            tris: self
+                .tris
+                .iter()
+                .filter(|(_, a)| **a < alpha)
+                .map(|(tri, _)| *tri)
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|(_, a)| **a < alpha)
+                .map(|(edge, _)| *edge)
+                .collect(),
+        }
+    }
+}
+
+/// A subcomplex of a Delaunay tetrahedralization.
+/// Every vertex of the tetrahedralization is in it, so only the other simplices are listed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlphaComplex {
+    /// The tetrahedrons in the complex
+    pub tets: FnvHashSet<TetId>,
+    /// The triangles in the complex
+    pub tris: FnvHashSet<TriId>,
+    /// The edges in the complex
+    pub edges: FnvHashSet<EdgeId>,
+}
+
+impl AlphaComplex {
+    /// Gets the triangles on the boundary of the alpha shape, pointing outward.
+    /// Triangles not on any tetrahedron in the complex are included with both orientations.
+    pub fn boundary_tris<M: HasTets>(&self, mesh: &M) -> Vec<TriId> {
+        let mut boundary = FnvHashSet::default();
+        for tri in &self.tris {
+            let inside = mesh.tri_tets(*tri).any(|tet| self.tets.contains(&tet));
+            let outside = mesh
+                .tri_tets(tri.twin())
+                .any(|tet| self.tets.contains(&tet));
+            if !outside {
+                boundary.insert(*tri);
+                if !inside {
+                    boundary.insert(tri.twin());
+                }
+            }
+        }
+        boundary.into_iter().collect()
+    }
+}
+
+/// Whether vertex `v` is strictly inside the sphere around `center` that vertex `on` is on
+fn encroaches<M>(mesh: &M, center: Point3<f64>, on: VertexId, v: VertexId) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    (mesh.position(v) - center).norm_squared() < (mesh.position(on) - center).norm_squared()
+}
+
+/// Computes the critical alphas of the simplices of a Delaunay tetrahedralization.
+/// A simplex enters at the radius of its smallest circumsphere
+/// if that sphere is empty of its neighbors, and with its first coface otherwise.
+pub(crate) fn alpha_filtration<M>(mesh: &M) -> AlphaFiltration
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let tets = mesh
+        .tet_ids()
+        .map(|tet| {
+            (
+                *tet,
+                (tet_circumcenter(mesh, *tet) - mesh.position(tet.0[0])).norm(),
+            )
+        })
+        .collect::<FnvHashMap<_, _>>();
+
+    let tris = mesh
+        .tri_ids()
+        .map(|tri| {
+            let cofaces = mesh
+                .tri_tets(*tri)
+                .chain(mesh.tri_tets(tri.twin()))
+                .collect::<Vec<_>>();
+            let center = tri_circumcenter(mesh, *tri);
+            let attached = cofaces.iter().any(|tet| {
+                tet.0
+                    .iter()
+                    .any(|v| !tri.contains_vertex(*v) && encroaches(mesh, center, tri.0[0], *v))
+            });
+            let alpha = if attached {
+                cofaces
+                    .iter()
+                    .map(|tet| tets[tet])
+                    .fold(f64::INFINITY, f64::min)
+            } else {
+                (mesh.position(tri.0[0]) - center).norm()
+            };
+            (*tri, alpha)
+        })
+        .collect::<FnvHashMap<_, _>>();
+
+    let edges = mesh
+        .edge_ids()
+        .map(|edge| {
+            let cofaces = mesh
+                .edge_vertex_opps(*edge)
+                .map(|opp| TriId::from_valid([edge.0[0], edge.0[1], opp]))
+                .chain(
+                    mesh.edge_vertex_opps(edge.twin())
+                        .map(|opp| TriId::from_valid([edge.0[1], edge.0[0], opp])),
+                )
+                .collect::<Vec<_>>();
+            let center = nalgebra::center(&mesh.position(edge.0[0]), &mesh.position(edge.0[1]));
+            let attached = cofaces.iter().any(|tri| {
+                tri.0
+                    .iter()
+                    .any(|v| !edge.contains_vertex(*v) && encroaches(mesh, center, edge.0[0], *v))
+            });
+            let alpha = if attached {
+                cofaces
+                    .iter()
+                    .map(|tri| tris[tri])
+                    .fold(f64::INFINITY, f64::min)
+            } else {
+                (mesh.position(edge.0[0]) - center).norm()
+            };
+            (*edge, alpha)
+        })
+        .collect::<FnvHashMap<_, _>>();
+
+    AlphaFiltration { tets, tris, edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::edge::HasEdges;
+    use crate::tet::HasPositionAndTets;
+    use crate::tri::HasTris;
+    use crate::vertex::HasVertices;
+    use crate::ComboMesh0;
+
+    #[test]
+    fn test_alpha_filtration_regular_tet() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let s = 1.0 / (2.0 * 2f64.sqrt());
+        mesh.extend_vertices(vec![
+            Point3::new(s, s, s),
+            Point3::new(s, -s, -s),
+            Point3::new(-s, s, -s),
+            Point3::new(-s, -s, s),
+        ]);
+        let mesh = mesh.delaunay_tets(|| (), || (), || ());
+        let filtration = mesh.alpha_filtration();
+
+        // Side length is 1
+        assert!(filtration
+            .edges
+            .values()
+            .all(|alpha| (alpha - 0.5).abs() < 1e-12));
+        assert!(filtration
+            .tris
+            .values()
+            .all(|alpha| (alpha - 1.0 / 3f64.sqrt()).abs() < 1e-12));
+        assert!(filtration
+            .tets
+            .values()
+            .all(|alpha| (alpha - 6f64.sqrt() / 4.0).abs() < 1e-12));
+
+        let complex = filtration.complex(0.6);
+        assert_eq!(
+            (complex.tets.len(), complex.tris.len(), complex.edges.len()),
+            (0, 4, 12)
+        );
+        assert_eq!(mesh.alpha_shape(0.6).num_tris(), 8);
+        assert_eq!(mesh.alpha_shape(0.7).num_tris(), 4);
+        assert_eq!(mesh.alpha_shape(0.4).num_vertices(), 4);
+        assert_eq!(mesh.alpha_shape(0.4).num_edges(), 0);
+        assert_eq!(mesh.alpha_shape(0.55).num_edges(), 12);
+        assert_eq!(mesh.alpha_shape(0.55).num_tris(), 0);
+        assert_eq!(mesh.alpha_shape(0.7).num_vertices(), 4);
+    }
+
+    #[test]
+    fn test_alpha_filtration_attached() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(1.0, 0.1, 0.0),
+            Point3::new(1.0, 0.0, 3.0),
+        ]);
+        let mesh = mesh.delaunay_tets(|| (), || (), || ());
+        let filtration = mesh.alpha_filtration();
+
+        // The long edge of the obtuse triangle has the third vertex inside its diametral sphere
+        let tri = TriId::from_valid([VertexId(0), VertexId(1), VertexId(2)]);
+        let tri = if filtration.tris.contains_key(&tri) {
+            tri
+        } else {
+            tri.twin()
+        };
+        let edge = EdgeId([VertexId(0), VertexId(1)]);
+        let edge = if filtration.edges.contains_key(&edge) {
+            edge
+        } else {
+            edge.twin()
+        };
+        assert!(filtration.edges[&edge] > 1.0);
+        assert!(filtration.edges[&edge] <= filtration.tris[&tri]);
+    }
+
+    #[test]
+    fn test_alpha_complex_closed() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let mut rng = Pcg64::seed_from_u64(5);
+        mesh.extend_vertices((0..200).map(|_| Point3::new(rng.gen(), rng.gen(), rng.gen())));
+        let mesh = mesh.delaunay_tets(|| (), || (), || ());
+        let filtration = mesh.alpha_filtration();
+
+        // Faces enter no later than their cofaces
+        for (tet, alpha) in &filtration.tets {
+            assert!(tet.tris().iter().all(|tri| filtration.tris[tri] <= *alpha));
+        }
+        for (tri, alpha) in &filtration.tris {
+            assert!(tri
+                .edges()
+                .iter()
+                .all(|edge| filtration.edges[edge] <= *alpha));
+        }
+
+        for alpha in [0.05, 0.1, 0.2] {
+            let complex = filtration.complex(alpha);
+            assert!(complex
+                .tets
+                .iter()
+                .all(|tet| tet.tris().iter().all(|tri| complex.tris.contains(tri))));
+            assert!(complex
+                .tris
+                .iter()
+                .all(|tri| tri.edges().iter().all(|edge| complex.edges.contains(edge))));
+        }
+
+        let hull = mesh.clone().convex_hull(|| (), || ());
+        let shape = mesh.alpha_shape(f64::INFINITY);
+        assert_eq!(
+            shape.tri_ids().copied().collect::<FnvHashSet<_>>(),
+            hull.tri_ids().copied().collect::<FnvHashSet<_>>()
+        );
+    }
+}
@@ -12,13 +12,15 @@ use typenum::{Bit, B0, B1};
 
 use crate::{
     iter::{IteratorExt, MapWith},
-    tri::{HasTris, TriWalker},
+    tri::{HasTris, TriWalker, WithTris},
     vertex::{HasPosition, IntoVertices},
 };
 //use crate::tri::{HasTris, TriWalker};
+use crate::error::MeshError;
+use crate::io::obj::{Obj, ObjAttributes};
 use crate::private::{Key, Lock};
 use crate::vertex::{HasPositionDim, HasPositionPoint, Position};
-use crate::vertex::{HasVertices, Vertex, VertexId};
+use crate::vertex::{HasVertices, Skeleton, Vertex, VertexId};
 
 /// An edge id is just the edge's vertices in order.
 /// The vertices are not allowed to be the same.
@@ -27,11 +29,11 @@ use crate::vertex::{HasVertices, Vertex, VertexId};
 pub struct EdgeId(pub(crate) [VertexId; 2]);
 
 impl TryFrom<[VertexId; 2]> for EdgeId {
-    type Error = &'static str;
+    type Error = MeshError;
 
     fn try_from(vertices: [VertexId; 2]) -> Result<Self, Self::Error> {
         if vertices[0] == vertices[1] {
-            Err("Vertices are not allowed to be the same")
+            Err(MeshError::DegenerateSimplex(vertices.to_vec()))
         } else {
             Ok(EdgeId(vertices))
         }
@@ -194,6 +196,23 @@ pub trait WithEdges<V, E> {
     type WithEdges: HasVertices<V = V> + HasEdges<E = E>;
 }
 
+/// The 0-skeleton is the vertices.
+impl<M: HasEdges> Skeleton<0> for M
+where
+    M::V: Clone,
+{
+    type Skeleton = M::WithoutEdges;
+
+    fn skeleton_r<L: Lock>(&self) -> Self::Skeleton {
+        let mut mesh = M::WithoutEdges::from_v_r::<_, Key>(
+            self.vertices().map(|(id, v)| (*id, v.clone())),
+            self.default_v_r::<Key>(),
+        );
+        *mesh.next_vertex_id_mut::<Key>() = self.next_vertex_id::<Key>();
+        mesh
+    }
+}
+
 /// For simplicial complexes that can have edges
 pub trait HasEdges: HasVertices<HigherV = B1> {
     type Edge: Edge<E = Self::E, Mwb = Self::MwbE, Higher = Self::HigherE>;
@@ -237,37 +256,56 @@ pub trait HasEdges: HasVertices<HigherV = B1> {
     fn default_e_r<L: Lock>(&self) -> fn() -> Self::E;
 
     #[doc(hidden)]
-    #[cfg(feature = "obj")]
-    fn obj_with_edges<L: Lock>(
-        &self,
-        data: &mut obj::ObjData,
-        v_inv: &FnvHashMap<VertexId, usize>,
-    ) {
+    fn obj_with_edges<L: Lock>(&self, obj: &mut Obj, v_inv: &FnvHashMap<VertexId, usize>) {
         // If there are triangles, only isolated edges should be added separately.
         if !<Self::HigherE as Bit>::BOOL {
-            data.objects[0].groups[0].polys.extend(
+            obj.lines.extend(
                 self.edge_ids()
                     .map(|edge| edge.undirected())
                     .collect::<FnvHashSet<_>>()
                     .into_iter()
-                    .map(|edge| {
-                        obj::SimplePolygon(vec![
-                            obj::IndexTuple(v_inv[&edge.0[0]], None, None),
-                            obj::IndexTuple(v_inv[&edge.0[1]], None, None),
-                        ])
-                    }),
+                    .map(|edge| vec![v_inv[&edge.0[0]], v_inv[&edge.0[1]]]),
             )
         }
 
-        self.obj_with_edges_higher::<Key>(data, v_inv);
+        self.obj_with_edges_higher::<Key>(obj, v_inv);
+    }
+
+    #[doc(hidden)]
+    fn obj_with_edges_higher<L: Lock>(&self, obj: &mut Obj, v_inv: &FnvHashMap<VertexId, usize>);
+
+    /// Adds the lines of OBJ data as edges. Edges that are already there are skipped,
+    /// and ones that conflict with the mesh's manifoldness get recorded in the attributes.
+    #[doc(hidden)]
+    fn obj_add_edges<L: Lock>(
+        &mut self,
+        obj: &Obj,
+        ids: &[VertexId],
+        attributes: &mut ObjAttributes,
+    ) {
+        for edge in obj.line_edges(ids) {
+            self.obj_add_edge::<Key>(edge, attributes);
+        }
+
+        self.obj_add_edges_higher::<Key>(obj, ids, attributes);
     }
 
+    /// Adds an edge from OBJ data,
+    /// recording it in the attributes if it conflicts with the mesh's manifoldness.
     #[doc(hidden)]
-    #[cfg(feature = "obj")]
-    fn obj_with_edges_higher<L: Lock>(
-        &self,
-        data: &mut obj::ObjData,
-        v_inv: &FnvHashMap<VertexId, usize>,
+    fn obj_add_edge<L: Lock>(&mut self, edge: EdgeId, attributes: &mut ObjAttributes) {
+        match self.try_add_edge(edge, self.default_edge()) {
+            Ok(()) | Err(MeshError::DuplicateId(_)) => {}
+            Err(_) => attributes.skipped_edges.push(edge),
+        }
+    }
+
+    #[doc(hidden)]
+    fn obj_add_edges_higher<L: Lock>(
+        &mut self,
+        obj: &Obj,
+        ids: &[VertexId],
+        attributes: &mut ObjAttributes,
     );
 
     /// Flips an edge into 2 edges with `vertex` between them.
@@ -460,6 +498,30 @@ pub trait HasEdges: HasVertices<HigherV = B1> {
         }
     }
 
+    /// Flips an edge into 2 edges with `vertex` between them,
+    /// or reports why that can't be done.
+    fn try_flip12<EI>(&mut self, edge: EI, vertex: VertexId) -> Result<(), MeshError>
+    where
+        EI: TryInto<EdgeId>,
+        MeshError: From<EI::Error>,
+        Self::E: Clone,
+    {
+        let edge = edge.try_into()?;
+        if !self.contains_vertex(vertex) {
+            return Err(MeshError::MissingVertex(vertex));
+        }
+        if edge.contains_vertex(vertex) {
+            return Err(MeshError::DegenerateSimplex(vec![
+                edge.0[0], edge.0[1], vertex,
+            ]));
+        }
+        if !self.contains_edge(edge) && !self.contains_edge(edge.twin()) {
+            return Err(MeshError::MissingSimplex(edge.into()));
+        }
+        self.flip12(edge, vertex);
+        Ok(())
+    }
+
     /// Adds an edge to the mesh. Vertex order is important!
     /// If the edge was already there, this replaces the value.
     /// Returns the previous value of the edge, if there was one.
@@ -560,6 +622,33 @@ pub trait HasEdges: HasVertices<HigherV = B1> {
         })
     }
 
+    /// Adds an edge to the mesh,
+    /// or reports why it can't be added without touching the rest of the mesh.
+    /// Unlike `add_edge`, this fails instead of replacing an existing edge
+    /// or removing the edges in the way in a mwb edge mesh.
+    fn try_add_edge<EI>(&mut self, vertices: EI, value: Self::E) -> Result<(), MeshError>
+    where
+        EI: TryInto<EdgeId>,
+        MeshError: From<EI::Error>,
+    {
+        let id = vertices.try_into()?;
+        check_new_edge(self, id)?;
+        self.add_edge(id, value);
+        Ok(())
+    }
+
+    /// Extends the edge list with an iterator, stopping at the first edge that can't be added.
+    /// The edges before that one stay added.
+    fn try_extend_edges<EI, I>(&mut self, iter: I) -> Result<(), MeshError>
+    where
+        EI: TryInto<EdgeId>,
+        MeshError: From<EI::Error>,
+        I: IntoIterator<Item = (EI, Self::E)>,
+    {
+        iter.into_iter()
+            .try_for_each(|(id, value)| self.try_add_edge(id, value))
+    }
+
     /// Removes an edge from the mesh and returns the value that was there,
     /// or None if there was nothing there
     fn remove_edge<EI: TryInto<EdgeId>>(&mut self, id: EI) -> Option<Self::E> {
@@ -656,6 +745,43 @@ pub trait HasEdges: HasVertices<HigherV = B1> {
         }
     }
 
+    /// Turns this mesh into a vertex mesh, keeping the ids and values of the vertices.
+    fn into_without_edges(self) -> Self::WithoutEdges
+    where
+        Self: Sized,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let next_vertex_id = self.next_vertex_id::<Key>();
+        let (vertices, _) = self.into_ve_r::<Key>();
+        let mut mesh = Self::WithoutEdges::from_v_r::<_, Key>(vertices, default_v);
+        *mesh.next_vertex_id_mut::<Key>() = next_vertex_id;
+        mesh
+    }
+
+    /// Turns this edge mesh into a tri mesh with no triangles, keeping all ids and values.
+    fn into_with_tris<F>(self, default_f: fn() -> F) -> Self::WithTris
+    where
+        Self: Sized
+            + HasEdges<HigherE = B0>
+            + WithTris<<Self as HasVertices>::V, <Self as HasEdges>::E, F>,
+        Self::WithTris: HasTris<HigherF = B0>,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let default_e = self.default_e_r::<Key>();
+        let next_vertex_id = self.next_vertex_id::<Key>();
+        let (vertices, edges) = self.into_ve_r::<Key>();
+        let mut mesh = Self::WithTris::from_vef_r::<_, _, _, Key>(
+            vertices,
+            edges,
+            vec![],
+            default_v,
+            default_e,
+            default_f,
+        );
+        *mesh.next_vertex_id_mut::<Key>() = next_vertex_id;
+        mesh
+    }
+
     /// Gets a walker that starts at the given vertex.
     /// Returns None if the vertex has no outgoing edge.
     fn edge_walker_from_vertex(&self, vertex: VertexId) -> Option<EdgeWalker<Self>> {
@@ -669,6 +795,26 @@ pub trait HasEdges: HasVertices<HigherV = B1> {
     }
 }
 
+/// Checks that an edge can be added without replacing or removing anything.
+pub(crate) fn check_new_edge<M: HasEdges + ?Sized>(mesh: &M, id: EdgeId) -> Result<(), MeshError> {
+    if let Some(v) = id.0.iter().find(|v| !mesh.contains_vertex(**v)) {
+        return Err(MeshError::MissingVertex(*v));
+    }
+    if mesh.contains_edge(id) {
+        return Err(MeshError::DuplicateId(id.into()));
+    }
+
+    if <M::MwbE as Bit>::BOOL {
+        if let Some(target) = mesh.vertex_targets(id.0[0]).next() {
+            return Err(MeshError::NonManifold(EdgeId([id.0[0], target]).into()));
+        }
+        if let Some(source) = mesh.vertex_sources(id.0[1]).next() {
+            return Err(MeshError::NonManifold(EdgeId([source, id.0[1]]).into()));
+        }
+    }
+    Ok(())
+}
+
 /// A walker for navigating a simplicial complex by edge.
 ///
 /// Anatomy of the edge walker:
@@ -1227,8 +1373,24 @@ macro_rules! impl_has_edges {
         }
 
         crate::if_b0! { $higher =>
-            #[cfg(feature = "obj")]
-            fn obj_with_edges_higher<L: crate::private::Lock>(&self, _: &mut obj::ObjData, _: &fnv::FnvHashMap<crate::vertex::VertexId, usize>) {}
+            fn obj_with_edges_higher<L: crate::private::Lock>(
+                &self,
+                _: &mut crate::io::obj::Obj,
+                _: &fnv::FnvHashMap<crate::vertex::VertexId, usize>,
+            ) {
+            }
+
+            /// Without triangles, faces can only be kept as their boundary edges.
+            fn obj_add_edges_higher<L: crate::private::Lock>(
+                &mut self,
+                obj: &crate::io::obj::Obj,
+                ids: &[crate::vertex::VertexId],
+                attributes: &mut crate::io::obj::ObjAttributes,
+            ) {
+                for edge in obj.face_edges(ids) {
+                    self.obj_add_edge::<crate::private::Key>(edge, attributes);
+                }
+            }
 
             fn flip12_edge_higher<
                 EI: std::convert::TryInto<crate::edge::EdgeId>, L: crate::private::Lock, C: FnMut(&mut Self)
@@ -1238,9 +1400,21 @@ macro_rules! impl_has_edges {
         }
 
         crate::if_b1! { $higher =>
-            #[cfg(feature = "obj")]
-            fn obj_with_edges_higher<L: crate::private::Lock>(&self, data: &mut obj::ObjData, v_inv: &fnv::FnvHashMap<crate::vertex::VertexId, usize>) {
-                self.obj_with_tris::<crate::private::Key>(data, v_inv);
+            fn obj_with_edges_higher<L: crate::private::Lock>(
+                &self,
+                obj: &mut crate::io::obj::Obj,
+                v_inv: &fnv::FnvHashMap<crate::vertex::VertexId, usize>,
+            ) {
+                self.obj_with_tris::<crate::private::Key>(obj, v_inv);
+            }
+
+            fn obj_add_edges_higher<L: crate::private::Lock>(
+                &mut self,
+                obj: &crate::io::obj::Obj,
+                ids: &[crate::vertex::VertexId],
+                attributes: &mut crate::io::obj::ObjAttributes,
+            ) {
+                self.obj_add_tris::<crate::private::Key>(obj, ids, attributes);
             }
 
             fn flip12_edge_higher<
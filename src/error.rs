@@ -0,0 +1,118 @@
+//! Errors reported by fallible mesh operations
+
+use fnv::FnvHashSet;
+use std::convert::Infallible;
+use std::fmt;
+
+use crate::edge::EdgeId;
+use crate::tet::TetId;
+use crate::tri::TriId;
+use crate::vertex::VertexId;
+
+/// The id of a simplex of any dimension
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SimplexId {
+    Vertex(VertexId),
+    Edge(EdgeId),
+    Tri(TriId),
+    Tet(TetId),
+}
+
+impl From<VertexId> for SimplexId {
+    fn from(id: VertexId) -> Self {
+        SimplexId::Vertex(id)
+    }
+}
+
+impl From<EdgeId> for SimplexId {
+    fn from(id: EdgeId) -> Self {
+        SimplexId::Edge(id)
+    }
+}
+
+impl From<TriId> for SimplexId {
+    fn from(id: TriId) -> Self {
+        SimplexId::Tri(id)
+    }
+}
+
+impl From<TetId> for SimplexId {
+    fn from(id: TetId) -> Self {
+        SimplexId::Tet(id)
+    }
+}
+
+/// Why a mesh operation couldn't be done
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MeshError {
+    /// A vertex the operation refers to isn't in the mesh.
+    MissingVertex(VertexId),
+    /// A simplex the operation refers to isn't in the mesh.
+    MissingSimplex(SimplexId),
+    /// The operation would attach a second simplex to an oriented simplex of a mwb mesh.
+    /// Contains the simplex that's already attached.
+    NonManifold(SimplexId),
+    /// Some vertices of a simplex are the same.
+    DegenerateSimplex(Vec<VertexId>),
+    /// A simplex with that id is already in the mesh.
+    DuplicateId(SimplexId),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::MissingVertex(v) => write!(f, "vertex {:?} is not in the mesh", v),
+            MeshError::MissingSimplex(s) => write!(f, "{:?} is not in the mesh", s),
+            MeshError::NonManifold(s) => {
+                write!(f, "{:?} is already attached there in a mwb mesh", s)
+            }
+            MeshError::DegenerateSimplex(v) => {
+                write!(f, "vertices {:?} are not allowed to be the same", v)
+            }
+            MeshError::DuplicateId(s) => write!(f, "{:?} is already in the mesh", s),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// For conversions from ids that are already valid
+impl From<Infallible> for MeshError {
+    fn from(x: Infallible) -> Self {
+        match x {}
+    }
+}
+
+/// The error from converting a mesh without the mwb property into a mwb mesh.
+/// Gives the mesh back unchanged.
+#[derive(Clone, Debug)]
+pub struct IntoMwbError<M> {
+    pub mesh: Box<M>,
+    /// The simplices that share an oriented boundary simplex with another one
+    pub simplices: Vec<SimplexId>,
+}
+
+impl<M> fmt::Display for IntoMwbError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the mesh doesn't have the mwb property because of {:?}",
+            self.simplices
+        )
+    }
+}
+
+impl<M: fmt::Debug> std::error::Error for IntoMwbError<M> {}
+
+/// Gets the simplices in groups of more than one, once each.
+pub(crate) fn mwb_conflicts<I: Into<SimplexId>>(
+    groups: impl Iterator<Item = Vec<I>>,
+) -> Vec<SimplexId> {
+    let mut seen = FnvHashSet::default();
+    groups
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .map(Into::into)
+        .filter(|simplex| seen.insert(*simplex))
+        .collect()
+}
@@ -0,0 +1,43 @@
+//! Exact geometric predicates that return the sign of the determinant, zero included.
+//! This module is private.
+//!
+//! `simplicity` breaks every tie with Simulation of Simplicity, so it can never say that points
+//! are exactly collinear, coplanar, or cospherical. Some things need to know that:
+//! point location reports hits on a face, an edge, or a vertex; convex hulls leave out vertices
+//! in the middle of an edge; PLC validation reports vertices on segments and facets;
+//! and the first simplex of a Delaunay triangulation must not be flat.
+//! Those go through here, and everything else goes through `simplicity`.
+//! The in-circle and in-sphere tests of Delaunay triangulations start here too,
+//! because they break ties by perturbing the lifted coordinate instead of the positions.
+
+use nalgebra::{Vector2, Vector3};
+use robust_geo as rg;
+
+/// Positive if `a`, `b`, and `c` are in counterclockwise order, and 0 if they're collinear.
+pub(crate) fn orient_2d(a: Vector2<f64>, b: Vector2<f64>, c: Vector2<f64>) -> f64 {
+    rg::orient_2d(a, b, c)
+}
+
+/// Positive if `d` is below the plane through `a`, `b`, and `c`,
+/// where above is the side they look counterclockwise from, and 0 if the 4 are coplanar.
+pub(crate) fn orient_3d(a: Vector3<f64>, b: Vector3<f64>, c: Vector3<f64>, d: Vector3<f64>) -> f64 {
+    rg::orient_3d(a, b, c, d)
+}
+
+/// Positive if `d` is inside the circle through the counterclockwise `a`, `b`, and `c`,
+/// and 0 if it's on it.
+pub(crate) fn in_circle(a: Vector2<f64>, b: Vector2<f64>, c: Vector2<f64>, d: Vector2<f64>) -> f64 {
+    rg::in_circle(a, b, c, d)
+}
+
+/// Positive if `e` is inside the sphere through `a`, `b`, `c`, and `d`
+/// when `orient_3d(a, b, c, d)` is positive, and 0 if it's on it.
+pub(crate) fn in_sphere(
+    a: Vector3<f64>,
+    b: Vector3<f64>,
+    c: Vector3<f64>,
+    d: Vector3<f64>,
+    e: Vector3<f64>,
+) -> f64 {
+    rg::in_sphere(a, b, c, d, e)
+}
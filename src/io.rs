@@ -1,7 +1,144 @@
 //! For importing and exporting.
 
-#[cfg(feature = "obj")]
-pub use obj_io::*;
+use fnv::FnvHashMap;
+use std::{collections::hash_map::Entry, hash::Hash};
 
-#[cfg(feature = "obj")]
-mod obj_io {}
+use crate::{
+    edge::{EdgeId, HasEdges},
+    error::MeshError,
+    tri::{HasTris, TriId},
+};
+
+pub mod gmsh;
+
+pub mod obj;
+
+pub mod ply;
+
+#[cfg(feature = "stl")]
+pub mod stl;
+
+pub mod tetgen;
+
+pub mod vtk;
+
+/// For values that can be stored in a file as an integer tag,
+/// like a TetGen boundary marker or region attribute, or a Gmsh physical group.
+/// Tag 0 means no tag.
+pub trait Tag {
+    /// The tag to write for this value
+    fn tag(&self) -> i32;
+
+    /// The value to read for a tag. Tag 0 should give the value that means no tag.
+    fn from_tag(tag: i32) -> Self;
+}
+
+impl Tag for () {
+    fn tag(&self) -> i32 {
+        0
+    }
+
+    fn from_tag(_: i32) -> Self {}
+}
+
+impl Tag for i32 {
+    fn tag(&self) -> i32 {
+        *self
+    }
+
+    fn from_tag(tag: i32) -> Self {
+        tag
+    }
+}
+
+/// Keeps the first of each set of ids that are the same undirected,
+/// with the first nonzero tag among them.
+fn dedup_tags<I: Copy + Eq + Hash>(ids: impl Iterator<Item = (I, I, i32)>) -> Vec<(I, i32)> {
+    let mut indexes = FnvHashMap::<I, usize>::default();
+    let mut result = Vec::<(I, i32)>::new();
+    for (id, undirected, tag) in ids {
+        match indexes.entry(undirected) {
+            Entry::Occupied(entry) => {
+                let old = &mut result[*entry.get()].1;
+                if *old == 0 {
+                    *old = tag;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(result.len());
+                result.push((id, tag));
+            }
+        }
+    }
+    result
+}
+
+/// Gets each edge of a mesh once regardless of direction, with its tag.
+/// If the directions have different tags, a nonzero one is used.
+pub(crate) fn undirected_edges<M: HasEdges>(mesh: &M) -> Vec<(EdgeId, i32)>
+where
+    M::E: Tag,
+{
+    dedup_tags(
+        mesh.edges()
+            .map(|(id, value)| (*id, id.undirected(), value.tag())),
+    )
+}
+
+/// Gets each triangle of a mesh once regardless of orientation, with its tag.
+/// If the orientations have different tags, a nonzero one is used.
+pub(crate) fn undirected_tris<M: HasTris>(mesh: &M) -> Vec<(TriId, i32)>
+where
+    M::F: Tag,
+{
+    dedup_tags(
+        mesh.tris()
+            .map(|(id, value)| (*id, id.undirected(), value.tag())),
+    )
+}
+
+/// Tags an edge in both directions, adding it if it isn't in the mesh in either.
+/// Returns why the edge couldn't be added if it couldn't.
+pub(crate) fn set_edge_tag<M: HasEdges>(
+    mesh: &mut M,
+    edge: EdgeId,
+    tag: i32,
+) -> Result<(), MeshError>
+where
+    M::E: Tag,
+{
+    let mut found = false;
+    for id in [edge, edge.twin()] {
+        if let Some(value) = mesh.edge_mut(id) {
+            *value = M::E::from_tag(tag);
+            found = true;
+        }
+    }
+
+    if found {
+        Ok(())
+    } else {
+        mesh.try_add_edge(edge, M::E::from_tag(tag))
+    }
+}
+
+/// Tags a triangle in both orientations, adding it if it isn't in the mesh in either.
+/// Returns why the triangle couldn't be added if it couldn't.
+pub(crate) fn set_tri_tag<M: HasTris>(mesh: &mut M, tri: TriId, tag: i32) -> Result<(), MeshError>
+where
+    M::F: Tag,
+{
+    let mut found = false;
+    for id in [tri, tri.twin()] {
+        if let Some(value) = mesh.tri_mut(id) {
+            *value = M::F::from_tag(tag);
+            found = true;
+        }
+    }
+
+    if found {
+        Ok(())
+    } else {
+        mesh.try_add_tri(tri, M::F::from_tag(tag))
+    }
+}
@@ -0,0 +1,883 @@
+//! Reading and writing Gmsh MSH 4.1 files, ASCII and binary.
+//!
+//! The first physical group of each element's entity becomes its value through the `Tag` trait.
+//! Points, lines, triangles and tets are read, including the corners of second-order elements;
+//! other elements are skipped and counted in `MshAttributes`.
+//! Gmsh orients tets the other way from this crate, so tets get flipped on the way in and out.
+
+use fnv::FnvHashMap;
+use nalgebra::dimension::U3;
+use nalgebra::Point3;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::{set_edge_tag, set_tri_tag, undirected_edges, undirected_tris, Tag};
+use crate::edge::EdgeId;
+use crate::tet::{HasTets, TetId};
+use crate::tri::TriId;
+use crate::vertex::{HasPosition3D, Position, VertexId};
+
+/// Errors from reading a Gmsh file
+#[derive(Debug)]
+pub enum MshError {
+    Io(io::Error),
+    /// Something in the file couldn't be parsed.
+    Parse,
+    /// The file ended in the middle of a section.
+    Truncated,
+    /// The file isn't in MSH 4 format. Contains the version it claims to be.
+    UnsupportedVersion(String),
+    /// A binary file whose sizes aren't 8 bytes. Contains the size it claims to use.
+    UnsupportedDataSize(usize),
+    /// An element type whose number of nodes isn't known. Contains the type.
+    UnsupportedElement(i32),
+    /// An element refers to a node that doesn't exist. Contains the node's tag.
+    MissingNode(usize),
+}
+
+impl fmt::Display for MshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MshError::Io(err) => write!(f, "I/O error in MSH file: {}", err),
+            MshError::Parse => write!(f, "can't parse MSH file"),
+            MshError::Truncated => write!(f, "MSH file is truncated"),
+            MshError::UnsupportedVersion(v) => write!(f, "MSH version {} is not supported", v),
+            MshError::UnsupportedDataSize(s) => write!(f, "MSH data size {} is not supported", s),
+            MshError::UnsupportedElement(t) => write!(f, "MSH element type {} is not supported", t),
+            MshError::MissingNode(tag) => write!(f, "MSH node {} doesn't exist", tag),
+        }
+    }
+}
+
+impl std::error::Error for MshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MshError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MshError {
+    fn from(err: io::Error) -> Self {
+        MshError::Io(err)
+    }
+}
+
+/// What reading a Gmsh file gives besides the mesh itself
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MshAttributes {
+    /// The ids of the new vertices in node order
+    pub vertices: Vec<VertexId>,
+    /// The number of elements of each type that were skipped because they aren't simplices,
+    /// like quads and hexes
+    pub skipped_elements: FnvHashMap<i32, usize>,
+    /// Tets that weren't added because they would break the manifoldness of a mwb mesh
+    /// or are already in the mesh
+    pub skipped_tets: Vec<TetId>,
+    /// Triangles that weren't added because they would break the manifoldness of a mwb mesh.
+    /// Triangles that were already in the mesh just get tagged.
+    pub skipped_tris: Vec<TriId>,
+    /// Edges that weren't added because they would break the manifoldness of a mwb mesh.
+    /// Edges that were already in the mesh just get tagged.
+    pub skipped_edges: Vec<EdgeId>,
+}
+
+/// The encoding to write a Gmsh file in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MshFormat {
+    Ascii,
+    Binary,
+}
+
+const POINT: i32 = 15;
+const LINE: i32 = 1;
+const TRIANGLE: i32 = 2;
+const TETRAHEDRON: i32 = 4;
+
+/// The number of nodes of an element type, and how many of them are corners of a simplex
+fn element_nodes(ty: i32) -> Option<(usize, usize)> {
+    Some(match ty {
+        POINT => (1, 1),
+        LINE => (2, 2),
+        TRIANGLE => (3, 3),
+        3 => (4, 0),
+        TETRAHEDRON => (4, 4),
+        5 => (8, 0),
+        6 => (6, 0),
+        7 => (5, 0),
+        // Second-order line, triangle and tet, which list their corners first
+        8 => (3, 2),
+        9 => (6, 3),
+        11 => (10, 4),
+        10 => (9, 0),
+        12 => (27, 0),
+        13 => (18, 0),
+        14 => (14, 0),
+        16 => (8, 0),
+        17 => (20, 0),
+        18 => (15, 0),
+        19 => (13, 0),
+        20 => (9, 3),
+        21 => (10, 3),
+        26 => (4, 2),
+        27 => (5, 2),
+        28 => (6, 2),
+        29 => (20, 4),
+        _ => return None,
+    })
+}
+
+/// Reads the mixed text and binary contents of a file
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    binary: bool,
+    big_endian: bool,
+}
+
+impl<'a> Cursor<'a> {
+    /// Reads a line of text, skipping leading empty lines.
+    /// Gives `None` at the end of the file.
+    fn line(&mut self) -> Result<Option<&'a str>, MshError> {
+        loop {
+            if self.pos >= self.bytes.len() {
+                return Ok(None);
+            }
+            let rest = &self.bytes[self.pos..];
+            let len = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+            self.pos += (len + 1).min(rest.len());
+            let line = std::str::from_utf8(&rest[..len])
+                .map_err(|_| MshError::Parse)?
+                .trim();
+            if !line.is_empty() {
+                return Ok(Some(line));
+            }
+        }
+    }
+
+    fn word(&mut self) -> Result<&'a str, MshError> {
+        let rest = &self.bytes[self.pos.min(self.bytes.len())..];
+        let start = rest
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .ok_or(MshError::Truncated)?;
+        let len = rest[start..]
+            .iter()
+            .position(|b| b.is_ascii_whitespace())
+            .unwrap_or(rest.len() - start);
+        self.pos += start + len;
+        std::str::from_utf8(&rest[start..start + len]).map_err(|_| MshError::Parse)
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], MshError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + N)
+            .ok_or(MshError::Truncated)?;
+        self.pos += N;
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        if self.big_endian {
+            array.reverse();
+        }
+        Ok(array)
+    }
+
+    fn size(&mut self) -> Result<usize, MshError> {
+        if self.binary {
+            Ok(u64::from_le_bytes(self.bytes()?) as usize)
+        } else {
+            self.word()?.parse().map_err(|_| MshError::Parse)
+        }
+    }
+
+    fn int(&mut self) -> Result<i32, MshError> {
+        if self.binary {
+            Ok(i32::from_le_bytes(self.bytes()?))
+        } else {
+            self.word()?.parse().map_err(|_| MshError::Parse)
+        }
+    }
+
+    fn float(&mut self) -> Result<f64, MshError> {
+        if self.binary {
+            Ok(f64::from_le_bytes(self.bytes()?))
+        } else {
+            self.word()?.parse().map_err(|_| MshError::Parse)
+        }
+    }
+
+    /// Skips to the line after the end of a section.
+    fn end_section(&mut self, name: &str) -> Result<(), MshError> {
+        let end = format!("$End{}", name);
+        let rest = &self.bytes[self.pos.min(self.bytes.len())..];
+        let start = rest
+            .windows(end.len())
+            .position(|w| w == end.as_bytes())
+            .ok_or(MshError::Truncated)?;
+        self.pos += start;
+        self.line()?;
+        Ok(())
+    }
+}
+
+/// The elements of a Gmsh file, as node tags
+#[derive(Default)]
+struct Elements {
+    edges: Vec<([usize; 2], i32)>,
+    tris: Vec<([usize; 3], i32)>,
+    tets: Vec<([usize; 4], i32)>,
+    skipped: FnvHashMap<i32, usize>,
+}
+
+fn read_format(cursor: &mut Cursor) -> Result<(), MshError> {
+    let line = cursor.line()?.ok_or(MshError::Truncated)?;
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    match words[..] {
+        [version, file_type, data_size] => {
+            if !version.starts_with("4.") {
+                return Err(MshError::UnsupportedVersion(version.to_owned()));
+            }
+            cursor.binary = file_type == "1";
+
+            // Sizes are only read as raw bytes in binary files
+            let data_size = data_size.parse::<usize>().map_err(|_| MshError::Parse)?;
+            if cursor.binary && data_size != 8 {
+                return Err(MshError::UnsupportedDataSize(data_size));
+            }
+        }
+        _ => return Err(MshError::Parse),
+    }
+
+    if cursor.binary {
+        let one = cursor.bytes::<4>()?;
+        if i32::from_le_bytes(one) != 1 {
+            cursor.big_endian = true;
+            if i32::from_be_bytes(one) != 1 {
+                return Err(MshError::Parse);
+            }
+        }
+    }
+    cursor.end_section("MeshFormat")
+}
+
+/// Reads the entities section into the first physical tag of each (dimension, entity) pair
+fn read_entities(cursor: &mut Cursor) -> Result<FnvHashMap<(usize, i32), i32>, MshError> {
+    let counts = [
+        cursor.size()?,
+        cursor.size()?,
+        cursor.size()?,
+        cursor.size()?,
+    ];
+    let mut physical = FnvHashMap::default();
+
+    for (dim, count) in counts.iter().enumerate() {
+        for _ in 0..*count {
+            let tag = cursor.int()?;
+            // Points have a position, the others a bounding box.
+            for _ in 0..if dim == 0 { 3 } else { 6 } {
+                cursor.float()?;
+            }
+            let num_physical = cursor.size()?;
+            for i in 0..num_physical {
+                let physical_tag = cursor.int()?;
+                if i == 0 {
+                    physical.insert((dim, tag), physical_tag);
+                }
+            }
+            if dim > 0 {
+                for _ in 0..cursor.size()? {
+                    cursor.int()?;
+                }
+            }
+        }
+    }
+
+    cursor.end_section("Entities")?;
+    Ok(physical)
+}
+
+fn read_nodes(cursor: &mut Cursor) -> Result<Vec<(usize, Point3<f64>)>, MshError> {
+    let num_blocks = cursor.size()?;
+    let _num_nodes = cursor.size()?;
+    let _min_tag = cursor.size()?;
+    let _max_tag = cursor.size()?;
+    let mut nodes = vec![];
+
+    for _ in 0..num_blocks {
+        let dim = cursor.int()?;
+        let _entity = cursor.int()?;
+        let parametric = cursor.int()? != 0;
+        let count = cursor.size()?;
+
+        let tags = (0..count)
+            .map(|_| cursor.size())
+            .collect::<Result<Vec<_>, _>>()?;
+        for tag in tags {
+            let point = Point3::new(cursor.float()?, cursor.float()?, cursor.float()?);
+            if parametric {
+                for _ in 0..dim {
+                    cursor.float()?;
+                }
+            }
+            nodes.push((tag, point));
+        }
+    }
+
+    cursor.end_section("Nodes")?;
+    Ok(nodes)
+}
+
+fn read_elements(
+    cursor: &mut Cursor,
+    physical: &FnvHashMap<(usize, i32), i32>,
+    elements: &mut Elements,
+) -> Result<(), MshError> {
+    let num_blocks = cursor.size()?;
+    let _num_elements = cursor.size()?;
+    let _min_tag = cursor.size()?;
+    let _max_tag = cursor.size()?;
+
+    for _ in 0..num_blocks {
+        let dim = cursor.int()?;
+        let entity = cursor.int()?;
+        let ty = cursor.int()?;
+        let count = cursor.size()?;
+        let (num_nodes, num_corners) = element_nodes(ty).ok_or(MshError::UnsupportedElement(ty))?;
+        let tag = physical.get(&(dim as usize, entity)).copied().unwrap_or(0);
+
+        for _ in 0..count {
+            let _element = cursor.size()?;
+            let nodes = (0..num_nodes)
+                .map(|_| cursor.size())
+                .collect::<Result<Vec<_>, _>>()?;
+            match (dim, num_corners) {
+                (1, 2) => elements.edges.push(([nodes[0], nodes[1]], tag)),
+                (2, 3) => elements.tris.push(([nodes[0], nodes[1], nodes[2]], tag)),
+                (3, 4) => elements
+                    .tets
+                    .push(([nodes[0], nodes[2], nodes[1], nodes[3]], tag)),
+                // Points are already vertices
+                (0, 1) => {}
+                _ => *elements.skipped.entry(ty).or_insert(0) += 1,
+            }
+        }
+    }
+
+    cursor.end_section("Elements")
+}
+
+/// Reads Gmsh MSH 4 data into a mesh. Each node becomes a vertex.
+/// Lines and triangles tag the edges and triangles of the tets in both orientations,
+/// and get added if they aren't part of a tet.
+/// Degenerate elements and elements that conflict with the mesh's manifoldness are skipped.
+/// The mesh is left unchanged if there's an error.
+///
+/// Returns the ids of the new vertices and the elements that got skipped.
+pub fn read_msh<M, R>(mesh: &mut M, mut reader: R) -> Result<MshAttributes, MshError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    M::E: Tag,
+    M::F: Tag,
+    M::T: Tag,
+    R: Read,
+{
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let mut cursor = Cursor {
+        bytes: &bytes,
+        pos: 0,
+        binary: false,
+        big_endian: false,
+    };
+
+    let mut physical = FnvHashMap::default();
+    let mut nodes = vec![];
+    let mut elements = Elements::default();
+    let mut has_format = false;
+
+    while let Some(line) = cursor.line()? {
+        match line {
+            "$MeshFormat" => {
+                read_format(&mut cursor)?;
+                has_format = true;
+            }
+            _ if !has_format => return Err(MshError::Parse),
+            "$Entities" => physical = read_entities(&mut cursor)?,
+            "$Nodes" => nodes = read_nodes(&mut cursor)?,
+            "$Elements" => read_elements(&mut cursor, &physical, &mut elements)?,
+            _ => match line.strip_prefix('$') {
+                Some(name) => cursor.end_section(name)?,
+                None => return Err(MshError::Parse),
+            },
+        }
+    }
+
+    // Resolve every element before touching the mesh
+    let index_map = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (tag, _))| (*tag, i))
+        .collect::<FnvHashMap<_, _>>();
+    let index = |tag: &usize| {
+        index_map
+            .get(tag)
+            .copied()
+            .ok_or(MshError::MissingNode(*tag))
+    };
+    let tets = elements
+        .tets
+        .iter()
+        .map(|(tet, tag)| {
+            let tet = [
+                index(&tet[0])?,
+                index(&tet[1])?,
+                index(&tet[2])?,
+                index(&tet[3])?,
+            ];
+            Ok((tet, *tag))
+        })
+        .collect::<Result<Vec<_>, MshError>>()?;
+    let tris = elements
+        .tris
+        .iter()
+        .map(|(tri, tag)| Ok(([index(&tri[0])?, index(&tri[1])?, index(&tri[2])?], *tag)))
+        .collect::<Result<Vec<_>, MshError>>()?;
+    let edges = elements
+        .edges
+        .iter()
+        .map(|(edge, tag)| Ok(([index(&edge[0])?, index(&edge[1])?], *tag)))
+        .collect::<Result<Vec<_>, MshError>>()?;
+
+    let ids = mesh.extend_vertices(
+        nodes
+            .iter()
+            .map(|(_, p)| mesh.default_vertex().with_position(*p))
+            .collect::<Vec<_>>(),
+    );
+
+    let mut attributes = MshAttributes {
+        skipped_elements: elements.skipped,
+        ..MshAttributes::default()
+    };
+    for (tet, tag) in tets {
+        if let Ok(tet) = TetId::try_from(tet.map(|i| ids[i])) {
+            if mesh.try_add_tet(tet, M::T::from_tag(tag)).is_err() {
+                attributes.skipped_tets.push(tet);
+            }
+        }
+    }
+    for (tri, tag) in tris {
+        if let Ok(tri) = TriId::try_from(tri.map(|i| ids[i])) {
+            if set_tri_tag(mesh, tri, tag).is_err() {
+                attributes.skipped_tris.push(tri);
+            }
+        }
+    }
+    for (edge, tag) in edges {
+        if let Ok(edge) = EdgeId::try_from(edge.map(|i| ids[i])) {
+            if set_edge_tag(mesh, edge, tag).is_err() {
+                attributes.skipped_edges.push(edge);
+            }
+        }
+    }
+
+    attributes.vertices = ids;
+    Ok(attributes)
+}
+
+/// Reads a Gmsh MSH 4 file into a mesh. See `read_msh`.
+pub fn load_msh<M, P>(mesh: &mut M, path: P) -> Result<MshAttributes, MshError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    M::E: Tag,
+    M::F: Tag,
+    M::T: Tag,
+    P: AsRef<Path>,
+{
+    read_msh(mesh, File::open(path)?)
+}
+
+/// Writes numbers either as text, separated by spaces and newlines, or as binary
+struct MshWriter<W: Write> {
+    writer: W,
+    binary: bool,
+    line_start: bool,
+}
+
+impl<W: Write> MshWriter<W> {
+    fn word(&mut self, word: impl fmt::Display) -> io::Result<()> {
+        if !self.line_start {
+            write!(self.writer, " ")?;
+        }
+        self.line_start = false;
+        write!(self.writer, "{}", word)
+    }
+
+    fn size(&mut self, x: usize) -> io::Result<()> {
+        if self.binary {
+            self.line_start = false;
+            self.writer.write_all(&(x as u64).to_le_bytes())
+        } else {
+            self.word(x)
+        }
+    }
+
+    fn int(&mut self, x: i32) -> io::Result<()> {
+        if self.binary {
+            self.line_start = false;
+            self.writer.write_all(&x.to_le_bytes())
+        } else {
+            self.word(x)
+        }
+    }
+
+    fn float(&mut self, x: f64) -> io::Result<()> {
+        if self.binary {
+            self.line_start = false;
+            self.writer.write_all(&x.to_le_bytes())
+        } else {
+            self.word(x)
+        }
+    }
+
+    /// Ends a line of text. Binary data has no lines.
+    fn end_line(&mut self) -> io::Result<()> {
+        if self.binary {
+            Ok(())
+        } else {
+            self.line_start = true;
+            writeln!(self.writer)
+        }
+    }
+
+    /// Writes a line of text, like a section header or footer, after any binary data.
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        if !self.line_start {
+            writeln!(self.writer)?;
+        }
+        self.line_start = true;
+        writeln!(self.writer, "{}", text)
+    }
+}
+
+/// Writes a mesh as Gmsh MSH 4.1 data.
+/// Tets, triangles and edges are written as elements, with triangles and edges
+/// written once regardless of orientation. Elements with the same tag share an entity,
+/// which is in the physical group with that tag unless the tag is 0.
+pub fn write_msh<M, W>(mesh: &M, writer: W, format: MshFormat) -> io::Result<()>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    M::E: Tag,
+    M::F: Tag,
+    M::T: Tag,
+    W: Write,
+{
+    let vertices = mesh.vertex_ids().copied().collect::<Vec<_>>();
+    let v_inv = vertices
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (*v, i + 1))
+        .collect::<FnvHashMap<_, _>>();
+    let positions = vertices
+        .iter()
+        .map(|v| mesh.position(*v))
+        .collect::<Vec<_>>();
+
+    // Element node tags grouped by dimension and tag
+    let mut blocks: [Vec<(i32, Vec<Vec<usize>>)>; 4] = Default::default();
+    let mut add = |dim: usize, tag: i32, nodes: Vec<usize>| match blocks[dim]
+        .iter_mut()
+        .find(|(t, _)| *t == tag)
+    {
+        Some((_, elements)) => elements.push(nodes),
+        None => blocks[dim].push((tag, vec![nodes])),
+    };
+    for (edge, tag) in undirected_edges(mesh) {
+        add(1, tag, edge.0.iter().map(|v| v_inv[v]).collect());
+    }
+    for (tri, tag) in undirected_tris(mesh) {
+        add(2, tag, tri.0.iter().map(|v| v_inv[v]).collect());
+    }
+    for (tet, value) in mesh.tets() {
+        add(
+            3,
+            value.tag(),
+            [0, 2, 1, 3].iter().map(|i| v_inv[&tet.0[*i]]).collect(),
+        );
+    }
+    // Nodes need an entity to belong to.
+    let node_entity = (1..4)
+        .rev()
+        .find(|d| !blocks[*d].is_empty())
+        .map_or((0, 1), |d| (d, 1));
+    let num_points = if node_entity.0 == 0 { 1 } else { 0 };
+
+    let first = positions.first().copied().unwrap_or_else(Point3::origin);
+    let (min, max) = positions
+        .iter()
+        .fold((first, first), |(min, max), p| (min.inf(p), max.sup(p)));
+
+    let mut w = MshWriter {
+        writer: BufWriter::new(writer),
+        binary: format == MshFormat::Binary,
+        line_start: true,
+    };
+
+    w.text("$MeshFormat")?;
+    if w.binary {
+        w.text("4.1 1 8")?;
+        w.int(1)?;
+    } else {
+        w.text("4.1 0 8")?;
+    }
+    w.text("$EndMeshFormat")?;
+
+    w.text("$Entities")?;
+    w.size(num_points)?;
+    for block in &blocks[1..] {
+        w.size(block.len())?;
+    }
+    w.end_line()?;
+    if num_points > 0 {
+        w.int(1)?;
+        for x in &min.coords {
+            w.float(*x)?;
+        }
+        w.size(0)?;
+        w.end_line()?;
+    }
+    for block in &blocks[1..] {
+        for (i, (tag, _)) in block.iter().enumerate() {
+            w.int(i as i32 + 1)?;
+            for x in min.coords.iter().chain(&max.coords) {
+                w.float(*x)?;
+            }
+            if *tag == 0 {
+                w.size(0)?;
+            } else {
+                w.size(1)?;
+                w.int(*tag)?;
+            }
+            w.size(0)?;
+            w.end_line()?;
+        }
+    }
+    w.text("$EndEntities")?;
+
+    w.text("$Nodes")?;
+    for x in [1, positions.len(), 1.min(positions.len()), positions.len()] {
+        w.size(x)?;
+    }
+    w.end_line()?;
+    w.int(node_entity.0 as i32)?;
+    w.int(node_entity.1)?;
+    w.int(0)?;
+    w.size(positions.len())?;
+    w.end_line()?;
+    for i in 0..positions.len() {
+        w.size(i + 1)?;
+        w.end_line()?;
+    }
+    for p in &positions {
+        for x in &p.coords {
+            w.float(*x)?;
+        }
+        w.end_line()?;
+    }
+    w.text("$EndNodes")?;
+
+    let num_blocks = blocks.iter().map(Vec::len).sum::<usize>();
+    let num_elements = blocks.iter().flatten().map(|(_, e)| e.len()).sum::<usize>();
+    w.text("$Elements")?;
+    for x in [num_blocks, num_elements, 1.min(num_elements), num_elements] {
+        w.size(x)?;
+    }
+    w.end_line()?;
+    let mut element_tag = 1;
+    for (dim, block) in blocks.iter().enumerate() {
+        for (i, (_, elements)) in block.iter().enumerate() {
+            w.int(dim as i32)?;
+            w.int(i as i32 + 1)?;
+            w.int([POINT, LINE, TRIANGLE, TETRAHEDRON][dim])?;
+            w.size(elements.len())?;
+            w.end_line()?;
+            for nodes in elements {
+                w.size(element_tag)?;
+                element_tag += 1;
+                for node in nodes {
+                    w.size(*node)?;
+                }
+                w.end_line()?;
+            }
+        }
+    }
+    w.text("$EndElements")?;
+
+    w.writer.flush()
+}
+
+/// Writes a mesh as a Gmsh MSH 4.1 file. See `write_msh`.
+pub fn save_msh<M, P>(mesh: &M, path: P, format: MshFormat) -> io::Result<()>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    M::E: Tag,
+    M::F: Tag,
+    M::T: Tag,
+    P: AsRef<Path>,
+{
+    write_msh(mesh, File::create(path)?, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::HasEdges;
+    use crate::mesh3::Mesh33;
+    use crate::tet::HasPositionAndTets;
+    use crate::tri::HasTris;
+    use crate::vertex::HasVertices;
+
+    fn new_mesh() -> Mesh33<(), i32, i32, i32> {
+        Mesh33::with_defaults(|| (Point3::origin(), ()), || 0, || 0, || 0)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut mesh = new_mesh();
+        let ids = mesh.extend_vertices(vec![
+            (Point3::new(0.0, 0.0, 0.0), ()),
+            (Point3::new(1.0, 0.0, 0.0), ()),
+            (Point3::new(0.0, 1.0, 0.0), ()),
+            (Point3::new(0.0, 0.0, 1.0), ()),
+            (Point3::new(0.0, 0.0, -1.0), ()),
+        ]);
+        mesh.add_tet([ids[0], ids[2], ids[1], ids[3]], 1);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[4]], 2);
+        *mesh.tri_mut([ids[0], ids[1], ids[3]]).unwrap() = 5;
+        *mesh.edge_mut([ids[0], ids[3]]).unwrap() = 7;
+
+        for format in [MshFormat::Ascii, MshFormat::Binary] {
+            let mut bytes = vec![];
+            write_msh(&mesh, &mut bytes, format).unwrap();
+
+            let mut read = new_mesh();
+            let ids = read_msh(&mut read, &bytes[..]).unwrap().vertices;
+
+            assert_eq!(read.num_vertices(), 5);
+            assert_eq!(read.num_tets(), 2);
+            assert_eq!(read.num_tris(), mesh.num_tris());
+            assert_eq!(read.num_edges(), mesh.num_edges());
+            assert_eq!(read.tet([ids[0], ids[2], ids[1], ids[3]]), Some(&1));
+            assert_eq!(read.tet([ids[0], ids[1], ids[2], ids[4]]), Some(&2));
+            assert_eq!(read.tri([ids[0], ids[1], ids[3]]), Some(&5));
+            assert_eq!(read.edge([ids[0], ids[3]]), Some(&7));
+            assert_eq!(read.edge([ids[3], ids[0]]), Some(&7));
+            assert!(read.validate_with_positions().is_valid());
+        }
+    }
+
+    #[test]
+    fn test_read_gmsh_ascii() {
+        // A single tet with its volume in physical group 10 and one face in group 20,
+        // as Gmsh orients them, with an unknown section and a quad that get skipped
+        let data = "\
+$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$PhysicalNames
+2
+2 20 \"wall\"
+3 10 \"inside\"
+$EndPhysicalNames
+$Entities
+0 0 1 1
+1 0 0 0 1 1 0 1 20 0
+1 0 0 0 1 1 1 1 10 0
+$EndEntities
+$Nodes
+1 5 1 5
+3 1 0 5
+1
+2
+3
+4
+5
+0 0 0
+1 0 0
+0 1 0
+0 0 1
+1 1 0
+$EndNodes
+$Elements
+3 3 1 3
+2 1 2 1
+1 1 2 3
+2 1 3 1
+2 1 2 5 3
+3 1 4 1
+3 1 2 3 4
+$EndElements
+";
+        let mut mesh = new_mesh();
+        let attributes = read_msh(&mut mesh, data.as_bytes()).unwrap();
+        let ids = attributes.vertices;
+
+        assert_eq!(ids.len(), 5);
+        assert_eq!(
+            attributes.skipped_elements,
+            [(3, 1)].iter().copied().collect()
+        );
+        assert_eq!(mesh.num_tets(), 1);
+        assert_eq!(mesh.tet([ids[0], ids[2], ids[1], ids[3]]), Some(&10));
+        assert_eq!(mesh.tri([ids[0], ids[2], ids[1]]), Some(&20));
+        assert_eq!(mesh.tri([ids[0], ids[1], ids[2]]), None);
+        assert!(mesh.validate_with_positions().is_valid());
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let mut mesh = new_mesh();
+        assert!(matches!(
+            read_msh(&mut mesh, "$MeshFormat\n2.2 0 8\n$EndMeshFormat\n".as_bytes()),
+            Err(MshError::UnsupportedVersion(v)) if v == "2.2"
+        ));
+
+        let missing = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 1 1 1\n0 1 0 1\n1\n0 0 0\n$EndNodes\n\
+            $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+        assert!(matches!(
+            read_msh(&mut mesh, missing.as_bytes()),
+            Err(MshError::MissingNode(2))
+        ));
+        assert_eq!(mesh.num_vertices(), 0);
+
+        let truncated = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 1 1 1\n0 1 0 1\n";
+        assert!(matches!(
+            read_msh(&mut new_mesh(), truncated.as_bytes()),
+            Err(MshError::Truncated)
+        ));
+
+        let mut bytes = b"$MeshFormat\n4.1 1 4\n".to_vec();
+        bytes.extend_from_slice(&1i32.to_le_bytes());
+        bytes.extend_from_slice(b"\n$EndMeshFormat\n");
+        assert!(matches!(
+            read_msh(&mut new_mesh(), &bytes[..]),
+            Err(MshError::UnsupportedDataSize(4))
+        ));
+
+        let invalid = b"$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$\xff\xfe\n";
+        assert!(matches!(
+            read_msh(&mut new_mesh(), &invalid[..]),
+            Err(MshError::Parse)
+        ));
+    }
+}
@@ -0,0 +1,786 @@
+//! Reading and writing Wavefront OBJ files.
+//!
+//! Any mesh with 3D positions can be read into: every `v` becomes a vertex,
+//! lines become edges, and faces become fan-triangulated triangles.
+//! Meshes without triangles get the boundary edges of faces instead,
+//! and meshes without edges just get the vertices.
+//!
+//! This has its own parser instead of going through the `obj` crate,
+//! which stores positions as `f32` (so meshes lose precision on a round trip),
+//! leaves out `p` statements, and doesn't check indexes,
+//! so a bad file panics when it gets added to a mesh.
+//! Only the parts that use the `obj` crate's types need the `obj` feature:
+//! `Obj` converts to and from its `ObjData`, `HasPosition3D::write_obj` returns its `ObjError`,
+//! and the deprecated `ComboMesh2::from_obj`, `ComboMesh2::read_obj` and `HasPosition3D::to_obj`
+//! take or give its data.
+
+use fnv::FnvHashMap;
+use nalgebra::{Point3, Vector2, Vector3};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::edge::EdgeId;
+use crate::private::Key;
+use crate::tri::TriId;
+use crate::vertex::{HasPosition3D, Position, VertexId};
+use nalgebra::dimension::U3;
+
+/// Errors from reading an OBJ file
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    /// A statement couldn't be parsed. Contains the 1-based line number.
+    Parse(usize),
+    /// An element refers to a position, texture coordinate or normal that doesn't exist.
+    /// Contains the 1-based line number.
+    IndexOutOfRange(usize),
+    /// A face of an `Obj` has fewer than 3 corners, or refers to a position, texture coordinate
+    /// or normal that doesn't exist. Contains the index of the face.
+    InvalidFace(usize),
+    /// A polyline of an `Obj` has fewer than 2 positions,
+    /// or refers to a position that doesn't exist.
+    /// Contains the index of the polyline.
+    InvalidLine(usize),
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "I/O error in OBJ file: {}", err),
+            ObjError::Parse(line) => write!(f, "can't parse OBJ statement on line {}", line),
+            ObjError::IndexOutOfRange(line) => write!(f, "OBJ index out of range on line {}", line),
+            ObjError::InvalidFace(face) => write!(f, "OBJ face {} is invalid", face),
+            ObjError::InvalidLine(line) => write!(f, "OBJ polyline {} is invalid", line),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+/// A corner of a face. Indexes are 0-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObjCorner {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+/// A face, with its corners in winding order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjFace {
+    pub corners: Vec<ObjCorner>,
+    /// Index into the group names, if the face is in a named group
+    pub group: Option<usize>,
+}
+
+/// The contents of an OBJ file.
+/// Indexes are 0-based and relative indexes are already resolved.
+/// Materials, objects, smoothing groups and free-form geometry are ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Obj {
+    pub positions: Vec<Point3<f64>>,
+    pub uvs: Vec<Vector2<f64>>,
+    pub normals: Vec<Vector3<f64>>,
+    pub groups: Vec<String>,
+    pub points: Vec<usize>,
+    /// Polylines, as position indexes
+    pub lines: Vec<Vec<usize>>,
+    pub faces: Vec<ObjFace>,
+}
+
+/// The data of an OBJ file that doesn't fit in a mesh, keyed by the elements it was read into
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjAttributes {
+    /// The normal of each vertex that had one.
+    /// If faces disagree on a vertex's normal, the first one wins.
+    pub normals: FnvHashMap<VertexId, Vector3<f64>>,
+    /// The texture coordinates of each vertex that had them.
+    /// If faces disagree on a vertex's texture coordinates, the first ones win.
+    pub uvs: FnvHashMap<VertexId, Vector2<f64>>,
+    /// The group name of each triangle from a face in a named group
+    pub tri_groups: FnvHashMap<TriId, String>,
+    /// Edges that weren't added because they would break the manifoldness of a mwb mesh.
+    /// Edges that were already in the mesh don't count.
+    pub skipped_edges: Vec<EdgeId>,
+    /// Triangles that weren't added because they would break the manifoldness of a mwb mesh.
+    /// Triangles that were already in the mesh don't count.
+    pub skipped_tris: Vec<TriId>,
+}
+
+/// Resolves a 1-based or negative relative OBJ index into a 0-based one.
+fn resolve(index: &str, len: usize, line: usize) -> Result<usize, ObjError> {
+    let index = index.parse::<isize>().map_err(|_| ObjError::Parse(line))?;
+    let resolved = if index > 0 {
+        index as usize - 1
+    } else if index < 0 && index.unsigned_abs() <= len {
+        len - index.unsigned_abs()
+    } else {
+        return Err(ObjError::IndexOutOfRange(line));
+    };
+
+    if resolved < len {
+        Ok(resolved)
+    } else {
+        Err(ObjError::IndexOutOfRange(line))
+    }
+}
+
+fn parse_floats<'a>(
+    words: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vec<f64>, ObjError> {
+    words
+        .map(|w| w.parse::<f64>().map_err(|_| ObjError::Parse(line)))
+        .collect()
+}
+
+impl Obj {
+    /// Parses OBJ data.
+    pub fn read<R: Read>(reader: R) -> Result<Self, ObjError> {
+        let mut obj = Obj::default();
+        let mut group = None;
+
+        for (i, text) in BufReader::new(reader).lines().enumerate() {
+            let text = text?;
+            let line = i + 1;
+            let text = text.split('#').next().unwrap();
+            let mut words = text.split_whitespace();
+
+            match words.next() {
+                Some("v") => match parse_floats(words, line)?[..] {
+                    [x, y, z] | [x, y, z, _] => obj.positions.push(Point3::new(x, y, z)),
+                    _ => return Err(ObjError::Parse(line)),
+                },
+
+                Some("vt") => match parse_floats(words, line)?[..] {
+                    [u] => obj.uvs.push(Vector2::new(u, 0.0)),
+                    [u, v] | [u, v, _] => obj.uvs.push(Vector2::new(u, v)),
+                    _ => return Err(ObjError::Parse(line)),
+                },
+
+                Some("vn") => match parse_floats(words, line)?[..] {
+                    [x, y, z] => obj.normals.push(Vector3::new(x, y, z)),
+                    _ => return Err(ObjError::Parse(line)),
+                },
+
+                Some("p") => {
+                    for word in words {
+                        obj.points.push(resolve(word, obj.positions.len(), line)?);
+                    }
+                }
+
+                Some("l") => {
+                    let polyline = words
+                        .map(|w| resolve(w.split('/').next().unwrap(), obj.positions.len(), line))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if polyline.len() < 2 {
+                        return Err(ObjError::Parse(line));
+                    }
+                    obj.lines.push(polyline);
+                }
+
+                Some("f") => {
+                    let corners = words
+                        .map(|w| obj.parse_corner(w, line))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    match corners.len() {
+                        0 | 1 => return Err(ObjError::Parse(line)),
+                        // Some exporters write edges as 2-sided faces
+                        2 => obj.lines.push(corners.iter().map(|c| c.position).collect()),
+                        _ => obj.faces.push(ObjFace { corners, group }),
+                    }
+                }
+
+                Some("g") => group = obj.group_index(words.collect::<Vec<_>>().join(" ")),
+
+                _ => {}
+            }
+        }
+
+        Ok(obj)
+    }
+
+    /// Gets the index of a group by name, adding it if it's new.
+    /// The unnamed default group has no index.
+    fn group_index(&mut self, name: String) -> Option<usize> {
+        if name.is_empty() || name == "default" {
+            return None;
+        }
+        Some(
+            self.groups
+                .iter()
+                .position(|g| *g == name)
+                .unwrap_or_else(|| {
+                    self.groups.push(name);
+                    self.groups.len() - 1
+                }),
+        )
+    }
+
+    /// Parses a face corner of the form `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+    fn parse_corner(&self, word: &str, line: usize) -> Result<ObjCorner, ObjError> {
+        let mut parts = word.split('/');
+        let position = resolve(parts.next().unwrap(), self.positions.len(), line)?;
+        let uv = match parts.next() {
+            Some("") | None => None,
+            Some(uv) => Some(resolve(uv, self.uvs.len(), line)?),
+        };
+        let normal = match parts.next() {
+            Some("") | None => None,
+            Some(normal) => Some(resolve(normal, self.normals.len(), line)?),
+        };
+        if parts.next().is_some() {
+            return Err(ObjError::Parse(line));
+        }
+        Ok(ObjCorner {
+            position,
+            uv,
+            normal,
+        })
+    }
+
+    /// Writes this as OBJ data.
+    pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+
+        for p in &self.positions {
+            writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        for uv in &self.uvs {
+            writeln!(writer, "vt {} {}", uv.x, uv.y)?;
+        }
+        for n in &self.normals {
+            writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+
+        for p in &self.points {
+            writeln!(writer, "p {}", p + 1)?;
+        }
+        for polyline in &self.lines {
+            let indexes = polyline
+                .iter()
+                .map(|i| (i + 1).to_string())
+                .collect::<Vec<_>>();
+            writeln!(writer, "l {}", indexes.join(" "))?;
+        }
+
+        let mut group = None;
+        for face in &self.faces {
+            if face.group != group {
+                group = face.group;
+                writeln!(writer, "g {}", group.map_or("default", |g| &self.groups[g]))?;
+            }
+
+            let corners = face
+                .corners
+                .iter()
+                .map(|c| match (c.uv, c.normal) {
+                    (None, None) => format!("{}", c.position + 1),
+                    (Some(uv), None) => format!("{}/{}", c.position + 1, uv + 1),
+                    (None, Some(n)) => format!("{}//{}", c.position + 1, n + 1),
+                    (Some(uv), Some(n)) => format!("{}/{}/{}", c.position + 1, uv + 1, n + 1),
+                })
+                .collect::<Vec<_>>();
+            writeln!(writer, "f {}", corners.join(" "))?;
+        }
+
+        writer.flush()
+    }
+
+    /// Converts a mesh into OBJ data.
+    /// Triangles become faces and edges that aren't part of a triangle become lines.
+    ///
+    /// # Panics
+    /// Panics if this is a tet mesh, because those and OBJ don't mix.
+    pub fn from_mesh<M>(mesh: &M) -> Self
+    where
+        M: HasPosition3D,
+        M::V: Position<Dim = U3>,
+    {
+        let vertices = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        let v_inv = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (*v, i))
+            .collect::<FnvHashMap<_, _>>();
+
+        let mut obj = Obj {
+            positions: vertices.into_iter().map(|v| mesh.position(v)).collect(),
+            ..Obj::default()
+        };
+        mesh.obj_with_vertices_higher::<Key>(&mut obj, &v_inv);
+        obj
+    }
+
+    /// Adds the contents of this to a mesh, using default values for the new elements,
+    /// and returns the data that doesn't fit in the mesh.
+    /// Fails without changing the mesh if a face or polyline is too small
+    /// or refers to something that doesn't exist.
+    pub fn add_to_mesh<M>(&self, mesh: &mut M) -> Result<ObjAttributes, ObjError>
+    where
+        M: HasPosition3D,
+        M::V: Position<Dim = U3>,
+    {
+        self.check()?;
+
+        let ids = mesh.extend_vertices(
+            self.positions
+                .iter()
+                .map(|p| mesh.default_vertex().with_position(*p))
+                .collect::<Vec<_>>(),
+        );
+
+        let mut attributes = ObjAttributes::default();
+        for corner in self.faces.iter().flat_map(|f| &f.corners) {
+            let id = ids[corner.position];
+            if let Some(uv) = corner.uv {
+                attributes.uvs.entry(id).or_insert(self.uvs[uv]);
+            }
+            if let Some(normal) = corner.normal {
+                attributes.normals.entry(id).or_insert(self.normals[normal]);
+            }
+        }
+
+        mesh.obj_add_vertices_higher::<Key>(self, &ids, &mut attributes);
+        Ok(attributes)
+    }
+
+    /// Checks that the faces and polylines are big enough and only refer to things that exist.
+    fn check(&self) -> Result<(), ObjError> {
+        for (i, face) in self.faces.iter().enumerate() {
+            let valid = face.corners.len() >= 3
+                && face.corners.iter().all(|c| {
+                    c.position < self.positions.len()
+                        && c.uv.map_or(true, |uv| uv < self.uvs.len())
+                        && c.normal.map_or(true, |normal| normal < self.normals.len())
+                });
+            if !valid {
+                return Err(ObjError::InvalidFace(i));
+            }
+        }
+
+        for (i, polyline) in self.lines.iter().enumerate() {
+            if polyline.len() < 2 || polyline.iter().any(|p| *p >= self.positions.len()) {
+                return Err(ObjError::InvalidLine(i));
+            }
+        }
+        Ok(())
+    }
+
+    /// The edges of the polylines in both directions, since lines aren't directed,
+    /// skipping ones that start and end at the same vertex
+    pub(crate) fn line_edges<'a>(
+        &'a self,
+        ids: &'a [VertexId],
+    ) -> impl Iterator<Item = EdgeId> + 'a {
+        self.lines.iter().flat_map(move |polyline| {
+            polyline.windows(2).flat_map(move |w| {
+                EdgeId::try_from([ids[w[0]], ids[w[1]]])
+                    .ok()
+                    .into_iter()
+                    .flat_map(|edge| vec![edge, edge.twin()])
+            })
+        })
+    }
+
+    /// The boundary edges of the faces, skipping ones that start and end at the same vertex
+    pub(crate) fn face_edges<'a>(
+        &'a self,
+        ids: &'a [VertexId],
+    ) -> impl Iterator<Item = EdgeId> + 'a {
+        self.faces.iter().flat_map(move |face| {
+            let n = face.corners.len();
+            (0..n).flat_map(move |i| {
+                EdgeId::try_from([
+                    ids[face.corners[i].position],
+                    ids[face.corners[(i + 1) % n].position],
+                ])
+                .ok()
+            })
+        })
+    }
+
+    /// The fan triangulations of the faces, with their groups, skipping degenerate triangles
+    pub(crate) fn face_tris<'a>(
+        &'a self,
+        ids: &'a [VertexId],
+    ) -> impl Iterator<Item = (TriId, Option<usize>)> + 'a {
+        self.faces.iter().flat_map(move |face| {
+            let c = &face.corners;
+            (1..c.len().saturating_sub(1)).flat_map(move |i| {
+                TriId::try_from([
+                    ids[c[0].position],
+                    ids[c[i].position],
+                    ids[c[i + 1].position],
+                ])
+                .ok()
+                .map(|tri| (tri, face.group))
+            })
+        })
+    }
+}
+
+#[cfg(feature = "obj")]
+impl From<::obj::ObjData> for Obj {
+    /// Converts data read by the `obj` crate.
+    /// Polygons with 1 or 2 vertices become points or lines.
+    fn from(data: ::obj::ObjData) -> Self {
+        let mut obj = Obj {
+            positions: data
+                .position
+                .iter()
+                .map(|[x, y, z]| Point3::new(*x as f64, *y as f64, *z as f64))
+                .collect(),
+            uvs: data
+                .texture
+                .iter()
+                .map(|[u, v]| Vector2::new(*u as f64, *v as f64))
+                .collect(),
+            normals: data
+                .normal
+                .iter()
+                .map(|[x, y, z]| Vector3::new(*x as f64, *y as f64, *z as f64))
+                .collect(),
+            ..Obj::default()
+        };
+
+        for group in data.objects.into_iter().flat_map(|object| object.groups) {
+            let index = obj.group_index(group.name);
+            for poly in group.polys {
+                let corners = poly
+                    .0
+                    .iter()
+                    .map(|t| ObjCorner {
+                        position: t.0,
+                        uv: t.1,
+                        normal: t.2,
+                    })
+                    .collect::<Vec<_>>();
+                match corners.len() {
+                    0 => {}
+                    1 => obj.points.push(corners[0].position),
+                    2 => obj.lines.push(corners.iter().map(|c| c.position).collect()),
+                    _ => obj.faces.push(ObjFace {
+                        corners,
+                        group: index,
+                    }),
+                }
+            }
+        }
+        obj
+    }
+}
+
+#[cfg(feature = "obj")]
+impl From<&Obj> for ::obj::ObjData {
+    /// Converts into data for the `obj` crate, with positions rounded to `f32`.
+    /// Points and the segments of lines become polygons with 1 and 2 vertices.
+    fn from(obj: &Obj) -> Self {
+        use ::obj::{Group, IndexTuple, Object, SimplePolygon};
+
+        let group = |name: &str| Group {
+            name: name.to_owned(),
+            index: 0,
+            material: None,
+            polys: vec![],
+        };
+        let mut groups = vec![group("default")];
+        groups.extend(obj.groups.iter().map(|name| group(name)));
+
+        groups[0].polys.extend(
+            obj.points
+                .iter()
+                .map(|p| SimplePolygon(vec![IndexTuple(*p, None, None)])),
+        );
+        groups[0]
+            .polys
+            .extend(obj.lines.iter().flat_map(|polyline| {
+                polyline
+                    .windows(2)
+                    .map(|w| {
+                        SimplePolygon(vec![
+                            IndexTuple(w[0], None, None),
+                            IndexTuple(w[1], None, None),
+                        ])
+                    })
+                    .collect::<Vec<_>>()
+            }));
+        for face in &obj.faces {
+            groups[face.group.map_or(0, |g| g + 1)]
+                .polys
+                .push(SimplePolygon(
+                    face.corners
+                        .iter()
+                        .map(|c| IndexTuple(c.position, c.uv, c.normal))
+                        .collect(),
+                ));
+        }
+
+        ::obj::ObjData {
+            position: obj
+                .positions
+                .iter()
+                .map(|p| [p.x as f32, p.y as f32, p.z as f32])
+                .collect(),
+            texture: obj
+                .uvs
+                .iter()
+                .map(|uv| [uv.x as f32, uv.y as f32])
+                .collect(),
+            normal: obj
+                .normals
+                .iter()
+                .map(|n| [n.x as f32, n.y as f32, n.z as f32])
+                .collect(),
+            objects: vec![Object {
+                name: "Mesh".to_owned(),
+                groups,
+            }],
+            material_libs: vec![],
+        }
+    }
+}
+
+/// Reads OBJ data into a mesh, using default values for the new elements,
+/// and returns the data that doesn't fit in the mesh.
+pub fn read_obj<M, R>(mesh: &mut M, reader: R) -> Result<ObjAttributes, ObjError>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+    R: Read,
+{
+    Obj::read(reader)?.add_to_mesh(mesh)
+}
+
+/// Reads an OBJ file into a mesh, using default values for the new elements,
+/// and returns the data that doesn't fit in the mesh.
+pub fn load_obj<M, P>(mesh: &mut M, path: P) -> Result<ObjAttributes, ObjError>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+    P: AsRef<Path>,
+{
+    read_obj(mesh, File::open(path)?)
+}
+
+/// Writes a mesh as OBJ data.
+///
+/// # Panics
+/// Panics if this is a tet mesh, because those and OBJ don't mix.
+pub fn write_obj<M, W>(mesh: &M, writer: W) -> io::Result<()>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+    W: Write,
+{
+    Obj::from_mesh(mesh).write(writer)
+}
+
+/// Writes a mesh as an OBJ file.
+///
+/// # Panics
+/// Panics if this is a tet mesh, because those and OBJ don't mix.
+pub fn save_obj<M, P>(mesh: &M, path: P) -> io::Result<()>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+    P: AsRef<Path>,
+{
+    write_obj(mesh, File::create(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::HasEdges;
+    use crate::mesh2::MwbComboMesh2;
+    use crate::tri::HasTris;
+    use crate::vertex::HasVertices;
+    use crate::{ComboMesh0, ComboMesh1, ComboMesh2};
+
+    const SQUARE: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+g top
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+    #[test]
+    fn test_read_polygon_fan() {
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let attributes = read_obj(&mut mesh, SQUARE.as_bytes()).unwrap();
+        let ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+
+        assert_eq!(mesh.num_vertices(), 4);
+        assert_eq!(mesh.num_tris(), 2);
+        assert!(mesh.contains_tri([ids[0], ids[1], ids[2]]));
+        assert!(mesh.contains_tri([ids[0], ids[2], ids[3]]));
+        assert_eq!(attributes.tri_groups.len(), 2);
+        assert!(attributes.tri_groups.values().all(|g| g == "top"));
+        assert_eq!(attributes.uvs[&ids[2]], Vector2::new(1.0, 1.0));
+        assert_eq!(attributes.normals[&ids[3]], Vector3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_read_into_lower_dimensions() {
+        let mut mesh = ComboMesh1::<Point3<f64>, ()>::with_defaults(Point3::origin, || ());
+        read_obj(&mut mesh, SQUARE.as_bytes()).unwrap();
+        assert_eq!(mesh.num_edges(), 4);
+
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        read_obj(&mut mesh, SQUARE.as_bytes()).unwrap();
+        assert_eq!(mesh.num_vertices(), 4);
+    }
+
+    #[test]
+    fn test_read_lines_and_relative_indexes() {
+        let data = "v 0 0 0\nv 1 0 0\nv 2 0 0\nl -3 -2 -1\np 1\n";
+        let obj = Obj::read(data.as_bytes()).unwrap();
+        assert_eq!(obj.lines, vec![vec![0, 1, 2]]);
+        assert_eq!(obj.points, vec![0]);
+
+        // Lines aren't directed, so they get edges both ways
+        let mut mesh = ComboMesh1::<Point3<f64>, ()>::with_defaults(Point3::origin, || ());
+        obj.add_to_mesh(&mut mesh).unwrap();
+        let ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        assert_eq!(mesh.num_edges(), 4);
+        assert!(mesh.contains_edge([ids[0], ids[1]]));
+        assert!(mesh.contains_edge([ids[1], ids[0]]));
+        assert!(mesh.contains_edge([ids[2], ids[1]]));
+    }
+
+    #[test]
+    fn test_read_skipped_tris() {
+        // The second face uses edge 1-2 the same way as the first, which a mwb mesh can't have
+        let data = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 1\nf 1 2 3\nf 4 2 3\nf 1 2 3\n";
+        let mut mesh =
+            MwbComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let attributes = read_obj(&mut mesh, data.as_bytes()).unwrap();
+        let ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        assert_eq!(mesh.num_tris(), 1);
+        assert_eq!(
+            attributes.skipped_tris,
+            vec![TriId::try_from([ids[3], ids[1], ids[2]]).unwrap()]
+        );
+        assert!(attributes.skipped_edges.is_empty());
+
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let attributes = read_obj(&mut mesh, data.as_bytes()).unwrap();
+        assert_eq!(mesh.num_tris(), 2);
+        assert!(attributes.skipped_tris.is_empty());
+    }
+
+    #[test]
+    fn test_read_errors() {
+        assert!(matches!(
+            Obj::read("v 0 0 0\nv 1 0 0\nf 1 2 3\n".as_bytes()),
+            Err(ObjError::IndexOutOfRange(3))
+        ));
+        assert!(matches!(
+            Obj::read("v 0 0 0\nl 1 0\n".as_bytes()),
+            Err(ObjError::IndexOutOfRange(2))
+        ));
+        assert!(matches!(
+            Obj::read("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1/2 2 3\n".as_bytes()),
+            Err(ObjError::IndexOutOfRange(4))
+        ));
+        assert!(matches!(
+            Obj::read("v 0 zero 0\n".as_bytes()),
+            Err(ObjError::Parse(1))
+        ));
+    }
+
+    #[test]
+    fn test_add_invalid_to_mesh() {
+        let mut obj = Obj::read(SQUARE.as_bytes()).unwrap();
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+
+        obj.faces[0].corners[1].normal = Some(1);
+        assert!(matches!(
+            obj.add_to_mesh(&mut mesh),
+            Err(ObjError::InvalidFace(0))
+        ));
+        obj.faces[0].corners[1].normal = None;
+        obj.faces.push(ObjFace {
+            corners: vec![],
+            group: None,
+        });
+        assert!(matches!(
+            obj.add_to_mesh(&mut mesh),
+            Err(ObjError::InvalidFace(1))
+        ));
+        obj.faces.pop();
+        obj.lines.push(vec![0, 4]);
+        assert!(matches!(
+            obj.add_to_mesh(&mut mesh),
+            Err(ObjError::InvalidLine(0))
+        ));
+        assert_eq!(mesh.num_vertices(), 0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(5.0, 5.0, 5.0),
+        ]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], ());
+        mesh.add_tri([ids[0], ids[3], ids[1]], ());
+        mesh.add_edge([ids[3], ids[4]], ());
+
+        let mut bytes = vec![];
+        write_obj(&mesh, &mut bytes).unwrap();
+        let obj = Obj::read(&bytes[..]).unwrap();
+        assert_eq!(obj, Obj::from_mesh(&mesh));
+        assert_eq!(obj.faces.len(), 2);
+        assert_eq!(obj.lines.len(), 1);
+
+        // The isolated edge comes back both ways
+        let mut read =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        obj.add_to_mesh(&mut read).unwrap();
+        assert_eq!(read.num_vertices(), 5);
+        assert_eq!(read.num_tris(), 2);
+        assert_eq!(read.num_edges(), mesh.num_edges() + 1);
+    }
+
+    #[test]
+    #[cfg(feature = "obj")]
+    fn test_obj_data_conversion() {
+        let data = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0.5\nvt 0 1\nvn 0 0 1\np 4\nl 3 4\nf 1 2/1 3//1\ng top\nf 1 3 4\n";
+        let obj = Obj::read(data.as_bytes()).unwrap();
+        let converted = Obj::from(::obj::ObjData::from(&obj));
+        assert_eq!(converted, obj);
+    }
+}
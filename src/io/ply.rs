@@ -0,0 +1,776 @@
+//! Reading and writing PLY files, in ASCII and in little- or big-endian binary.
+//!
+//! Vertex and face properties get converted to and from vertex and triangle values
+//! through the `PlyElement` trait. Scalar properties are passed around as `f64`,
+//! which holds every PLY scalar type exactly.
+//! Faces become fan-triangulated triangles that all get a value from the face's properties.
+//! Elements other than `vertex` and `face` are skipped.
+
+use nalgebra::Point3;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::SplitAsciiWhitespace;
+
+use crate::tri::{HasTris, TriId};
+use crate::vertex::VertexId;
+
+/// Errors from reading a PLY file
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    /// A header line couldn't be parsed. Contains the 1-based line number.
+    Header(usize),
+    /// A value in the body couldn't be parsed.
+    Parse,
+    /// The body ended before all elements were read.
+    Truncated,
+    /// The face element has no `vertex_indices` list.
+    MissingIndices,
+    /// A property needed to construct a value isn't in the file.
+    MissingProperty(String),
+    /// A face refers to a vertex that doesn't exist. Contains the face's index.
+    IndexOutOfRange(usize),
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlyError::Io(err) => write!(f, "I/O error in PLY file: {}", err),
+            PlyError::Header(line) => write!(f, "can't parse PLY header on line {}", line),
+            PlyError::Parse => write!(f, "can't parse PLY value"),
+            PlyError::Truncated => write!(f, "PLY file is truncated"),
+            PlyError::MissingIndices => write!(f, "PLY faces have no vertex indices"),
+            PlyError::MissingProperty(name) => write!(f, "PLY property {} is missing", name),
+            PlyError::IndexOutOfRange(face) => {
+                write!(f, "PLY face {} has a vertex index out of range", face)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PlyError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PlyError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            PlyError::Truncated
+        } else {
+            PlyError::Io(err)
+        }
+    }
+}
+
+/// The encoding of the body of a PLY file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// What reading a PLY file gives besides the mesh itself
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlyAttributes {
+    /// The new vertices, in file order
+    pub vertices: Vec<VertexId>,
+    /// The indexes of the faces in the file that weren't added in full,
+    /// because they're degenerate
+    /// or some of their triangles would break the manifoldness of a mwb mesh
+    pub skipped_faces: Vec<usize>,
+}
+
+/// The type of a scalar PLY property
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+impl PlyType {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => PlyType::Char,
+            "uchar" | "uint8" => PlyType::UChar,
+            "short" | "int16" => PlyType::Short,
+            "ushort" | "uint16" => PlyType::UShort,
+            "int" | "int32" => PlyType::Int,
+            "uint" | "uint32" => PlyType::UInt,
+            "float" | "float32" => PlyType::Float,
+            "double" | "float64" => PlyType::Double,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            PlyType::Char => "char",
+            PlyType::UChar => "uchar",
+            PlyType::Short => "short",
+            PlyType::UShort => "ushort",
+            PlyType::Int => "int",
+            PlyType::UInt => "uint",
+            PlyType::Float => "float",
+            PlyType::Double => "double",
+        }
+    }
+
+    fn read_binary<R: Read>(self, reader: &mut R, big_endian: bool) -> io::Result<f64> {
+        macro_rules! read_as {
+            ($t:ty) => {{
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                reader.read_exact(&mut bytes)?;
+                if big_endian {
+                    <$t>::from_be_bytes(bytes) as f64
+                } else {
+                    <$t>::from_le_bytes(bytes) as f64
+                }
+            }};
+        }
+
+        Ok(match self {
+            PlyType::Char => read_as!(i8),
+            PlyType::UChar => read_as!(u8),
+            PlyType::Short => read_as!(i16),
+            PlyType::UShort => read_as!(u16),
+            PlyType::Int => read_as!(i32),
+            PlyType::UInt => read_as!(u32),
+            PlyType::Float => read_as!(f32),
+            PlyType::Double => read_as!(f64),
+        })
+    }
+
+    fn write<W: Write>(self, writer: &mut W, value: f64, format: PlyFormat) -> io::Result<()> {
+        macro_rules! write_as {
+            ($t:ty) => {{
+                let value = value as $t;
+                match format {
+                    PlyFormat::Ascii => write!(writer, "{}", value),
+                    PlyFormat::BinaryLittleEndian => writer.write_all(&value.to_le_bytes()),
+                    PlyFormat::BinaryBigEndian => writer.write_all(&value.to_be_bytes()),
+                }
+            }};
+        }
+
+        match self {
+            PlyType::Char => write_as!(i8),
+            PlyType::UChar => write_as!(u8),
+            PlyType::Short => write_as!(i16),
+            PlyType::UShort => write_as!(u16),
+            PlyType::Int => write_as!(i32),
+            PlyType::UInt => write_as!(u32),
+            PlyType::Float => write_as!(f32),
+            PlyType::Double => write_as!(f64),
+        }
+    }
+}
+
+/// The scalar properties of one vertex or face
+#[derive(Clone, Copy, Debug)]
+pub struct PlyRecord<'a> {
+    names: &'a [String],
+    values: &'a [f64],
+}
+
+impl<'a> PlyRecord<'a> {
+    /// Gets the value of a property, or None if there's no property with that name.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|i| self.values[i])
+    }
+
+    /// Gets the value of a property, or an error if there's no property with that name.
+    pub fn require(&self, name: &str) -> Result<f64, PlyError> {
+        self.get(name)
+            .ok_or_else(|| PlyError::MissingProperty(name.to_owned()))
+    }
+}
+
+/// For values that can be converted to and from the scalar properties of a PLY element
+pub trait PlyElement: Sized {
+    /// The names and types of the properties this gets written as
+    fn ply_properties() -> Vec<(String, PlyType)>;
+
+    /// The values of the properties, in the order of `ply_properties`
+    fn to_ply(&self) -> Vec<f64>;
+
+    /// Constructs a value from the properties of an element
+    fn from_ply(record: PlyRecord) -> Result<Self, PlyError>;
+}
+
+impl PlyElement for () {
+    fn ply_properties() -> Vec<(String, PlyType)> {
+        vec![]
+    }
+
+    fn to_ply(&self) -> Vec<f64> {
+        vec![]
+    }
+
+    fn from_ply(_: PlyRecord) -> Result<Self, PlyError> {
+        Ok(())
+    }
+}
+
+impl PlyElement for Point3<f64> {
+    fn ply_properties() -> Vec<(String, PlyType)> {
+        ["x", "y", "z"]
+            .iter()
+            .map(|n| (n.to_string(), PlyType::Double))
+            .collect()
+    }
+
+    fn to_ply(&self) -> Vec<f64> {
+        vec![self.x, self.y, self.z]
+    }
+
+    fn from_ply(record: PlyRecord) -> Result<Self, PlyError> {
+        Ok(Point3::new(
+            record.require("x")?,
+            record.require("y")?,
+            record.require("z")?,
+        ))
+    }
+}
+
+/// For positioned values, like the vertices of a `Mesh23`
+impl<T: PlyElement> PlyElement for (Point3<f64>, T) {
+    fn ply_properties() -> Vec<(String, PlyType)> {
+        let mut properties = Point3::ply_properties();
+        properties.extend(T::ply_properties());
+        properties
+    }
+
+    fn to_ply(&self) -> Vec<f64> {
+        let mut values = self.0.to_ply();
+        values.extend(self.1.to_ply());
+        values
+    }
+
+    fn from_ply(record: PlyRecord) -> Result<Self, PlyError> {
+        Ok((Point3::from_ply(record)?, T::from_ply(record)?))
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar(String, PlyType),
+    List(String, PlyType, PlyType),
+}
+
+#[derive(Debug)]
+struct ElementDef {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// The values of all records of an element
+#[derive(Debug, Default)]
+struct ElementData {
+    names: Vec<String>,
+    scalars: Vec<Vec<f64>>,
+    /// The `vertex_indices` list of each record, if there is one
+    indices: Vec<Vec<f64>>,
+}
+
+impl ElementData {
+    fn record(&self, i: usize) -> PlyRecord<'_> {
+        PlyRecord {
+            names: &self.names,
+            values: &self.scalars[i],
+        }
+    }
+}
+
+fn read_header<R: BufRead>(reader: &mut R) -> Result<(PlyFormat, Vec<ElementDef>), PlyError> {
+    let mut format = None;
+    let mut elements = Vec::<ElementDef>::new();
+
+    for line in 1.. {
+        let mut text = String::new();
+        if reader.read_line(&mut text)? == 0 {
+            return Err(PlyError::Header(line));
+        }
+        let words = text.split_whitespace().collect::<Vec<_>>();
+
+        match words[..] {
+            ["ply"] if line == 1 => {}
+            _ if line == 1 => return Err(PlyError::Header(line)),
+            ["format", f, _] => {
+                format = Some(match f {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(PlyError::Header(line)),
+                })
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["element", name, count] => elements.push(ElementDef {
+                name: name.to_owned(),
+                count: count.parse().map_err(|_| PlyError::Header(line))?,
+                properties: vec![],
+            }),
+            ["property", "list", count, item, name] => {
+                let count = PlyType::from_name(count).ok_or(PlyError::Header(line))?;
+                let item = PlyType::from_name(item).ok_or(PlyError::Header(line))?;
+                elements
+                    .last_mut()
+                    .ok_or(PlyError::Header(line))?
+                    .properties
+                    .push(Property::List(name.to_owned(), count, item));
+            }
+            ["property", ty, name] => {
+                let ty = PlyType::from_name(ty).ok_or(PlyError::Header(line))?;
+                elements
+                    .last_mut()
+                    .ok_or(PlyError::Header(line))?
+                    .properties
+                    .push(Property::Scalar(name.to_owned(), ty));
+            }
+            ["end_header"] => return Ok((format.ok_or(PlyError::Header(line))?, elements)),
+            _ => return Err(PlyError::Header(line)),
+        }
+    }
+    unreachable!()
+}
+
+/// A place to read scalars of the body from
+trait Scalars {
+    fn next(&mut self, ty: PlyType) -> Result<f64, PlyError>;
+}
+
+struct AsciiScalars<'a>(SplitAsciiWhitespace<'a>);
+
+impl<'a> Scalars for AsciiScalars<'a> {
+    fn next(&mut self, _: PlyType) -> Result<f64, PlyError> {
+        self.0
+            .next()
+            .ok_or(PlyError::Truncated)?
+            .parse()
+            .map_err(|_| PlyError::Parse)
+    }
+}
+
+struct BinaryScalars<R> {
+    reader: R,
+    big_endian: bool,
+}
+
+impl<R: Read> Scalars for BinaryScalars<R> {
+    fn next(&mut self, ty: PlyType) -> Result<f64, PlyError> {
+        Ok(ty.read_binary(&mut self.reader, self.big_endian)?)
+    }
+}
+
+fn read_element<S: Scalars>(scalars: &mut S, def: &ElementDef) -> Result<ElementData, PlyError> {
+    let mut data = ElementData {
+        names: def
+            .properties
+            .iter()
+            .flat_map(|p| match p {
+                Property::Scalar(name, _) => Some(name.clone()),
+                Property::List(..) => None,
+            })
+            .collect(),
+        ..ElementData::default()
+    };
+
+    for _ in 0..def.count {
+        let mut values = vec![];
+        for property in &def.properties {
+            match property {
+                Property::Scalar(_, ty) => values.push(scalars.next(*ty)?),
+                Property::List(name, count, item) => {
+                    let count = scalars.next(*count)?;
+                    if count < 0.0 {
+                        return Err(PlyError::Parse);
+                    }
+                    let list = (0..count as usize)
+                        .map(|_| scalars.next(*item))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if name == "vertex_indices" || name == "vertex_index" {
+                        data.indices.push(list);
+                    }
+                }
+            }
+        }
+        data.scalars.push(values);
+    }
+
+    Ok(data)
+}
+
+/// Reads PLY data into a mesh. Vertex values are made from the `vertex` element
+/// and triangle values from the `face` element. Faces get fan-triangulated;
+/// degenerate triangles and triangles that conflict with the mesh's manifoldness are skipped.
+/// New edges get the default value. If there's an error, the mesh is left unchanged.
+///
+/// Returns the new vertices and the faces that weren't added in full.
+pub fn read_ply<M, R>(mesh: &mut M, reader: R) -> Result<PlyAttributes, PlyError>
+where
+    M: HasTris,
+    M::V: PlyElement,
+    M::F: PlyElement,
+    R: Read,
+{
+    let mut reader = BufReader::new(reader);
+    let (format, defs) = read_header(&mut reader)?;
+
+    let elements = match format {
+        PlyFormat::Ascii => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            let mut scalars = AsciiScalars(text.split_ascii_whitespace());
+            defs.iter()
+                .map(|def| read_element(&mut scalars, def))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        _ => {
+            let mut scalars = BinaryScalars {
+                reader,
+                big_endian: format == PlyFormat::BinaryBigEndian,
+            };
+            defs.iter()
+                .map(|def| read_element(&mut scalars, def))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+    };
+    let element = |name: &str| {
+        defs.iter()
+            .position(|def| def.name == name)
+            .map(|i| &elements[i])
+    };
+
+    // Build everything before touching the mesh, so errors leave it alone
+    let vertices = element("vertex")
+        .map(|vertices| {
+            (0..vertices.scalars.len())
+                .map(|i| M::V::from_ply(vertices.record(i)))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut tris = vec![];
+    let mut attributes = PlyAttributes::default();
+    if let Some(faces) = element("face") {
+        if faces.indices.len() < faces.scalars.len() {
+            return Err(PlyError::MissingIndices);
+        }
+
+        for (i, indices) in faces.indices.iter().enumerate() {
+            let face = indices
+                .iter()
+                .map(|index| {
+                    Some(*index as usize)
+                        .filter(|v| *index >= 0.0 && *v < vertices.len())
+                        .ok_or(PlyError::IndexOutOfRange(i))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let num_tris = tris.len();
+            for j in 1..face.len().saturating_sub(1) {
+                let tri = [face[0], face[j], face[j + 1]];
+                if tri[0] != tri[1] && tri[1] != tri[2] && tri[2] != tri[0] {
+                    tris.push((i, tri, M::F::from_ply(faces.record(i))?));
+                }
+            }
+            if tris.len() == num_tris {
+                attributes.skipped_faces.push(i);
+            }
+        }
+    }
+
+    let ids = vertices
+        .into_iter()
+        .map(|value| mesh.add_vertex(value))
+        .collect::<Vec<_>>();
+    for (face, [v0, v1, v2], value) in tris {
+        if mesh
+            .try_add_tri(TriId::from_valid([ids[v0], ids[v1], ids[v2]]), value)
+            .is_err()
+            && !attributes.skipped_faces.contains(&face)
+        {
+            attributes.skipped_faces.push(face);
+        }
+    }
+    attributes.skipped_faces.sort_unstable();
+    attributes.vertices = ids;
+
+    Ok(attributes)
+}
+
+/// Reads a PLY file into a mesh. See `read_ply`.
+pub fn load_ply<M, P>(mesh: &mut M, path: P) -> Result<PlyAttributes, PlyError>
+where
+    M: HasTris,
+    M::V: PlyElement,
+    M::F: PlyElement,
+    P: AsRef<Path>,
+{
+    read_ply(mesh, File::open(path)?)
+}
+
+/// Writes the vertices and triangles of a mesh as PLY data.
+/// Edge values aren't written.
+pub fn write_ply<M, W>(mesh: &M, writer: W, format: PlyFormat) -> io::Result<()>
+where
+    M: HasTris,
+    M::V: PlyElement,
+    M::F: PlyElement,
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+    let v_properties = M::V::ply_properties();
+    let f_properties = M::F::ply_properties();
+
+    writeln!(writer, "ply")?;
+    let format_name = match format {
+        PlyFormat::Ascii => "ascii",
+        PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        PlyFormat::BinaryBigEndian => "binary_big_endian",
+    };
+    writeln!(writer, "format {} 1.0", format_name)?;
+    writeln!(writer, "element vertex {}", mesh.num_vertices())?;
+    for (name, ty) in &v_properties {
+        writeln!(writer, "property {} {}", ty.name(), name)?;
+    }
+    writeln!(writer, "element face {}", mesh.num_tris())?;
+    writeln!(writer, "property list uchar int vertex_indices")?;
+    for (name, ty) in &f_properties {
+        writeln!(writer, "property {} {}", ty.name(), name)?;
+    }
+    writeln!(writer, "end_header")?;
+
+    let separator = |writer: &mut BufWriter<W>, first: bool| -> io::Result<()> {
+        if format == PlyFormat::Ascii && !first {
+            write!(writer, " ")?;
+        }
+        Ok(())
+    };
+    let end_record = |writer: &mut BufWriter<W>| -> io::Result<()> {
+        if format == PlyFormat::Ascii {
+            writeln!(writer)?;
+        }
+        Ok(())
+    };
+
+    let mut v_indexes = fnv::FnvHashMap::default();
+    for (i, (id, value)) in mesh.vertices().enumerate() {
+        v_indexes.insert(*id, i);
+        for (j, ((_, ty), x)) in v_properties.iter().zip(value.to_ply()).enumerate() {
+            separator(&mut writer, j == 0)?;
+            ty.write(&mut writer, x, format)?;
+        }
+        end_record(&mut writer)?;
+    }
+
+    for (id, value) in mesh.tris() {
+        PlyType::UChar.write(&mut writer, 3.0, format)?;
+        for v in &id.0 {
+            separator(&mut writer, false)?;
+            PlyType::Int.write(&mut writer, v_indexes[v] as f64, format)?;
+        }
+        for ((_, ty), x) in f_properties.iter().zip(value.to_ply()) {
+            separator(&mut writer, false)?;
+            ty.write(&mut writer, x, format)?;
+        }
+        end_record(&mut writer)?;
+    }
+
+    writer.flush()
+}
+
+/// Writes the vertices and triangles of a mesh as a PLY file. See `write_ply`.
+pub fn save_ply<M, P>(mesh: &M, path: P, format: PlyFormat) -> io::Result<()>
+where
+    M: HasTris,
+    M::V: PlyElement,
+    M::F: PlyElement,
+    P: AsRef<Path>,
+{
+    write_ply(mesh, File::create(path)?, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh2::{ComboMesh2, Mesh23};
+    use crate::vertex::HasVertices;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Temperature(f64);
+
+    impl PlyElement for Temperature {
+        fn ply_properties() -> Vec<(String, PlyType)> {
+            vec![("temperature".to_owned(), PlyType::Float)]
+        }
+
+        fn to_ply(&self) -> Vec<f64> {
+            vec![self.0]
+        }
+
+        fn from_ply(record: PlyRecord) -> Result<Self, PlyError> {
+            Ok(Temperature(record.require("temperature")?))
+        }
+    }
+
+    const QUAD: &str = "\
+ply
+format ascii 1.0
+comment a unit square with a temperature field
+element vertex 4
+property float x
+property float y
+property float z
+property float temperature
+element face 1
+property list uchar int vertex_indices
+property uchar material
+end_header
+0 0 0 10
+1 0 0 20
+1 1 0 30
+0 1 0 40
+4 0 1 2 3 7
+";
+
+    #[test]
+    fn test_read_ascii() {
+        let mut mesh = Mesh23::<Temperature, (), ()>::with_defaults(
+            || (Point3::origin(), Temperature(0.0)),
+            || (),
+            || (),
+        );
+        let attributes = read_ply(&mut mesh, QUAD.as_bytes()).unwrap();
+        let ids = attributes.vertices;
+
+        assert!(attributes.skipped_faces.is_empty());
+        assert_eq!(ids.len(), 4);
+        assert_eq!(mesh.num_tris(), 2);
+        assert_eq!(
+            mesh.vertex(ids[2]),
+            Some(&(Point3::new(1.0, 1.0, 0.0), Temperature(30.0)))
+        );
+        assert!(mesh.contains_tri([ids[0], ids[2], ids[3]]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut mesh = ComboMesh2::<(Point3<f64>, Temperature), (), Temperature>::with_defaults(
+            || (Point3::origin(), Temperature(0.0)),
+            || (),
+            || Temperature(0.0),
+        );
+        let ids = mesh.extend_vertices(vec![
+            (Point3::new(0.0, 0.0, 0.0), Temperature(1.5)),
+            (Point3::new(1.0, 0.0, 0.0), Temperature(2.5)),
+            (Point3::new(0.0, 1.0, 0.0), Temperature(-3.5)),
+            (Point3::new(0.0, 0.0, 1.0), Temperature(4.5)),
+        ]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], Temperature(100.0));
+        mesh.add_tri([ids[0], ids[3], ids[1]], Temperature(200.0));
+
+        for format in [
+            PlyFormat::Ascii,
+            PlyFormat::BinaryLittleEndian,
+            PlyFormat::BinaryBigEndian,
+        ] {
+            let mut bytes = vec![];
+            write_ply(&mesh, &mut bytes, format).unwrap();
+
+            let mut read = ComboMesh2::<(Point3<f64>, Temperature), (), Temperature>::with_defaults(
+                || (Point3::origin(), Temperature(0.0)),
+                || (),
+                || Temperature(0.0),
+            );
+            let read_ids = read_ply(&mut read, &bytes[..]).unwrap().vertices;
+
+            for (id, read_id) in ids.iter().zip(&read_ids) {
+                assert_eq!(read.vertex(*read_id), mesh.vertex(*id));
+            }
+            assert_eq!(
+                read.tri([read_ids[0], read_ids[1], read_ids[2]]),
+                Some(&Temperature(100.0))
+            );
+            assert_eq!(
+                read.tri([read_ids[0], read_ids[3], read_ids[1]]),
+                Some(&Temperature(200.0))
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_skipped_faces() {
+        let mut mesh = Mesh23::<Temperature, (), ()>::with_defaults(
+            || (Point3::origin(), Temperature(0.0)),
+            || (),
+            || (),
+        );
+        // The second face repeats the first, and the third has no area
+        let faces = QUAD
+            .replace("element face 1", "element face 3")
+            .replace("4 0 1 2 3 7\n", "4 0 1 2 3 7\n3 0 1 2 8\n3 0 1 1 9\n");
+        let attributes = read_ply(&mut mesh, faces.as_bytes()).unwrap();
+
+        assert_eq!(attributes.vertices.len(), 4);
+        assert_eq!(attributes.skipped_faces, vec![1, 2]);
+        assert_eq!(mesh.num_tris(), 2);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let mut mesh = Mesh23::<Temperature, (), ()>::with_defaults(
+            || (Point3::origin(), Temperature(0.0)),
+            || (),
+            || (),
+        );
+
+        let missing = QUAD
+            .replace("property float temperature\n", "")
+            .replace(" 10\n", "\n");
+        assert!(matches!(
+            read_ply(&mut mesh, missing.as_bytes()),
+            Err(PlyError::MissingProperty(name)) if name == "temperature"
+        ));
+        assert_eq!(mesh.num_vertices(), 0);
+
+        let out_of_range = QUAD.replace("4 0 1 2 3", "4 0 1 2 4");
+        assert!(matches!(
+            read_ply(&mut mesh, out_of_range.as_bytes()),
+            Err(PlyError::IndexOutOfRange(0))
+        ));
+        assert_eq!(mesh.num_vertices(), 0);
+
+        let truncated = &QUAD[..QUAD.len() - 8];
+        assert!(matches!(
+            read_ply(&mut mesh, truncated.as_bytes()),
+            Err(PlyError::Truncated)
+        ));
+
+        assert!(matches!(
+            read_ply(
+                &mut mesh,
+                "ply\nformat ascii 1.0\nelement vertex x\n".as_bytes()
+            ),
+            Err(PlyError::Header(3))
+        ));
+    }
+}
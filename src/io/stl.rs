@@ -0,0 +1,413 @@
+//! Reading and writing STL files, both ASCII and binary.
+//!
+//! STL stores every triangle with its own copies of its corners,
+//! so reading welds corners with the exact same position into a single vertex.
+
+use fnv::FnvHashMap;
+use nalgebra::dimension::U3;
+use nalgebra::{Point3, Vector3};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::tri::{HasPositionAndTris, TriId};
+use crate::vertex::{HasPosition3D, Position, VertexId};
+
+/// Errors from reading an STL file
+#[derive(Debug)]
+pub enum StlError {
+    Io(io::Error),
+    /// A statement of an ASCII STL file couldn't be parsed. Contains the 1-based line number.
+    Parse(usize),
+    /// A binary STL file ended before all its triangles were read.
+    Truncated,
+}
+
+impl fmt::Display for StlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StlError::Io(err) => write!(f, "I/O error in STL file: {}", err),
+            StlError::Parse(line) => write!(f, "can't parse STL statement on line {}", line),
+            StlError::Truncated => write!(f, "binary STL file is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for StlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StlError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StlError {
+    fn from(err: io::Error) -> Self {
+        StlError::Io(err)
+    }
+}
+
+/// The encoding to write an STL file in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    Binary,
+}
+
+/// What reading an STL file gives besides the mesh itself
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StlAttributes {
+    /// The new vertices in order of first appearance
+    pub vertices: Vec<VertexId>,
+    /// The indexes of the triangles in the file that weren't added,
+    /// because they're degenerate or would break the manifoldness of a mwb mesh
+    pub skipped_tris: Vec<usize>,
+}
+
+/// Parses the triangles of STL data, detecting whether it's ASCII or binary.
+fn read_triangles<R: Read>(mut reader: R) -> Result<Vec<[Point3<f64>; 3]>, StlError> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+
+    // Some binary files start with "solid" too, so the size is a better check.
+    let binary_len = bytes
+        .get(80..84)
+        .map(|n| 84 + 50 * u32::from_le_bytes([n[0], n[1], n[2], n[3]]) as usize);
+    let is_ascii = binary_len != Some(bytes.len())
+        && bytes
+            .iter()
+            .skip_while(|b| b.is_ascii_whitespace())
+            .take(5)
+            .eq(b"solid");
+
+    if is_ascii {
+        read_ascii(&String::from_utf8_lossy(&bytes))
+    } else {
+        read_binary(&bytes)
+    }
+}
+
+fn read_ascii(text: &str) -> Result<Vec<[Point3<f64>; 3]>, StlError> {
+    let mut tris = vec![];
+    let mut corners = vec![];
+
+    for (i, text) in text.lines().enumerate() {
+        let line = i + 1;
+        let mut words = text.split_whitespace();
+
+        match words.next() {
+            Some("vertex") => {
+                let coords = words
+                    .map(|w| w.parse::<f64>().map_err(|_| StlError::Parse(line)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match coords[..] {
+                    [x, y, z] => corners.push(Point3::new(x, y, z)),
+                    _ => return Err(StlError::Parse(line)),
+                }
+            }
+
+            Some("endloop") => match corners[..] {
+                [a, b, c] => {
+                    tris.push([a, b, c]);
+                    corners.clear();
+                }
+                _ => return Err(StlError::Parse(line)),
+            },
+
+            Some("solid") | Some("facet") | Some("outer") | Some("endfacet") | Some("endsolid")
+            | None => {}
+
+            Some(_) => return Err(StlError::Parse(line)),
+        }
+    }
+
+    Ok(tris)
+}
+
+fn read_binary(bytes: &[u8]) -> Result<Vec<[Point3<f64>; 3]>, StlError> {
+    let float = |b: &[u8]| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64;
+    let point = |b: &[u8]| Point3::new(float(&b[0..4]), float(&b[4..8]), float(&b[8..12]));
+
+    let count = bytes.get(80..84).ok_or(StlError::Truncated)?;
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+
+    (0..count)
+        .map(|i| {
+            // Skip the normal; it gets recomputed from the positions anyway.
+            let facet = bytes
+                .get(84 + 50 * i..84 + 50 * (i + 1))
+                .ok_or(StlError::Truncated)?;
+            Ok([
+                point(&facet[12..24]),
+                point(&facet[24..36]),
+                point(&facet[36..48]),
+            ])
+        })
+        .collect()
+}
+
+/// Reads STL data into a mesh, using default values for the new elements.
+/// Corners with the same position become the same vertex.
+/// Degenerate triangles and triangles that conflict with the mesh's manifoldness are skipped,
+/// and corners that only they use don't become vertices.
+///
+/// Returns the new vertices and the skipped triangles.
+pub fn read_stl<M, R>(mesh: &mut M, reader: R) -> Result<StlAttributes, StlError>
+where
+    M: HasPosition3D + HasPositionAndTris,
+    M::V: Position<Dim = U3>,
+    R: Read,
+{
+    let tris = read_triangles(reader)?;
+
+    // Positions are welded by bits, with -0 and 0 counting as the same.
+    let key = |p: &Point3<f64>| [p.x + 0.0, p.y + 0.0, p.z + 0.0].map(f64::to_bits);
+    let mut welded = FnvHashMap::default();
+    let mut attributes = StlAttributes::default();
+
+    for (i, corners) in tris.iter().enumerate() {
+        let keys = corners.map(|p| key(&p));
+        if keys[0] == keys[1] || keys[1] == keys[2] || keys[2] == keys[0] {
+            attributes.skipped_tris.push(i);
+            continue;
+        }
+
+        let mut added = vec![];
+        let ids = [0, 1, 2].map(|j| {
+            welded.get(&keys[j]).copied().unwrap_or_else(|| {
+                let id = mesh.add_vertex(mesh.default_vertex().with_position(corners[j]));
+                added.push((keys[j], id));
+                id
+            })
+        });
+
+        match mesh.try_add_tri(TriId::from_valid(ids), mesh.default_tri()) {
+            Ok(_) => {
+                for (key, id) in added {
+                    welded.insert(key, id);
+                    attributes.vertices.push(id);
+                }
+            }
+            Err(_) => {
+                for (_, id) in added {
+                    mesh.remove_vertex(id);
+                }
+                attributes.skipped_tris.push(i);
+            }
+        }
+    }
+
+    Ok(attributes)
+}
+
+/// Reads an STL file into a mesh. See `read_stl`.
+pub fn load_stl<M, P>(mesh: &mut M, path: P) -> Result<StlAttributes, StlError>
+where
+    M: HasPosition3D + HasPositionAndTris,
+    M::V: Position<Dim = U3>,
+    P: AsRef<Path>,
+{
+    read_stl(mesh, File::open(path)?)
+}
+
+/// Writes the triangles of a mesh as STL data.
+/// Facet normals are computed from the positions, and are zero for degenerate triangles.
+pub fn write_stl<M, W>(mesh: &M, writer: W, format: StlFormat) -> io::Result<()>
+where
+    M: HasPosition3D + HasPositionAndTris,
+    M::V: Position<Dim = U3>,
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+    let facets = mesh.tri_ids().map(|tri| {
+        let [a, b, c]: [Point3<f64>; 3] = mesh.tri_positions(*tri);
+        let normal = (b - a)
+            .cross(&(c - a))
+            .try_normalize(0.0)
+            .unwrap_or_else(Vector3::zeros);
+        (normal, [a, b, c])
+    });
+
+    match format {
+        StlFormat::Ascii => {
+            writeln!(writer, "solid mesh")?;
+            for (n, corners) in facets {
+                writeln!(writer, "  facet normal {} {} {}", n.x, n.y, n.z)?;
+                writeln!(writer, "    outer loop")?;
+                for p in &corners {
+                    writeln!(writer, "      vertex {} {} {}", p.x, p.y, p.z)?;
+                }
+                writeln!(writer, "    endloop")?;
+                writeln!(writer, "  endfacet")?;
+            }
+            writeln!(writer, "endsolid mesh")?;
+        }
+
+        StlFormat::Binary => {
+            writer.write_all(&[0; 80])?;
+            writer.write_all(&(mesh.num_tris() as u32).to_le_bytes())?;
+            for (n, corners) in facets {
+                for v in std::iter::once(n).chain(corners.iter().map(|p| p.coords)) {
+                    for x in v.iter() {
+                        writer.write_all(&(*x as f32).to_le_bytes())?;
+                    }
+                }
+                writer.write_all(&[0; 2])?;
+            }
+        }
+    }
+
+    writer.flush()
+}
+
+/// Writes the triangles of a mesh as an STL file. See `write_stl`.
+pub fn save_stl<M, P>(mesh: &M, path: P, format: StlFormat) -> io::Result<()>
+where
+    M: HasPosition3D + HasPositionAndTris,
+    M::V: Position<Dim = U3>,
+    P: AsRef<Path>,
+{
+    write_stl(mesh, File::create(path)?, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::HasEdges;
+    use crate::mesh2::{Mesh23, MwbComboMesh2};
+    use crate::tri::HasTris;
+    use crate::vertex::HasVertices;
+
+    const TETRA: &str = "\
+solid tetra
+  facet normal 0 0 -1
+    outer loop
+      vertex 0 0 0
+      vertex 0 1 0
+      vertex 1 0 0
+    endloop
+  endfacet
+  facet normal 0 -1 0
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 0 1
+    endloop
+  endfacet
+  facet normal -1 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 0 0 1
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 1 1 1
+    outer loop
+      vertex 1 0 0
+      vertex 0 1 0
+      vertex 0 0 1
+    endloop
+  endfacet
+endsolid tetra
+";
+
+    fn new_mesh() -> Mesh23<(), (), ()> {
+        Mesh23::with_defaults(|| (Point3::origin(), ()), || (), || ())
+    }
+
+    #[test]
+    fn test_read_ascii_welds() {
+        let mut mesh = new_mesh();
+        let vertices = read_stl(&mut mesh, TETRA.as_bytes()).unwrap().vertices;
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(mesh.num_vertices(), 4);
+        assert_eq!(mesh.num_tris(), 4);
+        assert_eq!(mesh.num_edges(), 12);
+        assert!(mesh.contains_tri([vertices[0], vertices[1], vertices[2]]));
+    }
+
+    #[test]
+    fn test_read_skips() {
+        // A degenerate triangle, and one with a new corner that shares a directed edge
+        // with the first one, which a mwb mesh can't take
+        let data = format!(
+            "{}solid skips
+  facet normal 0 0 1
+    outer loop
+      vertex 5 0 0
+      vertex 5 1 0
+      vertex 5 0 0
+    endloop
+  endfacet
+  facet normal 0 0 -1
+    outer loop
+      vertex 0 0 0
+      vertex 0 1 0
+      vertex 0 0 -1
+    endloop
+  endfacet
+endsolid skips
+",
+            TETRA
+        );
+        let mut mesh =
+            MwbComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let attributes = read_stl(&mut mesh, data.as_bytes()).unwrap();
+
+        assert_eq!(attributes.vertices.len(), 4);
+        assert_eq!(attributes.skipped_tris, vec![4, 5]);
+        assert_eq!(mesh.num_vertices(), 4);
+        assert_eq!(mesh.num_tris(), 4);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut mesh = new_mesh();
+        read_stl(&mut mesh, TETRA.as_bytes()).unwrap();
+
+        for format in [StlFormat::Ascii, StlFormat::Binary] {
+            let mut bytes = vec![];
+            write_stl(&mesh, &mut bytes, format).unwrap();
+            if format == StlFormat::Binary {
+                assert_eq!(bytes.len(), 84 + 50 * 4);
+            }
+
+            let mut read = new_mesh();
+            read_stl(&mut read, &bytes[..]).unwrap();
+            assert_eq!(read.num_vertices(), 4);
+            assert_eq!(read.num_tris(), 4);
+
+            let mut positions = read.vertices().map(|(_, v)| v.0).collect::<Vec<_>>();
+            positions.sort_by(|a, b| {
+                a.coords
+                    .as_slice()
+                    .partial_cmp(b.coords.as_slice())
+                    .unwrap()
+            });
+            assert_eq!(positions[3], Point3::new(1.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let mut mesh = new_mesh();
+        assert!(matches!(
+            read_stl(
+                &mut mesh,
+                "solid x\nfacet normal 0 0 1\nouter loop\nvertex 0 0\n".as_bytes()
+            ),
+            Err(StlError::Parse(4))
+        ));
+
+        let mut bytes = vec![0; 84];
+        bytes[80] = 2;
+        bytes.extend(vec![0; 60]);
+        assert!(matches!(
+            read_stl(&mut mesh, &bytes[..]),
+            Err(StlError::Truncated)
+        ));
+    }
+}
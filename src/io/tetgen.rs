@@ -0,0 +1,604 @@
+//! Reading and writing TetGen `.node`, `.ele`, `.face` and `.edge` files.
+//!
+//! Region attributes of tets and boundary markers of faces and edges
+//! become tet, triangle and edge values through the `Tag` trait.
+//! TetGen orients tets the other way from this crate, so tets get flipped on the way in and out.
+
+use nalgebra::dimension::U3;
+use nalgebra::Point3;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::{set_edge_tag, set_tri_tag, undirected_edges, undirected_tris, Tag};
+use crate::edge::EdgeId;
+use crate::tet::{HasTets, TetId};
+use crate::tri::TriId;
+use crate::vertex::{HasPosition3D, Position, VertexId};
+
+/// Errors from reading TetGen files
+#[derive(Debug)]
+pub enum TetGenError {
+    Io(io::Error),
+    /// A line couldn't be parsed. Contains the 1-based line number.
+    Parse(usize),
+    /// An element refers to a node that doesn't exist. Contains the 1-based line number.
+    IndexOutOfRange(usize),
+}
+
+impl fmt::Display for TetGenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TetGenError::Io(err) => write!(f, "I/O error in TetGen file: {}", err),
+            TetGenError::Parse(line) => write!(f, "can't parse TetGen line {}", line),
+            TetGenError::IndexOutOfRange(line) => {
+                write!(f, "TetGen node index out of range on line {}", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TetGenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TetGenError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TetGenError {
+    fn from(err: io::Error) -> Self {
+        TetGenError::Io(err)
+    }
+}
+
+/// What adding TetGen data to a mesh gives besides the mesh itself
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TetGenAttributes {
+    /// The ids of the new vertices in node order
+    pub vertices: Vec<VertexId>,
+    /// Tets that weren't added because they would break the manifoldness of a mwb mesh
+    /// or are already in the mesh
+    pub skipped_tets: Vec<TetId>,
+    /// Triangles that weren't added because they would break the manifoldness of a mwb mesh.
+    /// Triangles that were already in the mesh just get tagged.
+    pub skipped_tris: Vec<TriId>,
+    /// Edges that weren't added because they would break the manifoldness of a mwb mesh.
+    /// Edges that were already in the mesh just get tagged.
+    pub skipped_edges: Vec<EdgeId>,
+    /// The indexes of the tets that weren't added because they refer to a node that doesn't exist
+    pub invalid_tets: Vec<usize>,
+    /// The indexes of the faces that weren't added because they refer to a node that doesn't exist
+    pub invalid_tris: Vec<usize>,
+    /// The indexes of the edges that weren't added because they refer to a node that doesn't exist
+    pub invalid_edges: Vec<usize>,
+}
+
+/// The contents of a set of TetGen files.
+/// Node indexes are 0-based, and tets are in this crate's orientation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TetGen {
+    pub nodes: Vec<Point3<f64>>,
+    /// Edges with their boundary markers
+    pub edges: Vec<([usize; 2], i32)>,
+    /// Faces with their boundary markers
+    pub faces: Vec<([usize; 3], i32)>,
+    /// Tets with their region attributes
+    pub tets: Vec<([usize; 4], i32)>,
+    /// The index of the first node in the files read
+    base: usize,
+}
+
+/// A line number and the words on that line
+type Record = (usize, Vec<String>);
+
+/// A line number and the numbers in the header on that line
+type Header = (usize, Vec<usize>);
+
+/// Reads the non-empty lines of a TetGen file without comments, split into words,
+/// along with their line numbers. Also returns the total number of lines.
+fn read_lines<R: Read>(reader: R) -> Result<(Vec<Record>, usize), TetGenError> {
+    let mut lines = vec![];
+    let mut num_lines = 0;
+    for (i, text) in BufReader::new(reader).lines().enumerate() {
+        let text = text?;
+        num_lines = i + 1;
+        let words = text
+            .split('#')
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        if !words.is_empty() {
+            lines.push((i + 1, words));
+        }
+    }
+    Ok((lines, num_lines))
+}
+
+/// Parses the header and the records of a TetGen file.
+/// Records must have at least `min_len` words.
+/// Returns the line number of the header along with it.
+fn read_records<R: Read>(reader: R, min_len: usize) -> Result<(Header, Vec<Record>), TetGenError> {
+    let (lines, num_lines) = read_lines(reader)?;
+    let mut lines = lines.into_iter();
+    let (line, header) = lines.next().ok_or(TetGenError::Parse(num_lines + 1))?;
+    let header = header
+        .iter()
+        .map(|w| w.parse::<usize>().map_err(|_| TetGenError::Parse(line)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let records = lines.take(header[0]).collect::<Vec<_>>();
+    if records.len() < header[0] {
+        return Err(TetGenError::Parse(
+            records.last().map_or(line, |(l, _)| *l) + 1,
+        ));
+    }
+    if let Some((line, _)) = records.iter().find(|(_, words)| words.len() < min_len) {
+        return Err(TetGenError::Parse(*line));
+    }
+    Ok(((line, header), records))
+}
+
+fn parse<T: std::str::FromStr>(word: &str, line: usize) -> Result<T, TetGenError> {
+    word.parse().map_err(|_| TetGenError::Parse(line))
+}
+
+/// Parses a region attribute or boundary marker, which may be written as a float
+fn parse_tag(word: Option<&String>, line: usize) -> Result<i32, TetGenError> {
+    word.map_or(Ok(0), |w| parse::<f64>(w, line).map(|tag| tag as i32))
+}
+
+impl TetGen {
+    /// Parses node indexes, checking that they exist.
+    fn indexes<const N: usize>(
+        &self,
+        words: &[String],
+        line: usize,
+    ) -> Result<[usize; N], TetGenError> {
+        let mut indexes = [0; N];
+        for (index, word) in indexes.iter_mut().zip(words) {
+            *index = parse::<usize>(word, line)?
+                .checked_sub(self.base)
+                .filter(|i| *i < self.nodes.len())
+                .ok_or(TetGenError::IndexOutOfRange(line))?;
+        }
+        Ok(indexes)
+    }
+
+    /// Reads a `.node` file, replacing the nodes.
+    /// Attributes and boundary markers of nodes are ignored.
+    pub fn read_node<R: Read>(&mut self, reader: R) -> Result<(), TetGenError> {
+        let ((line, header), records) = read_records(reader, 4)?;
+        if header.get(1).map_or(false, |dim| *dim != 3) {
+            return Err(TetGenError::Parse(line));
+        }
+
+        self.base = records
+            .first()
+            .map_or(Ok(0), |(line, words)| parse(&words[0], *line))?;
+        self.nodes = records
+            .iter()
+            .map(|(line, w)| {
+                Ok(Point3::new(
+                    parse(&w[1], *line)?,
+                    parse(&w[2], *line)?,
+                    parse(&w[3], *line)?,
+                ))
+            })
+            .collect::<Result<_, TetGenError>>()?;
+        Ok(())
+    }
+
+    /// Reads a `.ele` file, replacing the tets. The nodes must already be read.
+    /// The first attribute of a tet is its region.
+    /// Nodes beyond the corners of quadratic tets are ignored.
+    pub fn read_ele<R: Read>(&mut self, reader: R) -> Result<(), TetGenError> {
+        let ((_, header), records) = read_records(reader, 5)?;
+        let num_nodes = header.get(1).copied().unwrap_or(4);
+        let has_region = header.get(2).map_or(false, |n| *n > 0);
+
+        self.tets = records
+            .iter()
+            .map(|(line, words)| {
+                let [a, b, c, d] = self.indexes(&words[1..], *line)?;
+                let region = if has_region {
+                    parse_tag(words.get(1 + num_nodes), *line)?
+                } else {
+                    0
+                };
+                Ok(([a, c, b, d], region))
+            })
+            .collect::<Result<_, TetGenError>>()?;
+        Ok(())
+    }
+
+    /// Reads a `.face` file, replacing the faces. The nodes must already be read.
+    pub fn read_face<R: Read>(&mut self, reader: R) -> Result<(), TetGenError> {
+        let ((_, header), records) = read_records(reader, 4)?;
+        let has_marker = header.get(1).map_or(false, |n| *n > 0);
+
+        self.faces = records
+            .iter()
+            .map(|(line, words)| {
+                let marker = if has_marker {
+                    parse_tag(words.get(4), *line)?
+                } else {
+                    0
+                };
+                Ok((self.indexes(&words[1..], *line)?, marker))
+            })
+            .collect::<Result<_, TetGenError>>()?;
+        Ok(())
+    }
+
+    /// Reads an `.edge` file, replacing the edges. The nodes must already be read.
+    pub fn read_edge<R: Read>(&mut self, reader: R) -> Result<(), TetGenError> {
+        let ((_, header), records) = read_records(reader, 3)?;
+        let has_marker = header.get(1).map_or(false, |n| *n > 0);
+
+        self.edges = records
+            .iter()
+            .map(|(line, words)| {
+                let marker = if has_marker {
+                    parse_tag(words.get(3), *line)?
+                } else {
+                    0
+                };
+                Ok((self.indexes(&words[1..], *line)?, marker))
+            })
+            .collect::<Result<_, TetGenError>>()?;
+        Ok(())
+    }
+
+    /// Writes a `.node` file. Nodes are numbered from 1.
+    pub fn write_node<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} 3 0 0", self.nodes.len())?;
+        for (i, p) in self.nodes.iter().enumerate() {
+            writeln!(writer, "{} {} {} {}", i + 1, p.x, p.y, p.z)?;
+        }
+        writer.flush()
+    }
+
+    /// Writes an `.ele` file with a region attribute for each tet.
+    pub fn write_ele<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} 4 1", self.tets.len())?;
+        for (i, ([a, b, c, d], region)) in self.tets.iter().enumerate() {
+            writeln!(
+                writer,
+                "{} {} {} {} {} {}",
+                i + 1,
+                a + 1,
+                c + 1,
+                b + 1,
+                d + 1,
+                region
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Writes a `.face` file with a boundary marker for each face.
+    pub fn write_face<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} 1", self.faces.len())?;
+        for (i, ([a, b, c], marker)) in self.faces.iter().enumerate() {
+            writeln!(writer, "{} {} {} {} {}", i + 1, a + 1, b + 1, c + 1, marker)?;
+        }
+        writer.flush()
+    }
+
+    /// Writes an `.edge` file with a boundary marker for each edge.
+    pub fn write_edge<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} 1", self.edges.len())?;
+        for (i, ([a, b], marker)) in self.edges.iter().enumerate() {
+            writeln!(writer, "{} {} {} {}", i + 1, a + 1, b + 1, marker)?;
+        }
+        writer.flush()
+    }
+
+    /// Reads `<base>.node` and `<base>.ele`, and `<base>.face` and `<base>.edge` if they exist.
+    pub fn load<P: AsRef<Path>>(base: P) -> Result<Self, TetGenError> {
+        let base = base.as_ref();
+        let mut tetgen = TetGen::default();
+        tetgen.read_node(File::open(base.with_extension("node"))?)?;
+        tetgen.read_ele(File::open(base.with_extension("ele"))?)?;
+
+        let face = base.with_extension("face");
+        if face.exists() {
+            tetgen.read_face(File::open(face)?)?;
+        }
+        let edge = base.with_extension("edge");
+        if edge.exists() {
+            tetgen.read_edge(File::open(edge)?)?;
+        }
+        Ok(tetgen)
+    }
+
+    /// Writes `<base>.node`, `<base>.ele`, `<base>.face` and `<base>.edge`.
+    pub fn save<P: AsRef<Path>>(&self, base: P) -> io::Result<()> {
+        let base = base.as_ref();
+        self.write_node(File::create(base.with_extension("node"))?)?;
+        self.write_ele(File::create(base.with_extension("ele"))?)?;
+        self.write_face(File::create(base.with_extension("face"))?)?;
+        self.write_edge(File::create(base.with_extension("edge"))?)
+    }
+
+    /// Converts a mesh into TetGen data.
+    /// Edges and triangles are written once regardless of direction.
+    pub fn from_mesh<M>(mesh: &M) -> Self
+    where
+        M: HasTets + HasPosition3D,
+        M::V: Position<Dim = U3>,
+        M::E: Tag,
+        M::F: Tag,
+        M::T: Tag,
+    {
+        let vertices = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        let v_inv = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (*v, i))
+            .collect::<fnv::FnvHashMap<_, _>>();
+
+        TetGen {
+            nodes: vertices.iter().map(|v| mesh.position(*v)).collect(),
+            edges: undirected_edges(mesh)
+                .into_iter()
+                .map(|(id, tag)| (id.0.map(|v| v_inv[&v]), tag))
+                .collect(),
+            faces: undirected_tris(mesh)
+                .into_iter()
+                .map(|(id, tag)| (id.0.map(|v| v_inv[&v]), tag))
+                .collect(),
+            tets: mesh
+                .tets()
+                .map(|(id, value)| (id.0.map(|v| v_inv[&v]), value.tag()))
+                .collect(),
+            base: 1,
+        }
+    }
+
+    /// Adds the contents of this to a mesh and returns the ids of the new vertices
+    /// and the elements that got skipped.
+    /// Faces and edges tag the triangles and edges of the tets in both orientations,
+    /// and get added if they aren't part of a tet.
+    /// Degenerate elements, elements that conflict with the mesh's manifoldness
+    /// and elements that refer to nodes that don't exist are skipped.
+    pub fn add_to_mesh<M>(&self, mesh: &mut M) -> TetGenAttributes
+    where
+        M: HasTets + HasPosition3D,
+        M::V: Position<Dim = U3>,
+        M::E: Tag,
+        M::F: Tag,
+        M::T: Tag,
+    {
+        let ids = mesh.extend_vertices(
+            self.nodes
+                .iter()
+                .map(|p| mesh.default_vertex().with_position(*p))
+                .collect::<Vec<_>>(),
+        );
+
+        let mut attributes = TetGenAttributes::default();
+        for (i, (tet, region)) in self.tets.iter().enumerate() {
+            if tet.iter().any(|v| *v >= ids.len()) {
+                attributes.invalid_tets.push(i);
+            } else if let Ok(tet) = TetId::try_from(tet.map(|i| ids[i])) {
+                if mesh.try_add_tet(tet, M::T::from_tag(*region)).is_err() {
+                    attributes.skipped_tets.push(tet);
+                }
+            }
+        }
+        for (i, (tri, marker)) in self.faces.iter().enumerate() {
+            if tri.iter().any(|v| *v >= ids.len()) {
+                attributes.invalid_tris.push(i);
+            } else if let Ok(tri) = TriId::try_from(tri.map(|i| ids[i])) {
+                if set_tri_tag(mesh, tri, *marker).is_err() {
+                    attributes.skipped_tris.push(tri);
+                }
+            }
+        }
+        for (i, (edge, marker)) in self.edges.iter().enumerate() {
+            if edge.iter().any(|v| *v >= ids.len()) {
+                attributes.invalid_edges.push(i);
+            } else if let Ok(edge) = EdgeId::try_from(edge.map(|i| ids[i])) {
+                if set_edge_tag(mesh, edge, *marker).is_err() {
+                    attributes.skipped_edges.push(edge);
+                }
+            }
+        }
+
+        attributes.vertices = ids;
+        attributes
+    }
+}
+
+/// Reads TetGen files named `<base>.node`, `<base>.ele` and,
+/// if they exist, `<base>.face` and `<base>.edge` into a mesh.
+/// Returns the ids of the new vertices in node order and the elements that got skipped.
+pub fn load_tetgen<M, P>(mesh: &mut M, base: P) -> Result<TetGenAttributes, TetGenError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    M::E: Tag,
+    M::F: Tag,
+    M::T: Tag,
+    P: AsRef<Path>,
+{
+    Ok(TetGen::load(base)?.add_to_mesh(mesh))
+}
+
+/// Writes a mesh as TetGen files named `<base>.node`, `<base>.ele`,
+/// `<base>.face` and `<base>.edge`.
+pub fn save_tetgen<M, P>(mesh: &M, base: P) -> io::Result<()>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    M::E: Tag,
+    M::F: Tag,
+    M::T: Tag,
+    P: AsRef<Path>,
+{
+    TetGen::from_mesh(mesh).save(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::HasEdges;
+    use crate::mesh3::Mesh33;
+    use crate::tet::HasPositionAndTets;
+    use crate::tri::HasTris;
+    use crate::vertex::HasVertices;
+
+    fn two_tets() -> Mesh33<(), i32, i32, i32> {
+        let mut mesh = Mesh33::with_defaults(|| (Point3::origin(), ()), || 0, || 0, || 0);
+        let ids = mesh.extend_vertices(vec![
+            (Point3::new(0.0, 0.0, 0.0), ()),
+            (Point3::new(1.0, 0.0, 0.0), ()),
+            (Point3::new(0.0, 1.0, 0.0), ()),
+            (Point3::new(0.0, 0.0, 1.0), ()),
+            (Point3::new(0.0, 0.0, -1.0), ()),
+        ]);
+        mesh.add_tet([ids[0], ids[2], ids[1], ids[3]], 1);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[4]], 2);
+        *mesh.tri_mut([ids[0], ids[1], ids[3]]).unwrap() = 5;
+        *mesh.edge_mut([ids[0], ids[3]]).unwrap() = 7;
+        mesh
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mesh = two_tets();
+
+        let tetgen = TetGen::from_mesh(&mesh);
+        let (mut node, mut ele, mut face, mut edge) = (vec![], vec![], vec![], vec![]);
+        tetgen.write_node(&mut node).unwrap();
+        tetgen.write_ele(&mut ele).unwrap();
+        tetgen.write_face(&mut face).unwrap();
+        tetgen.write_edge(&mut edge).unwrap();
+
+        let mut read = TetGen::default();
+        read.read_node(&node[..]).unwrap();
+        read.read_ele(&ele[..]).unwrap();
+        read.read_face(&face[..]).unwrap();
+        read.read_edge(&edge[..]).unwrap();
+        assert_eq!(read, tetgen);
+
+        let mut result = Mesh33::with_defaults(|| (Point3::origin(), ()), || 0, || 0, || 0);
+        let ids = read.add_to_mesh(&mut result).vertices;
+
+        assert_eq!(result.num_vertices(), 5);
+        assert_eq!(result.num_tets(), 2);
+        assert_eq!(result.num_tris(), mesh.num_tris());
+        assert_eq!(result.num_edges(), mesh.num_edges());
+        assert_eq!(result.tet([ids[0], ids[2], ids[1], ids[3]]), Some(&1));
+        assert_eq!(result.tet([ids[0], ids[1], ids[2], ids[4]]), Some(&2));
+        assert_eq!(result.tri([ids[0], ids[1], ids[3]]), Some(&5));
+        assert_eq!(result.edge([ids[0], ids[3]]), Some(&7));
+        assert_eq!(result.edge([ids[3], ids[0]]), Some(&7));
+    }
+
+    #[test]
+    fn test_read_zero_based_with_comments() {
+        let mut tetgen = TetGen::default();
+        tetgen
+            .read_node("# nodes\n4 3 0 1\n0 0 0 0 1\n1 1 0 0 1\n2 0 1 0 1\n3 0 0 1 1\n".as_bytes())
+            .unwrap();
+        tetgen
+            .read_ele("1 4 1\n0 0 1 2 3 -2.0 # region\n".as_bytes())
+            .unwrap();
+
+        assert_eq!(tetgen.nodes[1], Point3::new(1.0, 0.0, 0.0));
+        assert_eq!(tetgen.tets, vec![([0, 2, 1, 3], -2)]);
+
+        let mut mesh =
+            Mesh33::<(), (), (), i32>::with_defaults(|| (Point3::origin(), ()), || (), || (), || 0);
+        tetgen.add_to_mesh(&mut mesh);
+        assert!(mesh.validate_with_positions().is_valid());
+    }
+
+    #[test]
+    fn test_add_to_mesh_skipped() {
+        let mut tetgen = TetGen::default();
+        tetgen
+            .read_node(
+                "4 3 0 0
+0 0 0 0
+1 1 0 0
+2 0 1 0
+3 0 0 1
+"
+                .as_bytes(),
+            )
+            .unwrap();
+        tetgen
+            .read_ele(
+                "2 4 0
+0 0 1 2 3
+1 0 1 2 3
+"
+                .as_bytes(),
+            )
+            .unwrap();
+
+        let mut mesh = Mesh33::with_defaults(|| (Point3::origin(), ()), || 0, || 0, || 0);
+        let attributes = tetgen.add_to_mesh(&mut mesh);
+        let ids = &attributes.vertices;
+
+        assert_eq!(mesh.num_tets(), 1);
+        assert_eq!(
+            attributes.skipped_tets,
+            vec![TetId::from_valid([ids[0], ids[2], ids[1], ids[3]])]
+        );
+        assert!(attributes.skipped_tris.is_empty());
+        assert!(attributes.skipped_edges.is_empty());
+
+        // The public fields can refer to nodes that don't exist
+        tetgen.faces.push(([0, 1, 4], 0));
+        tetgen.edges.push(([5, 0], 0));
+        let mut mesh = Mesh33::with_defaults(|| (Point3::origin(), ()), || 0, || 0, || 0);
+        let attributes = tetgen.add_to_mesh(&mut mesh);
+        assert_eq!(mesh.num_tets(), 1);
+        assert!(attributes.invalid_tets.is_empty());
+        assert_eq!(attributes.invalid_tris, vec![0]);
+        assert_eq!(attributes.invalid_edges, vec![0]);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let mut tetgen = TetGen::default();
+        tetgen
+            .read_node("2 3 0 0\n1 0 0 0\n2 1 0 0\n".as_bytes())
+            .unwrap();
+
+        assert!(matches!(
+            tetgen.read_edge("1 0\n1 1 3\n".as_bytes()),
+            Err(TetGenError::IndexOutOfRange(2))
+        ));
+        assert!(matches!(
+            tetgen.read_edge("2 0\n1 1 2\n".as_bytes()),
+            Err(TetGenError::Parse(3))
+        ));
+        assert!(matches!(
+            tetgen.read_node("1 3 0 0\n1 0 zero 0\n".as_bytes()),
+            Err(TetGenError::Parse(2))
+        ));
+        assert!(matches!(
+            tetgen.read_node("# a 2D mesh\n\n1 2 0 0\n1 0 0 0\n".as_bytes()),
+            Err(TetGenError::Parse(3))
+        ));
+        assert!(matches!(
+            tetgen.read_ele("# nothing\n".as_bytes()),
+            Err(TetGenError::Parse(2))
+        ));
+    }
+}
@@ -0,0 +1,570 @@
+//! Writing VTK unstructured grids, as legacy `.vtk` files and XML `.vtu` files.
+//!
+//! Meshes are written as their maximal simplices: tets, triangles that aren't part of a tet,
+//! edges that aren't part of a triangle, and vertices that aren't part of an edge.
+//! Edges and triangles are written once regardless of orientation.
+//! Vertex values become point data and the values of the other simplices become cell data
+//! through the `VtkData` trait.
+
+use fnv::{FnvHashMap, FnvHashSet};
+use nalgebra::dimension::U3;
+use nalgebra::{Point3, Vector3};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::edge::HasEdges;
+use crate::tet::HasTets;
+use crate::tri::HasTris;
+use crate::vertex::{HasPosition3D, HasVertices, Position, VertexId};
+use crate::{mesh1::MwbComboMesh1, mesh2::MwbComboMesh2, mesh3::MwbComboMesh3};
+use crate::{ComboMesh0, ComboMesh1, ComboMesh2, ComboMesh3};
+
+/// A value of a data array
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VtkValue {
+    Scalar(f64),
+    Vector(Vector3<f64>),
+}
+
+impl VtkValue {
+    fn components(&self) -> &[f64] {
+        match self {
+            VtkValue::Scalar(x) => std::slice::from_ref(x),
+            VtkValue::Vector(v) => v.as_slice(),
+        }
+    }
+}
+
+/// For values that contribute to named point or cell data arrays.
+/// The first value that names an array decides whether it holds scalars or vectors.
+/// Elements that don't contribute to an array get zeros in it.
+pub trait VtkData {
+    fn vtk_data(&self) -> Vec<(String, VtkValue)>;
+}
+
+impl VtkData for () {
+    fn vtk_data(&self) -> Vec<(String, VtkValue)> {
+        vec![]
+    }
+}
+
+/// The position is written as the point itself.
+impl VtkData for Point3<f64> {
+    fn vtk_data(&self) -> Vec<(String, VtkValue)> {
+        vec![]
+    }
+}
+
+/// For positioned values, like the vertices of a `Mesh33`
+impl<T: VtkData> VtkData for (Point3<f64>, T) {
+    fn vtk_data(&self) -> Vec<(String, VtkValue)> {
+        self.1.vtk_data()
+    }
+}
+
+/// The type of a VTK cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VtkCellType {
+    Vertex = 1,
+    Line = 3,
+    Triangle = 5,
+    Tetra = 10,
+}
+
+/// A cell of an unstructured grid
+#[derive(Clone, Debug, PartialEq)]
+pub struct VtkCell {
+    pub cell_type: VtkCellType,
+    pub vertices: Vec<VertexId>,
+    pub data: Vec<(String, VtkValue)>,
+}
+
+/// For meshes that can be written as VTK unstructured grids
+pub trait VtkMesh: HasVertices {
+    /// Gets the maximal simplices of this mesh as cells.
+    fn vtk_cells(&self) -> Vec<VtkCell>;
+}
+
+fn vertex_cells<M: HasEdges>(mesh: &M, cells: &mut Vec<VtkCell>) {
+    cells.extend(
+        mesh.vertex_ids()
+            .filter(|v| {
+                mesh.vertex_edges_out(**v).next().is_none()
+                    && mesh.vertex_edges_in(**v).next().is_none()
+            })
+            .map(|v| VtkCell {
+                cell_type: VtkCellType::Vertex,
+                vertices: vec![*v],
+                data: vec![],
+            }),
+    );
+}
+
+fn edge_cells<M: HasEdges>(
+    mesh: &M,
+    cells: &mut Vec<VtkCell>,
+    is_maximal: impl Fn(&M, [VertexId; 2]) -> bool,
+) where
+    M::E: VtkData,
+{
+    let mut seen = FnvHashSet::default();
+    cells.extend(
+        mesh.edges()
+            .filter(|(id, _)| is_maximal(mesh, id.0) && seen.insert(id.undirected()))
+            .map(|(id, value)| VtkCell {
+                cell_type: VtkCellType::Line,
+                vertices: id.0.to_vec(),
+                data: value.vtk_data(),
+            }),
+    );
+}
+
+fn tri_cells<M: HasTris>(
+    mesh: &M,
+    cells: &mut Vec<VtkCell>,
+    is_maximal: impl Fn(&M, [VertexId; 3]) -> bool,
+) where
+    M::F: VtkData,
+{
+    let mut seen = FnvHashSet::default();
+    cells.extend(
+        mesh.tris()
+            .filter(|(id, _)| is_maximal(mesh, id.0) && seen.insert(id.undirected()))
+            .map(|(id, value)| VtkCell {
+                cell_type: VtkCellType::Triangle,
+                vertices: id.0.to_vec(),
+                data: value.vtk_data(),
+            }),
+    );
+}
+
+fn tet_cells<M: HasTets>(mesh: &M, cells: &mut Vec<VtkCell>)
+where
+    M::T: VtkData,
+{
+    // VTK orients tets the other way from this crate.
+    cells.extend(mesh.tets().map(|(id, value)| VtkCell {
+        cell_type: VtkCellType::Tetra,
+        vertices: vec![id.0[0], id.0[2], id.0[1], id.0[3]],
+        data: value.vtk_data(),
+    }));
+}
+
+fn edge_has_tris<M: HasTris>(mesh: &M, [a, b]: [VertexId; 2]) -> bool {
+    mesh.edge_vertex_opps([a, b]).next().is_some() || mesh.edge_vertex_opps([b, a]).next().is_some()
+}
+
+fn tri_has_tets<M: HasTets>(mesh: &M, [a, b, c]: [VertexId; 3]) -> bool {
+    mesh.tri_vertex_opps([a, b, c]).next().is_some()
+        || mesh.tri_vertex_opps([a, c, b]).next().is_some()
+}
+
+impl<V> VtkMesh for ComboMesh0<V> {
+    fn vtk_cells(&self) -> Vec<VtkCell> {
+        self.vertex_ids()
+            .map(|v| VtkCell {
+                cell_type: VtkCellType::Vertex,
+                vertices: vec![*v],
+                data: vec![],
+            })
+            .collect()
+    }
+}
+
+macro_rules! impl_vtk_mesh_1 {
+    ($name:ident) => {
+        impl<V, E: VtkData> VtkMesh for $name<V, E> {
+            fn vtk_cells(&self) -> Vec<VtkCell> {
+                let mut cells = vec![];
+                vertex_cells(self, &mut cells);
+                edge_cells(self, &mut cells, |_, _| true);
+                cells
+            }
+        }
+    };
+}
+
+macro_rules! impl_vtk_mesh_2 {
+    ($name:ident) => {
+        impl<V, E: VtkData, F: VtkData> VtkMesh for $name<V, E, F> {
+            fn vtk_cells(&self) -> Vec<VtkCell> {
+                let mut cells = vec![];
+                vertex_cells(self, &mut cells);
+                edge_cells(self, &mut cells, |mesh, edge| !edge_has_tris(mesh, edge));
+                tri_cells(self, &mut cells, |_, _| true);
+                cells
+            }
+        }
+    };
+}
+
+macro_rules! impl_vtk_mesh_3 {
+    ($name:ident) => {
+        impl<V, E: VtkData, F: VtkData, T: VtkData> VtkMesh for $name<V, E, F, T> {
+            fn vtk_cells(&self) -> Vec<VtkCell> {
+                let mut cells = vec![];
+                vertex_cells(self, &mut cells);
+                edge_cells(self, &mut cells, |mesh, edge| !edge_has_tris(mesh, edge));
+                tri_cells(self, &mut cells, |mesh, tri| !tri_has_tets(mesh, tri));
+                tet_cells(self, &mut cells);
+                cells
+            }
+        }
+    };
+}
+
+impl_vtk_mesh_1!(ComboMesh1);
+impl_vtk_mesh_1!(MwbComboMesh1);
+impl_vtk_mesh_2!(ComboMesh2);
+impl_vtk_mesh_2!(MwbComboMesh2);
+impl_vtk_mesh_3!(ComboMesh3);
+impl_vtk_mesh_3!(MwbComboMesh3);
+
+/// A named data array, with `components` values per element
+struct DataArray {
+    name: String,
+    components: usize,
+    values: Vec<f64>,
+}
+
+/// Collects the data of each element into arrays, in order of first appearance.
+fn data_arrays<'a>(
+    data: impl ExactSizeIterator<Item = Vec<(String, VtkValue)>> + 'a,
+) -> Vec<DataArray> {
+    let len = data.len();
+    let mut arrays = Vec::<DataArray>::new();
+    let mut indexes = FnvHashMap::default();
+
+    for (i, element) in data.enumerate() {
+        for (name, value) in element {
+            let components = value.components();
+            let index = *indexes.entry(name.clone()).or_insert_with(|| {
+                arrays.push(DataArray {
+                    name,
+                    components: components.len(),
+                    values: vec![0.0; len * components.len()],
+                });
+                arrays.len() - 1
+            });
+
+            let array = &mut arrays[index];
+            let n = array.components;
+            for (x, value) in array.values[i * n..(i + 1) * n].iter_mut().zip(components) {
+                *x = *value;
+            }
+        }
+    }
+
+    arrays
+}
+
+/// A mesh, flattened for writing
+struct Grid {
+    points: Vec<Point3<f64>>,
+    cells: Vec<(VtkCellType, Vec<usize>)>,
+    point_data: Vec<DataArray>,
+    cell_data: Vec<DataArray>,
+}
+
+impl Grid {
+    fn new<M>(mesh: &M) -> Self
+    where
+        M: VtkMesh + HasPosition3D,
+        M::V: Position<Dim = U3> + VtkData,
+    {
+        let vertices = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        let v_inv = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (*v, i))
+            .collect::<FnvHashMap<_, _>>();
+        let cells = mesh.vtk_cells();
+
+        Grid {
+            points: vertices.iter().map(|v| mesh.position(*v)).collect(),
+            point_data: data_arrays(vertices.iter().map(|v| mesh.vertex(*v).unwrap().vtk_data())),
+            cells: cells
+                .iter()
+                .map(|c| (c.cell_type, c.vertices.iter().map(|v| v_inv[v]).collect()))
+                .collect(),
+            cell_data: data_arrays(cells.into_iter().map(|c| c.data)),
+        }
+    }
+}
+
+/// Writes a mesh as a legacy ASCII VTK file.
+/// Spaces in array names are replaced with underscores, since the format doesn't allow them.
+pub fn write_vtk<M, W>(mesh: &M, writer: W) -> io::Result<()>
+where
+    M: VtkMesh + HasPosition3D,
+    M::V: Position<Dim = U3> + VtkData,
+    W: Write,
+{
+    let grid = Grid::new(mesh);
+    let mut w = BufWriter::new(writer);
+
+    writeln!(w, "# vtk DataFile Version 3.0")?;
+    writeln!(w, "cimplex mesh")?;
+    writeln!(w, "ASCII")?;
+    writeln!(w, "DATASET UNSTRUCTURED_GRID")?;
+
+    writeln!(w, "POINTS {} double", grid.points.len())?;
+    for p in &grid.points {
+        writeln!(w, "{} {} {}", p.x, p.y, p.z)?;
+    }
+
+    let size = grid.cells.iter().map(|(_, v)| v.len() + 1).sum::<usize>();
+    writeln!(w, "CELLS {} {}", grid.cells.len(), size)?;
+    for (_, vertices) in &grid.cells {
+        let indexes = vertices.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        writeln!(w, "{} {}", vertices.len(), indexes.join(" "))?;
+    }
+    writeln!(w, "CELL_TYPES {}", grid.cells.len())?;
+    for (cell_type, _) in &grid.cells {
+        writeln!(w, "{}", *cell_type as u8)?;
+    }
+
+    for (header, count, arrays) in [
+        ("POINT_DATA", grid.points.len(), &grid.point_data),
+        ("CELL_DATA", grid.cells.len(), &grid.cell_data),
+    ] {
+        if arrays.is_empty() {
+            continue;
+        }
+        writeln!(w, "{} {}", header, count)?;
+        for array in arrays {
+            let name = array.name.replace(char::is_whitespace, "_");
+            if array.components == 1 {
+                writeln!(w, "SCALARS {} double 1", name)?;
+                writeln!(w, "LOOKUP_TABLE default")?;
+            } else {
+                writeln!(w, "VECTORS {} double", name)?;
+            }
+            for values in array.values.chunks(array.components) {
+                let values = values.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                writeln!(w, "{}", values.join(" "))?;
+            }
+        }
+    }
+
+    w.flush()
+}
+
+/// Escapes text for an XML attribute.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_vtu_array<W: Write, T: ToString>(
+    w: &mut W,
+    ty: &str,
+    name: Option<&str>,
+    components: usize,
+    values: impl Iterator<Item = T>,
+) -> io::Result<()> {
+    let name = name.map_or(String::new(), |n| format!(" Name=\"{}\"", escape(n)));
+    writeln!(
+        w,
+        "        <DataArray type=\"{}\"{} NumberOfComponents=\"{}\" format=\"ascii\">",
+        ty, name, components
+    )?;
+    let values = values.map(|x| x.to_string()).collect::<Vec<_>>();
+    writeln!(w, "          {}", values.join(" "))?;
+    writeln!(w, "        </DataArray>")
+}
+
+/// Writes a mesh as an XML VTK unstructured grid (`.vtu`) with ASCII data.
+pub fn write_vtu<M, W>(mesh: &M, writer: W) -> io::Result<()>
+where
+    M: VtkMesh + HasPosition3D,
+    M::V: Position<Dim = U3> + VtkData,
+    W: Write,
+{
+    let grid = Grid::new(mesh);
+    let mut w = BufWriter::new(writer);
+
+    writeln!(w, "<?xml version=\"1.0\"?>")?;
+    writeln!(
+        w,
+        "<VTKFile type=\"UnstructuredGrid\" version=\"0.1\" byte_order=\"LittleEndian\">"
+    )?;
+    writeln!(w, "  <UnstructuredGrid>")?;
+    writeln!(
+        w,
+        "    <Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
+        grid.points.len(),
+        grid.cells.len()
+    )?;
+
+    for (tag, arrays) in [
+        ("PointData", &grid.point_data),
+        ("CellData", &grid.cell_data),
+    ] {
+        writeln!(w, "      <{}>", tag)?;
+        for array in arrays {
+            write_vtu_array(
+                &mut w,
+                "Float64",
+                Some(&array.name),
+                array.components,
+                array.values.iter(),
+            )?;
+        }
+        writeln!(w, "      </{}>", tag)?;
+    }
+
+    writeln!(w, "      <Points>")?;
+    write_vtu_array(
+        &mut w,
+        "Float64",
+        None,
+        3,
+        grid.points.iter().flat_map(|p| p.coords.iter()),
+    )?;
+    writeln!(w, "      </Points>")?;
+
+    writeln!(w, "      <Cells>")?;
+    let connectivity = grid.cells.iter().flat_map(|(_, v)| v.iter());
+    write_vtu_array(&mut w, "Int64", Some("connectivity"), 1, connectivity)?;
+    let offsets = grid.cells.iter().scan(0, |offset, (_, v)| {
+        *offset += v.len();
+        Some(*offset)
+    });
+    write_vtu_array(&mut w, "Int64", Some("offsets"), 1, offsets)?;
+    let types = grid.cells.iter().map(|(t, _)| *t as u8);
+    write_vtu_array(&mut w, "UInt8", Some("types"), 1, types)?;
+    writeln!(w, "      </Cells>")?;
+
+    writeln!(w, "    </Piece>")?;
+    writeln!(w, "  </UnstructuredGrid>")?;
+    writeln!(w, "</VTKFile>")?;
+
+    w.flush()
+}
+
+/// Writes a mesh as a legacy ASCII VTK file. See `write_vtk`.
+pub fn save_vtk<M, P>(mesh: &M, path: P) -> io::Result<()>
+where
+    M: VtkMesh + HasPosition3D,
+    M::V: Position<Dim = U3> + VtkData,
+    P: AsRef<Path>,
+{
+    write_vtk(mesh, File::create(path)?)
+}
+
+/// Writes a mesh as an XML VTK unstructured grid file. See `write_vtu`.
+pub fn save_vtu<M, P>(mesh: &M, path: P) -> io::Result<()>
+where
+    M: VtkMesh + HasPosition3D,
+    M::V: Position<Dim = U3> + VtkData,
+    P: AsRef<Path>,
+{
+    write_vtu(mesh, File::create(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh1::Mesh13;
+    use crate::mesh2::Mesh23;
+    use crate::mesh3::Mesh33;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Heat(f64);
+
+    impl VtkData for Heat {
+        fn vtk_data(&self) -> Vec<(String, VtkValue)> {
+            vec![("heat".to_owned(), VtkValue::Scalar(self.0))]
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Flow(Vector3<f64>);
+
+    impl VtkData for Flow {
+        fn vtk_data(&self) -> Vec<(String, VtkValue)> {
+            vec![("flow rate".to_owned(), VtkValue::Vector(self.0))]
+        }
+    }
+
+    /// A tet with a dangling edge, a lone triangle and an isolated vertex
+    fn mixed_mesh() -> Mesh33<Heat, Flow, Heat, Heat> {
+        let mut mesh = Mesh33::with_defaults(
+            || (Point3::origin(), Heat(0.0)),
+            Flow::default,
+            Heat::default,
+            Heat::default,
+        );
+        let ids = mesh.extend_vertices(
+            (0..9)
+                .map(|i| {
+                    (
+                        Point3::new(i as f64, (i % 2) as f64, (i % 3) as f64),
+                        Heat(i as f64),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+        mesh.add_tet([ids[0], ids[2], ids[1], ids[3]], Heat(1.0));
+        mesh.add_edge([ids[3], ids[4]], Flow(Vector3::new(1.0, 2.0, 3.0)));
+        mesh.add_tri([ids[5], ids[6], ids[7]], Heat(2.0));
+        mesh
+    }
+
+    #[test]
+    fn test_cells() {
+        let mesh = mixed_mesh();
+        let mut types = mesh
+            .vtk_cells()
+            .iter()
+            .map(|c| c.cell_type as u8)
+            .collect::<Vec<_>>();
+        types.sort_unstable();
+        assert_eq!(types, vec![1, 3, 5, 10]);
+
+        let mut mesh = Mesh13::<(), ()>::with_defaults(|| (Point3::origin(), ()), || ());
+        let ids = mesh.extend_vertices(vec![(Point3::origin(), ()); 3]);
+        mesh.add_edge([ids[0], ids[1]], ());
+        mesh.add_edge([ids[1], ids[0]], ());
+        assert_eq!(mesh.vtk_cells().len(), 2);
+
+        let mut mesh = Mesh23::<(), (), ()>::with_defaults(|| (Point3::origin(), ()), || (), || ());
+        let ids = mesh.extend_vertices(vec![(Point3::origin(), ()); 3]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], ());
+        mesh.add_tri([ids[0], ids[2], ids[1]], ());
+        let cells = mesh.vtk_cells();
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].cell_type, VtkCellType::Triangle);
+    }
+
+    #[test]
+    fn test_write_vtk() {
+        let mut bytes = vec![];
+        write_vtk(&mixed_mesh(), &mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+
+        assert!(text.contains("POINTS 9 double\n"));
+        assert!(text.contains("CELLS 4 14\n"));
+        assert!(text.contains("CELL_TYPES 4\n"));
+        assert!(text.contains("POINT_DATA 9\nSCALARS heat double 1\nLOOKUP_TABLE default\n0\n1\n"));
+        assert!(text.contains("CELL_DATA 4\n"));
+        assert!(text.contains("VECTORS flow_rate double\n"));
+    }
+
+    #[test]
+    fn test_write_vtu() {
+        let mut bytes = vec![];
+        write_vtu(&mixed_mesh(), &mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+
+        assert!(text.contains("<Piece NumberOfPoints=\"9\" NumberOfCells=\"4\">"));
+        assert!(text.contains("Name=\"flow rate\" NumberOfComponents=\"3\""));
+        assert!(text.contains("Name=\"heat\" NumberOfComponents=\"1\""));
+        assert!(text.contains("Name=\"offsets\""));
+        assert_eq!(text.matches("<DataArray").count(), 7);
+    }
+}
@@ -1,14 +1,21 @@
+pub mod alpha;
 pub mod edge;
+pub mod error;
 pub mod io;
 pub mod mesh0;
 pub mod mesh1;
 pub mod mesh2;
 pub mod mesh3;
+pub mod plc;
 pub mod tet;
 pub mod tetrahedralize;
 pub mod tri;
+pub mod triangulate;
+pub mod validate;
 pub mod vertex;
+pub mod voronoi;
 
+mod exact;
 mod iter;
 mod private;
 
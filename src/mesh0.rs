@@ -3,6 +3,7 @@ use nalgebra::dimension::{U2, U3};
 use std::iter::{Extend, FromIterator, IntoIterator, Map};
 use typenum::B0;
 
+use crate::validate::ValidationReport;
 use crate::vertex::{HasVertices, IdType, Vertex as VertexIntr, VertexId};
 use crate::PtN;
 use crate::{
@@ -60,6 +61,12 @@ impl<V> ComboMesh0<V> {
             default_v: vertex,
         }
     }
+
+    /// Checks the internal structure of this mesh, for use in tests and debugging.
+    /// A vertex mesh has no links between elements, so there's nothing that can go wrong.
+    pub fn validate(&self) -> ValidationReport {
+        ValidationReport::default()
+    }
 }
 
 impl<V> IntoIterator for ComboMesh0<V> {
@@ -5,6 +5,9 @@ use nalgebra::dimension::{U2, U3};
 use std::fmt::Debug;
 use typenum::{B0, B1};
 
+use crate::error::{mwb_conflicts, IntoMwbError};
+use crate::private::Key;
+use crate::validate::{self, ValidationReport};
 use crate::{edge, vertex::HasVertices, PtN};
 use crate::{edge::EdgeId, vertex::IdType};
 use crate::{private::Lock, ComboMesh2, ComboMesh3};
@@ -70,6 +73,20 @@ impl<V: Default, E: Default> Default for ComboMesh1<V, E> {
     }
 }
 
+/// Turns a vertex mesh into an edge mesh with no edges,
+/// with `E::default` as the default edge value.
+impl<V, E: Default> From<ComboMesh0<V>> for ComboMesh1<V, E> {
+    fn from(mesh: ComboMesh0<V>) -> Self {
+        mesh.into_with_edges(Default::default)
+    }
+}
+
+impl<V, E> From<MwbComboMesh1<V, E>> for ComboMesh1<V, E> {
+    fn from(mesh: MwbComboMesh1<V, E>) -> Self {
+        mesh.into_non_mwb()
+    }
+}
+
 impl<V, E> ComboMesh1<V, E> {
     /// Creates an empty vertex mesh.
     pub fn new() -> Self
@@ -90,6 +107,38 @@ impl<V, E> ComboMesh1<V, E> {
             default_e: edge,
         }
     }
+
+    /// Checks the internal structure of this mesh, for use in tests and debugging.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate::check_edges(self, &mut report);
+        report
+    }
+
+    /// Converts this mesh into one with the mwb property, keeping all ids and values.
+    /// Fails if a vertex is the source or target of more than 1 edge,
+    /// giving back the mesh along with the edges at those vertices.
+    pub fn into_mwb(self) -> Result<MwbComboMesh1<V, E>, IntoMwbError<Self>> {
+        let simplices = mwb_conflicts(self.vertex_ids().flat_map(|v| {
+            [
+                self.vertex_edges_out(*v).collect::<Vec<_>>(),
+                self.vertex_edges_in(*v).collect(),
+            ]
+        }));
+        if !simplices.is_empty() {
+            return Err(IntoMwbError {
+                mesh: Box::new(self),
+                simplices,
+            });
+        }
+
+        let next_vertex_id = self.next_vertex_id;
+        let (default_v, default_e) = (self.default_v, self.default_e);
+        let (vertices, edges) = self.into_ve_r::<Key>();
+        let mut mesh = MwbComboMesh1::from_ve_r::<_, _, Key>(vertices, edges, default_v, default_e);
+        mesh.next_vertex_id = next_vertex_id;
+        Ok(mesh)
+    }
 }
 
 /// A position-containing edge mesh
@@ -174,6 +223,23 @@ impl<V, E> MwbComboMesh1<V, E> {
             default_e: edge,
         }
     }
+
+    /// Checks the internal structure of this mesh, for use in tests and debugging.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate::check_edges(self, &mut report);
+        report
+    }
+
+    /// Converts this mesh into one without the mwb property, keeping all ids and values.
+    pub fn into_non_mwb(self) -> ComboMesh1<V, E> {
+        let next_vertex_id = self.next_vertex_id;
+        let (default_v, default_e) = (self.default_v, self.default_e);
+        let (vertices, edges) = self.into_ve_r::<Key>();
+        let mut mesh = ComboMesh1::from_ve_r::<_, _, Key>(vertices, edges, default_v, default_e);
+        mesh.next_vertex_id = next_vertex_id;
+        mesh
+    }
 }
 
 pub(crate) mod internal {
@@ -217,6 +283,7 @@ pub(crate) mod internal {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::MeshError;
     use fnv::FnvHashSet;
     use std::convert::TryInto;
     use std::fmt::Debug;
@@ -790,6 +857,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_try_add_edge_m() {
+        let mut mesh = MwbComboMesh1::<usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        assert_eq!(mesh.try_add_edge([ids[1], ids[3]], 54), Ok(()));
+        assert_eq!(
+            mesh.try_add_edge([ids[1], ids[3]], 1),
+            Err(MeshError::DuplicateId(EdgeId([ids[1], ids[3]]).into()))
+        );
+        assert_eq!(
+            mesh.try_add_edge([ids[1], ids[2]], 1),
+            Err(MeshError::NonManifold(EdgeId([ids[1], ids[3]]).into()))
+        );
+        assert_eq!(
+            mesh.try_add_edge([ids[0], ids[3]], 1),
+            Err(MeshError::NonManifold(EdgeId([ids[1], ids[3]]).into()))
+        );
+        assert_eq!(
+            mesh.try_add_edge([ids[2], ids[2]], 1),
+            Err(MeshError::DegenerateSimplex(vec![ids[2], ids[2]]))
+        );
+        mesh.remove_vertex(ids[0]);
+        assert_eq!(
+            mesh.try_add_edge([ids[0], ids[2]], 1),
+            Err(MeshError::MissingVertex(ids[0]))
+        );
+
+        assert_edges_m(&mesh, vec![([ids[1], ids[3]], 54)]);
+        assert_eq!(
+            mesh.try_extend_edges(vec![([ids[3], ids[1]], 2), ([ids[2], ids[3]], 8)]),
+            Err(MeshError::NonManifold(EdgeId([ids[1], ids[3]]).into()))
+        );
+        assert_edges_m(&mesh, vec![([ids[1], ids[3]], 54), ([ids[3], ids[1]], 2)]);
+    }
+
     #[test]
     fn test_remove_vertex_m() {
         let mut mesh = MwbComboMesh1::<usize, usize>::default();
@@ -997,4 +1099,57 @@ mod tests {
             .collect::<FnvHashSet<_>>();
         assert_eq!(set, expected);
     }
+
+    #[test]
+    fn test_into_mwb() {
+        let mut mesh = ComboMesh1::<usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.extend_edges(vec![
+            ([ids[0], ids[1]], 1),
+            ([ids[0], ids[2]], 2),
+            ([ids[1], ids[2]], 3),
+        ]);
+
+        let err = mesh.into_mwb().unwrap_err();
+        let simplices = err.simplices.iter().copied().collect::<FnvHashSet<_>>();
+        let expect = vec![
+            EdgeId([ids[0], ids[1]]).into(),
+            EdgeId([ids[0], ids[2]]).into(),
+            EdgeId([ids[1], ids[2]]).into(),
+        ]
+        .into_iter()
+        .collect::<FnvHashSet<_>>();
+        assert_eq!(simplices, expect);
+
+        let mut mesh = *err.mesh;
+        mesh.remove_edge([ids[0], ids[2]]);
+        mesh.remove_vertex(ids[3]);
+        let mut mesh = mesh.into_mwb().unwrap();
+        assert!(mesh.validate().is_valid());
+        assert_vertices_m(&mesh, vec![(ids[0], 3), (ids[1], 6), (ids[2], 9)]);
+        assert_edges_m(&mesh, vec![([ids[0], ids[1]], 1), ([ids[1], ids[2]], 3)]);
+        assert_eq!(mesh.add_vertex(5), VertexId(4));
+
+        let mesh = mesh.into_non_mwb();
+        assert!(mesh.validate().is_valid());
+        assert_edges(&mesh, vec![([ids[0], ids[1]], 1), ([ids[1], ids[2]], 3)]);
+        assert_eq!(mesh.num_vertices(), 4);
+    }
+
+    #[test]
+    fn test_from_mesh0() {
+        let mut mesh = ComboMesh0::<usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9]);
+        mesh.remove_vertex(ids[2]);
+
+        let mut mesh = ComboMesh1::<usize, usize>::from(mesh);
+        assert_vertices(&mesh, vec![(ids[0], 3), (ids[1], 6)]);
+        assert_edges(&mesh, Vec::<([VertexId; 2], usize)>::new());
+        assert_eq!(mesh.add_vertex(2), VertexId(3));
+        mesh.add_edge([ids[0], ids[1]], 4);
+        assert!(mesh.validate().is_valid());
+
+        let mesh = mesh.into_without_edges();
+        assert_eq!(mesh.num_vertices(), 3);
+    }
 }
@@ -1,10 +1,11 @@
 use fnv::FnvHashMap;
 use idmap::OrderedIdMap;
 use nalgebra::dimension::{U2, U3};
-use nalgebra::Point3;
-use std::{fmt::Debug, path::Path};
+use std::fmt::Debug;
 use typenum::{B0, B1};
 
+use crate::error::{mwb_conflicts, IntoMwbError};
+use crate::private::Key;
 use crate::private::Lock;
 use crate::tri::{HasTris, TriId};
 use crate::vertex::{HasVertices, IdType, VertexId};
@@ -14,11 +15,14 @@ use crate::{
     mesh1::MwbComboMesh1,
     ComboMesh0, ComboMesh1,
 };
+#[cfg(feature = "obj")]
 use crate::{
-    mesh1::internal::HigherVertex,
+    io::obj::Obj,
     vertex::{HasPosition3D, Position},
-    ComboMesh3,
 };
+use crate::{mesh1::internal::HigherVertex, ComboMesh3};
+#[cfg(feature = "obj")]
+use std::path::Path;
 
 use internal::{HigherEdge, MwbTri, Tri};
 
@@ -105,6 +109,28 @@ impl<V: Default, E: Default, F: Default> Default for ComboMesh2<V, E, F> {
     }
 }
 
+/// Turns an edge mesh into a tri mesh with no triangles,
+/// with `F::default` as the default triangle value.
+impl<V, E, F: Default> From<ComboMesh1<V, E>> for ComboMesh2<V, E, F> {
+    fn from(mesh: ComboMesh1<V, E>) -> Self {
+        mesh.into_with_tris(Default::default)
+    }
+}
+
+/// Turns an edge mesh into a tri mesh with no triangles,
+/// with `F::default` as the default triangle value.
+impl<V, E, F: Default> From<MwbComboMesh1<V, E>> for ComboMesh2<V, E, F> {
+    fn from(mesh: MwbComboMesh1<V, E>) -> Self {
+        mesh.into_with_tris(Default::default)
+    }
+}
+
+impl<V, E, F> From<MwbComboMesh2<V, E, F>> for ComboMesh2<V, E, F> {
+    fn from(mesh: MwbComboMesh2<V, E, F>) -> Self {
+        mesh.into_non_mwb()
+    }
+}
+
 impl<V, E, F> ComboMesh2<V, E, F> {
     /// Creates an empty tri mesh.
     pub fn new() -> Self
@@ -129,9 +155,21 @@ impl<V, E, F> ComboMesh2<V, E, F> {
         }
     }
 
-    /// Loads this from OBJ data.
-    /// Will remove from this class once PLC gets added.
+    /// Checks the internal structure of this mesh, for use in tests and debugging.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate::check_tris(self, &mut report);
+        report
+    }
+
+    /// Loads this from OBJ data of the `obj` crate.
+    ///
+    /// # Panics
+    /// Panics if a polygon refers to a position, texture coordinate or normal that doesn't exist.
     #[cfg(feature = "obj")]
+    #[deprecated(
+        note = "use `io::obj::read_obj`, which reads into any mesh and keeps `f64` positions"
+    )]
     pub fn from_obj(
         data: obj::ObjData,
         default_vertex: fn() -> V,
@@ -143,36 +181,20 @@ impl<V, E, F> ComboMesh2<V, E, F> {
         V: Position<Dim = U3>,
     {
         let mut mesh = Self::with_defaults(default_vertex, default_edge, default_tri);
-
-        let ids = mesh.extend_vertices(data.position.into_iter().map(|[x, y, z]| {
-            <V as Position>::with_position(
-                default_vertex(),
-                Point3::new(x as f64, y as f64, z as f64),
-            )
-        }));
-
-        for object in data.objects {
-            for group in object.groups {
-                for poly in group.polys {
-                    if poly.0.len() >= 3 {
-                        mesh.add_tri(
-                            [ids[poly.0[0].0], ids[poly.0[1].0], ids[poly.0[2].0]],
-                            default_tri(),
-                        );
-                    } else if poly.0.len() == 2 {
-                        mesh.add_edge([ids[poly.0[0].0], ids[poly.0[1].0]], default_edge());
-                        mesh.add_edge([ids[poly.0[1].0], ids[poly.0[0].0]], default_edge());
-                    }
-                }
-            }
-        }
-
+        Obj::from(data)
+            .add_to_mesh(&mut mesh)
+            .expect("OBJ data refers to something that doesn't exist");
         mesh
     }
 
-    /// Loads this from an OBJ file.
-    /// Will remove from this class once PLC gets added.
+    /// Loads this from an OBJ file using the `obj` crate.
+    ///
+    /// # Panics
+    /// Panics if a polygon refers to a position, texture coordinate or normal that doesn't exist.
     #[cfg(feature = "obj")]
+    #[deprecated(
+        note = "use `io::obj::load_obj`, which reads into any mesh and keeps `f64` positions"
+    )]
     pub fn read_obj<P: AsRef<Path>>(
         path: P,
         default_vertex: fn() -> V,
@@ -183,9 +205,36 @@ impl<V, E, F> ComboMesh2<V, E, F> {
         Self: HasPosition3D + HasVertices<V = V, HigherV = B1>,
         V: Position<Dim = U3>,
     {
+        #[allow(deprecated)]
         obj::Obj::load(path)
             .map(|data| Self::from_obj(data.data, default_vertex, default_edge, default_tri))
     }
+
+    /// Converts this mesh into one with the mwb property, keeping all ids and values.
+    /// Fails if an edge is part of more than 1 triangle,
+    /// giving back the mesh along with the triangles at those edges.
+    pub fn into_mwb(self) -> Result<MwbComboMesh2<V, E, F>, IntoMwbError<Self>> {
+        let simplices = mwb_conflicts(
+            self.edges
+                .keys()
+                .map(|e| self.edge_tris(*e).collect::<Vec<_>>()),
+        );
+        if !simplices.is_empty() {
+            return Err(IntoMwbError {
+                mesh: Box::new(self),
+                simplices,
+            });
+        }
+
+        let next_vertex_id = self.next_vertex_id;
+        let (default_v, default_e, default_f) = (self.default_v, self.default_e, self.default_f);
+        let (vertices, edges, tris) = self.into_vef_r::<Key>();
+        let mut mesh = MwbComboMesh2::from_vef_r::<_, _, _, Key>(
+            vertices, edges, tris, default_v, default_e, default_f,
+        );
+        mesh.next_vertex_id = next_vertex_id;
+        Ok(mesh)
+    }
 }
 
 /// A position-containing tri mesh
@@ -302,6 +351,25 @@ impl<V, E, F> MwbComboMesh2<V, E, F> {
             default_f: tri,
         }
     }
+
+    /// Checks the internal structure of this mesh, for use in tests and debugging.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate::check_tris(self, &mut report);
+        report
+    }
+
+    /// Converts this mesh into one without the mwb property, keeping all ids and values.
+    pub fn into_non_mwb(self) -> ComboMesh2<V, E, F> {
+        let next_vertex_id = self.next_vertex_id;
+        let (default_v, default_e, default_f) = (self.default_v, self.default_e, self.default_f);
+        let (vertices, edges, tris) = self.into_vef_r::<Key>();
+        let mut mesh = ComboMesh2::from_vef_r::<_, _, _, Key>(
+            vertices, edges, tris, default_v, default_e, default_f,
+        );
+        mesh.next_vertex_id = next_vertex_id;
+        mesh
+    }
 }
 
 pub(crate) mod internal {
@@ -354,6 +422,7 @@ pub(crate) mod internal {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::MeshError;
     use fnv::FnvHashSet;
     use std::convert::TryInto;
     use std::fmt::Debug;
@@ -1091,6 +1160,76 @@ mod tests {
         assert_eq!(mesh.num_tris(), 0);
     }
 
+    #[test]
+    fn test_try_add_tri_m() {
+        let mut mesh = MwbComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        assert_eq!(mesh.try_add_tri([ids[0], ids[1], ids[2]], 1), Ok(()));
+        assert_eq!(
+            mesh.try_add_tri([ids[1], ids[2], ids[0]], 2),
+            Err(MeshError::DuplicateId(
+                TriId([ids[0], ids[1], ids[2]]).into()
+            ))
+        );
+        assert_eq!(
+            mesh.try_add_tri([ids[0], ids[1], ids[3]], 2),
+            Err(MeshError::NonManifold(
+                TriId([ids[0], ids[1], ids[2]]).into()
+            ))
+        );
+        assert!(matches!(
+            mesh.try_add_tri([ids[0], ids[3], ids[0]], 2),
+            Err(MeshError::DegenerateSimplex(_))
+        ));
+        assert_eq!(mesh.try_add_tri([ids[1], ids[0], ids[3]], 2), Ok(()));
+        assert_eq!(mesh.num_tris(), 2);
+    }
+
+    #[test]
+    fn test_try_flip22() {
+        let mut mesh = MwbComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], 1);
+        mesh.add_tri([ids[1], ids[0], ids[3]], 2);
+
+        assert_eq!(mesh.try_flip22(ids[0], ids[1], ids[2], ids[3]), Ok(()));
+        assert!(mesh.contains_tri([ids[0], ids[3], ids[2]]));
+        assert!(mesh.contains_tri([ids[3], ids[1], ids[2]]));
+        assert_eq!(mesh.num_tris(), 2);
+
+        assert_eq!(
+            mesh.try_flip22(ids[0], ids[1], ids[2], ids[3]),
+            Err(MeshError::MissingSimplex(
+                TriId([ids[0], ids[1], ids[2]]).into()
+            ))
+        );
+        assert_eq!(mesh.num_tris(), 2);
+    }
+
+    #[test]
+    fn test_try_flip13_m() {
+        let mut mesh = MwbComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], 1);
+        // Uses the edge from ids[3] to ids[0], which a new triangle would need
+        mesh.add_tri([ids[3], ids[0], ids[4]], 2);
+
+        assert_eq!(
+            mesh.try_flip13([ids[0], ids[1], ids[2]], ids[3]),
+            Err(MeshError::NonManifold(
+                TriId::from_valid([ids[3], ids[0], ids[4]]).into()
+            ))
+        );
+        assert_eq!(mesh.num_tris(), 2);
+
+        mesh.remove_tri([ids[3], ids[0], ids[4]]);
+        assert_eq!(mesh.try_flip13([ids[0], ids[1], ids[2]], ids[3]), Ok(()));
+        assert!(mesh.contains_tri([ids[0], ids[1], ids[3]]));
+        assert!(mesh.contains_tri([ids[1], ids[2], ids[3]]));
+        assert!(mesh.contains_tri([ids[2], ids[0], ids[3]]));
+        assert_eq!(mesh.num_tris(), 3);
+    }
+
     #[test]
     fn test_add_tri_m() {
         let mut mesh = MwbComboMesh2::<usize, usize, usize>::default();
@@ -1447,4 +1586,154 @@ mod tests {
         .collect::<FnvHashSet<_>>();
         assert_eq!(set, expected);
     }
+
+    #[test]
+    fn test_into_mwb() {
+        use crate::error::SimplexId;
+
+        let mut mesh = ComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[1], ids[2]], 1),
+            ([ids[0], ids[1], ids[3]], 2),
+            ([ids[2], ids[1], ids[4]], 3),
+        ]);
+
+        let err = mesh.into_mwb().unwrap_err();
+        let simplices = err.simplices.iter().copied().collect::<FnvHashSet<_>>();
+        let expect = vec![
+            SimplexId::Tri([ids[0], ids[1], ids[2]].try_into().unwrap()),
+            SimplexId::Tri([ids[0], ids[1], ids[3]].try_into().unwrap()),
+        ]
+        .into_iter()
+        .collect::<FnvHashSet<_>>();
+        assert_eq!(simplices, expect);
+
+        let mut mesh = *err.mesh;
+        mesh.remove_tri([ids[0], ids[1], ids[3]]);
+        mesh.add_tri([ids[1], ids[0], ids[3]], 2);
+        let mesh = mesh.into_mwb().unwrap();
+        assert!(mesh.validate().is_valid());
+        assert_tris_m(
+            &mesh,
+            vec![
+                ([ids[0], ids[1], ids[2]], 1),
+                ([ids[1], ids[0], ids[3]], 2),
+                ([ids[2], ids[1], ids[4]], 3),
+            ],
+        );
+
+        let mesh = mesh.into_non_mwb();
+        assert!(mesh.validate().is_valid());
+        assert_tris(
+            &mesh,
+            vec![
+                ([ids[0], ids[1], ids[2]], 1),
+                ([ids[1], ids[0], ids[3]], 2),
+                ([ids[2], ids[1], ids[4]], 3),
+            ],
+        );
+        assert_eq!(mesh.num_edges(), 9);
+    }
+
+    #[test]
+    fn test_into_with_tris() {
+        let mut mesh = ComboMesh1::<usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.extend_edges(vec![([ids[0], ids[1]], 1), ([ids[2], ids[3]], 2)]);
+
+        let mut mesh = mesh.into_with_tris(|| 7);
+        assert_vertices(
+            &mesh,
+            vec![(ids[0], 3), (ids[1], 6), (ids[2], 9), (ids[3], 2)],
+        );
+        assert_edges(&mesh, vec![([ids[0], ids[1]], 1), ([ids[2], ids[3]], 2)]);
+        assert_tris(&mesh, Vec::<([VertexId; 3], usize)>::new());
+
+        mesh.add_tri([ids[0], ids[1], ids[2]], 5);
+        assert!(mesh.validate().is_valid());
+        assert_eq!(mesh.default_tri(), 7);
+
+        let mesh = mesh.into_without_tris();
+        assert!(mesh.validate().is_valid());
+        assert_eq!(mesh.num_edges(), 4);
+        assert_eq!(mesh.edge([ids[0], ids[1]]), Some(&1));
+        assert_eq!(mesh.edge([ids[1], ids[2]]), Some(&0));
+    }
+
+    #[test]
+    fn test_boundary_loops() {
+        let mut mesh = ComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5, 8]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[1], ids[2]], 1),
+            ([ids[0], ids[2], ids[3]], 2),
+        ]);
+        mesh.add_tri([ids[3], ids[4], ids[5]], 3);
+        mesh.add_tri([ids[3], ids[5], ids[4]], 4);
+
+        let loops = mesh.boundary_loops();
+        assert_eq!(loops.len(), 1);
+        let boundary_loop = &loops[0];
+        assert_eq!(boundary_loop.len(), 4);
+        for (edge, next) in boundary_loop
+            .iter()
+            .zip(boundary_loop.iter().cycle().skip(1))
+        {
+            assert_eq!(edge.target(), next.source());
+        }
+        let edges = boundary_loop.iter().copied().collect::<FnvHashSet<_>>();
+        let expect = vec![
+            [ids[0], ids[1]],
+            [ids[1], ids[2]],
+            [ids[2], ids[3]],
+            [ids[3], ids[0]],
+        ]
+        .into_iter()
+        .map(|e| e.try_into().unwrap())
+        .collect::<FnvHashSet<_>>();
+        assert_eq!(edges, expect);
+    }
+
+    #[test]
+    #[cfg(feature = "obj")]
+    #[allow(deprecated)]
+    fn test_from_obj() {
+        use crate::vertex::HasPosition3D;
+        use nalgebra::Point3;
+
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], ());
+        mesh.add_edge([ids[2], ids[3]], ());
+
+        let read = ComboMesh2::from_obj(mesh.to_obj(), Point3::origin, || (), || ());
+        assert_eq!(read.num_vertices(), 4);
+        assert_eq!(read.num_tris(), 1);
+        assert_eq!(read.num_edges(), 5);
+    }
+
+    #[test]
+    fn test_skeleton() {
+        let mut mesh = ComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], 1);
+        *mesh.edge_mut([ids[0], ids[1]]).unwrap() = 7;
+
+        let skeleton = mesh.skeleton::<1>();
+        assert!(skeleton.validate().is_valid());
+        assert_eq!(skeleton.num_vertices(), 4);
+        assert_eq!(skeleton.num_edges(), 3);
+        assert_eq!(skeleton.edge([ids[0], ids[1]]), Some(&7));
+
+        let skeleton = mesh.skeleton::<0>();
+        assert_eq!(skeleton.vertex(ids[3]), Some(&2));
+        assert_eq!(skeleton.num_vertices(), 4);
+    }
 }
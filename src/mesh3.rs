@@ -4,7 +4,9 @@ use nalgebra::dimension::{U2, U3};
 use std::fmt::Debug;
 use typenum::{B0, B1};
 
+use crate::error::{mwb_conflicts, IntoMwbError};
 use crate::mesh2::internal::HigherEdge;
+use crate::private::Key;
 use crate::tet::{HasTets, TetId};
 use crate::tri::{HasTris, TriId};
 use crate::vertex::{HasVertices, VertexId};
@@ -131,6 +133,28 @@ impl<V: Default, E: Default, F: Default, T: Default> Default for ComboMesh3<V, E
     }
 }
 
+/// Turns a tri mesh into a tet mesh with no tetrahedrons,
+/// with `T::default` as the default tetrahedron value.
+impl<V, E, F, T: Default> From<ComboMesh2<V, E, F>> for ComboMesh3<V, E, F, T> {
+    fn from(mesh: ComboMesh2<V, E, F>) -> Self {
+        mesh.into_with_tets(Default::default)
+    }
+}
+
+/// Turns a tri mesh into a tet mesh with no tetrahedrons,
+/// with `T::default` as the default tetrahedron value.
+impl<V, E, F, T: Default> From<MwbComboMesh2<V, E, F>> for ComboMesh3<V, E, F, T> {
+    fn from(mesh: MwbComboMesh2<V, E, F>) -> Self {
+        mesh.into_with_tets(Default::default)
+    }
+}
+
+impl<V, E, F, T> From<MwbComboMesh3<V, E, F, T>> for ComboMesh3<V, E, F, T> {
+    fn from(mesh: MwbComboMesh3<V, E, F, T>) -> Self {
+        mesh.into_non_mwb()
+    }
+}
+
 impl<V, E, F, T> ComboMesh3<V, E, F, T> {
     /// Creates an empty tet mesh.
     pub fn new() -> Self
@@ -162,6 +186,40 @@ impl<V, E, F, T> ComboMesh3<V, E, F, T> {
             default_t: tet,
         }
     }
+
+    /// Checks the internal structure of this mesh, for use in tests and debugging.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate::check_tets(self, &mut report);
+        report
+    }
+
+    /// Converts this mesh into one with the mwb property, keeping all ids and values.
+    /// Fails if a triangle is part of more than 1 tetrahedron,
+    /// giving back the mesh along with the tetrahedrons at those triangles.
+    pub fn into_mwb(self) -> Result<MwbComboMesh3<V, E, F, T>, IntoMwbError<Self>> {
+        let simplices = mwb_conflicts(
+            self.tris
+                .keys()
+                .map(|f| self.tri_tets(*f).collect::<Vec<_>>()),
+        );
+        if !simplices.is_empty() {
+            return Err(IntoMwbError {
+                mesh: Box::new(self),
+                simplices,
+            });
+        }
+
+        let next_vertex_id = self.next_vertex_id;
+        let (default_v, default_e) = (self.default_v, self.default_e);
+        let (default_f, default_t) = (self.default_f, self.default_t);
+        let (vertices, edges, tris, tets) = self.into_veft_r::<Key>();
+        let mut mesh = MwbComboMesh3::from_veft_r::<_, _, _, _, Key>(
+            vertices, edges, tris, tets, default_v, default_e, default_f, default_t,
+        );
+        mesh.next_vertex_id = next_vertex_id;
+        Ok(mesh)
+    }
 }
 
 /// A position-containing tet mesh
@@ -326,6 +384,26 @@ impl<V, E, F, T> MwbComboMesh3<V, E, F, T> {
             default_t: tet,
         }
     }
+
+    /// Checks the internal structure of this mesh, for use in tests and debugging.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate::check_tets(self, &mut report);
+        report
+    }
+
+    /// Converts this mesh into one without the mwb property, keeping all ids and values.
+    pub fn into_non_mwb(self) -> ComboMesh3<V, E, F, T> {
+        let next_vertex_id = self.next_vertex_id;
+        let (default_v, default_e) = (self.default_v, self.default_e);
+        let (default_f, default_t) = (self.default_f, self.default_t);
+        let (vertices, edges, tris, tets) = self.into_veft_r::<Key>();
+        let mut mesh = ComboMesh3::from_veft_r::<_, _, _, _, Key>(
+            vertices, edges, tris, tets, default_v, default_e, default_f, default_t,
+        );
+        mesh.next_vertex_id = next_vertex_id;
+        mesh
+    }
 }
 
 mod internal {
@@ -377,6 +455,7 @@ mod internal {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::MeshError;
     use fnv::FnvHashSet;
     use std::convert::TryInto;
     use std::fmt::Debug;
@@ -1398,6 +1477,107 @@ mod tests {
         assert_eq!(mesh.num_tets(), 0);
     }
 
+    #[test]
+    fn test_try_add_tet_m() {
+        let mut mesh = MwbComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        assert_eq!(
+            mesh.try_add_tet([ids[0], ids[1], ids[2], ids[3]], 1),
+            Ok(())
+        );
+        assert_eq!(
+            mesh.try_add_tet([ids[0], ids[1], ids[2], ids[3]], 2),
+            Err(MeshError::DuplicateId(
+                TetId([ids[0], ids[1], ids[2], ids[3]]).into()
+            ))
+        );
+        assert_eq!(
+            mesh.try_add_tet([ids[0], ids[1], ids[2], ids[4]], 2),
+            Err(MeshError::NonManifold(
+                TetId([ids[0], ids[1], ids[2], ids[3]]).into()
+            ))
+        );
+        assert!(matches!(
+            mesh.try_add_tet([ids[0], ids[1], ids[0], ids[4]], 2),
+            Err(MeshError::DegenerateSimplex(_))
+        ));
+        mesh.remove_vertex(ids[4]);
+        assert_eq!(
+            mesh.try_add_tet([ids[2], ids[1], ids[0], ids[4]], 2),
+            Err(MeshError::MissingVertex(ids[4]))
+        );
+        assert_eq!(mesh.num_tets(), 1);
+    }
+
+    #[test]
+    fn test_try_flip23_flip32() {
+        let mut mesh = MwbComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[3]], 1);
+        mesh.add_tet([ids[2], ids[1], ids[0], ids[4]], 2);
+
+        assert_eq!(
+            mesh.try_flip23(ids[0], ids[1], ids[2], ids[3], ids[4]),
+            Ok(())
+        );
+        assert!(mesh.contains_tet([ids[0], ids[1], ids[4], ids[3]]));
+        assert!(mesh.contains_tet([ids[1], ids[2], ids[4], ids[3]]));
+        assert!(mesh.contains_tet([ids[2], ids[0], ids[4], ids[3]]));
+        assert_eq!(mesh.num_tets(), 3);
+        assert_eq!(
+            mesh.try_flip23(ids[0], ids[1], ids[2], ids[3], ids[4]),
+            Err(MeshError::MissingSimplex(
+                TetId([ids[0], ids[1], ids[2], ids[3]]).into()
+            ))
+        );
+
+        assert_eq!(
+            mesh.try_flip32(ids[0], ids[1], ids[2], ids[3], ids[4]),
+            Ok(())
+        );
+        assert!(mesh.contains_tet([ids[0], ids[1], ids[2], ids[3]]));
+        assert!(mesh.contains_tet([ids[2], ids[1], ids[0], ids[4]]));
+        assert_eq!(mesh.num_tets(), 2);
+
+        // Has the same oriented triangle as one of the tets the flip would add
+        let v = mesh.add_vertex(8);
+        mesh.add_tet([ids[0], v, ids[4], ids[3]], 3);
+        assert_eq!(
+            mesh.try_flip23(ids[0], ids[1], ids[2], ids[3], ids[4]),
+            Err(MeshError::NonManifold(
+                TetId::from_valid([ids[0], v, ids[4], ids[3]]).into()
+            ))
+        );
+        assert_eq!(mesh.num_tets(), 3);
+        assert!(mesh.contains_tet([ids[0], v, ids[4], ids[3]]));
+    }
+
+    #[test]
+    fn test_try_tet_walker_from_tet() {
+        let mut mesh = MwbComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[3]], 1);
+
+        let walker = mesh
+            .try_tet_walker_from_tet([ids[1], ids[2], ids[0], ids[3]])
+            .unwrap();
+        assert_eq!(walker.tet(), TetId([ids[0], ids[1], ids[2], ids[3]]));
+        assert_eq!(
+            mesh.try_tet_walker_from_tet([ids[0], ids[1], ids[2], ids[4]])
+                .err(),
+            Some(MeshError::MissingSimplex(
+                TetId([ids[0], ids[1], ids[2], ids[4]]).into()
+            ))
+        );
+        assert_eq!(
+            mesh.try_tet_walker_from_tet([ids[0], ids[1], ids[2], ids[2]])
+                .err(),
+            Some(MeshError::DegenerateSimplex(vec![
+                ids[0], ids[1], ids[2], ids[2]
+            ]))
+        );
+    }
+
     #[test]
     fn test_add_tet_m() {
         let mut mesh = MwbComboMesh3::<usize, usize, usize, usize>::default();
@@ -1910,4 +2090,122 @@ mod tests {
             .collect::<FnvHashSet<_>>();
         assert_eq!(set, expected);
     }
+
+    #[test]
+    fn test_into_mwb() {
+        use crate::error::SimplexId;
+
+        let mut mesh = ComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.extend_tets(vec![
+            ([ids[0], ids[1], ids[2], ids[3]], 1),
+            ([ids[0], ids[1], ids[2], ids[4]], 2),
+        ]);
+
+        let err = mesh.into_mwb().unwrap_err();
+        let simplices = err.simplices.iter().copied().collect::<FnvHashSet<_>>();
+        let expect = vec![
+            SimplexId::Tet([ids[0], ids[1], ids[2], ids[3]].try_into().unwrap()),
+            SimplexId::Tet([ids[0], ids[1], ids[2], ids[4]].try_into().unwrap()),
+        ]
+        .into_iter()
+        .collect::<FnvHashSet<_>>();
+        assert_eq!(simplices, expect);
+
+        let mut mesh = *err.mesh;
+        mesh.remove_tet([ids[0], ids[1], ids[2], ids[4]]);
+        mesh.add_tet([ids[0], ids[2], ids[1], ids[4]], 2);
+        let mesh = mesh.into_mwb().unwrap();
+        assert!(mesh.validate().is_valid());
+        assert_tets_m(
+            &mesh,
+            vec![
+                ([ids[0], ids[1], ids[2], ids[3]], 1),
+                ([ids[0], ids[2], ids[1], ids[4]], 2),
+            ],
+        );
+
+        let mesh = mesh.into_non_mwb();
+        assert!(mesh.validate().is_valid());
+        assert_tets(
+            &mesh,
+            vec![
+                ([ids[0], ids[1], ids[2], ids[3]], 1),
+                ([ids[0], ids[2], ids[1], ids[4]], 2),
+            ],
+        );
+        assert_eq!(mesh.num_tris(), 8);
+    }
+
+    #[test]
+    fn test_into_without_tets() {
+        let mut mesh = ComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[3]], 1);
+        mesh.add_tri([ids[1], ids[2], ids[4]], 2);
+
+        let mesh = mesh.into_without_tets();
+        assert!(mesh.validate().is_valid());
+        assert_eq!(mesh.num_vertices(), 5);
+        assert_eq!(mesh.num_edges(), 14);
+        assert_eq!(mesh.num_tris(), 5);
+        assert_eq!(mesh.tri([ids[1], ids[2], ids[4]]), Some(&2));
+
+        let mut mesh = ComboMesh3::<usize, usize, usize, usize>::from(mesh);
+        assert_eq!(mesh.num_tets(), 0);
+        assert_eq!(mesh.num_tris(), 5);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[3]], 1);
+        assert!(mesh.validate().is_valid());
+    }
+
+    #[test]
+    fn test_boundary_surface() {
+        let mut mesh = ComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5, 8]);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[3]], 1);
+        mesh.add_tet([ids[0], ids[2], ids[1], ids[4]], 2);
+        let face = mesh
+            .tris()
+            .map(|(id, _)| *id)
+            .find(|id| id.contains_vertex(ids[3]))
+            .unwrap();
+        *mesh.tri_mut(face).unwrap() = 7;
+
+        let boundary = mesh.boundary_surface();
+        assert!(boundary.validate().is_valid());
+        assert_eq!(boundary.num_vertices(), 5);
+        assert_eq!(boundary.num_edges(), 18);
+        assert_eq!(boundary.num_tris(), 6);
+        assert_eq!(boundary.tri(face), Some(&7));
+        assert_eq!(boundary.tri([ids[0], ids[1], ids[2]]), None);
+        assert_eq!(boundary.tri([ids[0], ids[2], ids[1]]), None);
+        for (id, _) in boundary.tris() {
+            assert!(mesh.tri_tets(*id).next().is_some());
+        }
+        assert!(boundary.boundary_loops().is_empty());
+    }
+
+    #[test]
+    fn test_skeleton() {
+        let mut mesh = ComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[3]], 1);
+        let face = mesh
+            .tris()
+            .map(|(id, _)| *id)
+            .find(|id| id.contains_vertex(ids[3]))
+            .unwrap();
+        *mesh.tri_mut(face).unwrap() = 7;
+
+        let skeleton = mesh.skeleton::<2>();
+        assert!(skeleton.validate().is_valid());
+        assert_eq!(skeleton.num_tris(), 4);
+        assert_eq!(skeleton.num_edges(), 12);
+        assert_eq!(skeleton.tri(face), Some(&7));
+
+        let skeleton = mesh.skeleton::<1>();
+        assert!(skeleton.validate().is_valid());
+        assert_eq!(skeleton.num_edges(), 12);
+        assert_eq!(mesh.skeleton::<0>().num_vertices(), 4);
+    }
 }
@@ -0,0 +1,1235 @@
+//! Piecewise linear complexes, the input to constrained tetrahedralization.
+
+use fnv::FnvHashMap;
+use nalgebra::{Point2, Point3, Vector3};
+use std::error::Error;
+use std::fmt;
+
+use crate::edge::HasEdges;
+use crate::exact;
+use crate::mesh2::ComboMesh2;
+use crate::mesh3::MwbComboMesh3;
+use crate::tet::{HasPositionAndTets, HasTets, TetId, TetLocation};
+use crate::tetrahedralize::{self, DelaunayTetsError, RecoveryError};
+use crate::tri::HasTris;
+use crate::vertex::{HasPosition, HasVertices};
+
+/// How far a facet's vertices may stray from its plane,
+/// relative to the size of the facet.
+const PLANARITY_TOLERANCE: f64 = 1e-9;
+
+/// A polygonal facet of a PLC, with vertices given by index.
+/// The facet may have polygonal holes in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Facet {
+    /// The outer boundary of the facet.
+    pub boundary: Vec<usize>,
+    /// The boundaries of the holes in the facet.
+    pub holes: Vec<Vec<usize>>,
+}
+
+impl Facet {
+    /// Creates a facet without holes.
+    pub fn new(boundary: Vec<usize>) -> Self {
+        Self {
+            boundary,
+            holes: vec![],
+        }
+    }
+
+    /// Creates a facet with holes.
+    pub fn with_holes(boundary: Vec<usize>, holes: Vec<Vec<usize>>) -> Self {
+        Self { boundary, holes }
+    }
+
+    /// Iterates over the boundary, followed by the holes.
+    fn loops(&self) -> impl Iterator<Item = &Vec<usize>> {
+        std::iter::once(&self.boundary).chain(self.holes.iter())
+    }
+
+    /// Iterates over the edges of the boundary and the holes.
+    fn edges(&self) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.loops().flat_map(|l| {
+            l.iter()
+                .zip(l.iter().cycle().skip(1))
+                .map(|(v0, v1)| [*v0, *v1])
+        })
+    }
+}
+
+/// Ways a PLC can be invalid or fail to get tetrahedralized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlcError {
+    /// A segment or facet refers to a vertex that doesn't exist.
+    VertexOutOfRange(usize),
+    /// Two vertices have the same position.
+    DuplicateVertex(usize, usize),
+    /// A segment has the same vertex at both ends.
+    DegenerateSegment(usize),
+    /// A facet has too few vertices, repeats a vertex, or has no area.
+    DegenerateFacet(usize),
+    /// A facet's vertices don't lie on a plane.
+    NonPlanarFacet(usize),
+    /// A facet's boundary or holes intersect each other.
+    SelfIntersectingFacet(usize),
+    /// A hole of a facet lies outside the facet's boundary.
+    HoleOutsideFacet(usize),
+    /// A segment crosses through a facet, crosses one of its edges, or lies inside it.
+    SegmentCrossesFacet { segment: usize, facet: usize },
+    /// Two segments cross each other.
+    SegmentsIntersect(usize, usize),
+    /// A vertex lies on a segment without being one of its ends.
+    VertexOnSegment { vertex: usize, segment: usize },
+    /// A vertex lies on a facet without being one of its vertices.
+    VertexOnFacet { vertex: usize, facet: usize },
+    /// Two facets cross through each other, or overlap while lying in the same plane.
+    FacetsIntersect(usize, usize),
+    /// The PLC encloses no volume, so there is nothing to tetrahedralize.
+    Flat,
+    /// A region seed lies outside the convex hull of the vertices.
+    RegionSeedOutside(usize),
+    /// The Delaunay tetrahedralization of the vertices failed.
+    Delaunay(DelaunayTetsError),
+    /// Recovering the segments and facets in the tetrahedralization failed.
+    Recovery(RecoveryError),
+}
+
+impl fmt::Display for PlcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlcError::VertexOutOfRange(v) => write!(f, "vertex {} does not exist", v),
+            PlcError::DuplicateVertex(v0, v1) => {
+                write!(f, "vertices {} and {} have the same position", v0, v1)
+            }
+            PlcError::DegenerateSegment(s) => write!(f, "segment {} is degenerate", s),
+            PlcError::DegenerateFacet(fa) => write!(f, "facet {} is degenerate", fa),
+            PlcError::NonPlanarFacet(fa) => write!(f, "facet {} is not planar", fa),
+            PlcError::SelfIntersectingFacet(fa) => write!(f, "facet {} intersects itself", fa),
+            PlcError::HoleOutsideFacet(fa) => {
+                write!(f, "facet {} has a hole outside its boundary", fa)
+            }
+            PlcError::SegmentCrossesFacet { segment, facet } => {
+                write!(f, "segment {} crosses facet {}", segment, facet)
+            }
+            PlcError::SegmentsIntersect(s0, s1) => {
+                write!(f, "segments {} and {} intersect", s0, s1)
+            }
+            PlcError::VertexOnSegment { vertex, segment } => {
+                write!(f, "vertex {} lies on segment {}", vertex, segment)
+            }
+            PlcError::VertexOnFacet { vertex, facet } => {
+                write!(f, "vertex {} lies on facet {}", vertex, facet)
+            }
+            PlcError::FacetsIntersect(f0, f1) => write!(f, "facets {} and {} intersect", f0, f1),
+            PlcError::Flat => write!(f, "the PLC encloses no volume"),
+            PlcError::RegionSeedOutside(r) => {
+                write!(f, "region seed {} is outside the convex hull", r)
+            }
+            PlcError::Delaunay(err) => write!(f, "Delaunay tetrahedralization failed: {}", err),
+            PlcError::Recovery(err) => write!(f, "recovering segments and facets failed: {}", err),
+        }
+    }
+}
+
+impl Error for PlcError {}
+
+/// A 3D piecewise linear complex: vertices, constraint segments,
+/// and polygonal facets with holes, along with region seed points.
+///
+/// This is the input to a constrained tetrahedralization.
+/// The segments and facets are recovered in the tetrahedralization,
+/// and the facets separate it into regions.
+#[derive(Clone, Debug, Default)]
+pub struct Plc3 {
+    vertices: Vec<Point3<f64>>,
+    segments: Vec<[usize; 2]>,
+    facets: Vec<Facet>,
+    region_seeds: Vec<Point3<f64>>,
+}
+
+impl Plc3 {
+    /// Creates an empty PLC.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the vertex positions. Vertices are referred to by their index here.
+    pub fn vertices(&self) -> &[Point3<f64>] {
+        &self.vertices
+    }
+
+    /// Gets the constraint segments.
+    pub fn segments(&self) -> &[[usize; 2]] {
+        &self.segments
+    }
+
+    /// Gets the facets.
+    pub fn facets(&self) -> &[Facet] {
+        &self.facets
+    }
+
+    /// Gets the region seed points.
+    pub fn region_seeds(&self) -> &[Point3<f64>] {
+        &self.region_seeds
+    }
+
+    /// Adds a vertex and returns its index.
+    pub fn add_vertex(&mut self, position: Point3<f64>) -> usize {
+        self.vertices.push(position);
+        self.vertices.len() - 1
+    }
+
+    /// Extends the vertex list with an iterator and returns the indexes of the new vertices.
+    pub fn extend_vertices<I: IntoIterator<Item = Point3<f64>>>(&mut self, iter: I) -> Vec<usize> {
+        let start = self.vertices.len();
+        self.vertices.extend(iter);
+        (start..self.vertices.len()).collect()
+    }
+
+    /// Adds a constraint segment between 2 vertices and returns its index.
+    pub fn add_segment(&mut self, vertices: [usize; 2]) -> usize {
+        self.segments.push(vertices);
+        self.segments.len() - 1
+    }
+
+    /// Adds a facet and returns its index.
+    pub fn add_facet(&mut self, facet: Facet) -> usize {
+        self.facets.push(facet);
+        self.facets.len() - 1
+    }
+
+    /// Adds a region seed point and returns its index.
+    /// If a PLC has region seeds, only the regions containing them get tetrahedralized.
+    pub fn add_region_seed(&mut self, position: Point3<f64>) -> usize {
+        self.region_seeds.push(position);
+        self.region_seeds.len() - 1
+    }
+
+    /// Checks that this is a valid PLC.
+    /// Segments and facets must refer to existing vertices, vertices must have distinct positions,
+    /// facets must be planar and not intersect themselves, segments and facets may not cross
+    /// through or lie inside facets or cross each other, and vertices may only touch
+    /// the segments and facets they belong to.
+    pub fn validate(&self) -> Result<(), PlcError> {
+        self.triangulate_facets().map(|_| ())
+    }
+
+    /// Tetrahedralizes the regions enclosed by the facets, recovering the segments and facets
+    /// and adding Steiner points as needed.
+    /// If there are region seeds, only the regions containing them are kept.
+    /// The vertices of the PLC get the first vertex ids of the mesh, in order.
+    pub fn tetrahedralize(&self) -> Result<MwbComboMesh3<Point3<f64>, (), (), ()>, PlcError> {
+        let triangulations = self.triangulate_facets()?;
+
+        let mut mesh = MwbComboMesh3::with_defaults(Point3::origin, || (), || (), || ());
+        let ids = mesh.extend_vertices(self.vertices.iter().copied());
+
+        let mut tri_mesh = ComboMesh2::with_defaults(Point3::origin, || (), || ());
+        tri_mesh.extend_vertices_with_ids(ids.iter().copied().zip(self.vertices.iter().copied()));
+        for [v0, v1, v2] in triangulations.into_iter().flatten() {
+            tri_mesh.add_tri([ids[v0], ids[v1], ids[v2]], ());
+        }
+        for [v0, v1] in &self.segments {
+            if !tri_mesh.contains_edge([ids[*v0], ids[*v1]])
+                && !tri_mesh.contains_edge([ids[*v1], ids[*v0]])
+            {
+                tri_mesh.add_edge([ids[*v0], ids[*v1]], ());
+            }
+        }
+
+        let mut mesh = tetrahedralize::delaunay_tets(mesh).map_err(PlcError::Delaunay)?;
+        if mesh.num_tets() == 0 {
+            return Err(PlcError::Flat);
+        }
+
+        let mut edge_steiner_map = tetrahedralize::recover_edges(&mut mesh, &mut tri_mesh, None)
+            .map_err(PlcError::Recovery)?;
+        tetrahedralize::recover_tris(&mut mesh, &mut tri_mesh, &mut edge_steiner_map)
+            .map_err(PlcError::Recovery)?;
+
+        let seeds = self
+            .region_seeds
+            .iter()
+            .enumerate()
+            .map(|(i, seed)| {
+                tet_containing_point(&mesh, *seed).ok_or(PlcError::RegionSeedOutside(i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        tetrahedralize::remove_unseeded_tets(&mut mesh, &tri_mesh, &seeds);
+
+        Ok(mesh)
+    }
+
+    /// Validates this PLC, returning a triangulation of each facet.
+    fn triangulate_facets(&self) -> Result<Vec<Vec<[usize; 3]>>, PlcError> {
+        let vertex_ids = self
+            .segments
+            .iter()
+            .flat_map(|s| s.iter())
+            .chain(self.facets.iter().flat_map(|f| f.loops().flatten()));
+        for v in vertex_ids {
+            if *v >= self.vertices.len() {
+                return Err(PlcError::VertexOutOfRange(*v));
+            }
+        }
+
+        // Positions are compared by bits, with -0 and 0 counting as the same.
+        let mut positions = FnvHashMap::default();
+        for (i, pos) in self.vertices.iter().enumerate() {
+            let key = [pos.x + 0.0, pos.y + 0.0, pos.z + 0.0].map(f64::to_bits);
+            if let Some(j) = positions.insert(key, i) {
+                return Err(PlcError::DuplicateVertex(j, i));
+            }
+        }
+
+        for (i, [v0, v1]) in self.segments.iter().enumerate() {
+            if v0 == v1 {
+                return Err(PlcError::DegenerateSegment(i));
+            }
+        }
+
+        let triangulations = self
+            .facets
+            .iter()
+            .enumerate()
+            .map(|(i, facet)| triangulate_facet(&self.vertices, facet, i))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (vertex, pos) in self.vertices.iter().enumerate() {
+            for (segment, [v0, v1]) in self.segments.iter().enumerate() {
+                if vertex != *v0
+                    && vertex != *v1
+                    && on_segment(self.vertices[*v0], self.vertices[*v1], *pos)
+                {
+                    return Err(PlcError::VertexOnSegment { vertex, segment });
+                }
+            }
+
+            for (facet, tris) in triangulations.iter().enumerate() {
+                if !self.facets[facet].loops().flatten().any(|v| *v == vertex)
+                    && tris.iter().any(|tri| self.tri_contains(*tri, *pos))
+                {
+                    return Err(PlcError::VertexOnFacet { vertex, facet });
+                }
+            }
+        }
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            for (j, other) in self.segments.iter().enumerate().skip(i + 1) {
+                if self.edges_intersect(*segment, *other) {
+                    return Err(PlcError::SegmentsIntersect(i, j));
+                }
+            }
+
+            for j in 0..self.facets.len() {
+                if self.edge_meets_facet(*segment, j, &triangulations) {
+                    return Err(PlcError::SegmentCrossesFacet {
+                        segment: i,
+                        facet: j,
+                    });
+                }
+            }
+        }
+
+        for i in 0..self.facets.len() {
+            for j in i + 1..self.facets.len() {
+                // Crossing edges get caught from the edges of the first facet
+                if self.facets[i]
+                    .edges()
+                    .any(|edge| self.edge_meets_facet(edge, j, &triangulations))
+                    || self.facets[j].edges().any(|[v0, v1]| {
+                        self.edge_crosses_tris(v0, v1, &triangulations[i])
+                            || self.edge_inside_facet(v0, v1, &self.facets[i], &triangulations[i])
+                    })
+                    || self.coplanar_tris_overlap(&triangulations[i], &triangulations[j])
+                {
+                    return Err(PlcError::FacetsIntersect(i, j));
+                }
+            }
+        }
+
+        Ok(triangulations)
+    }
+
+    /// Whether the edge between 2 vertices crosses through a facet, crosses one of its edges,
+    /// or lies inside it. Touching doesn't count.
+    fn edge_meets_facet(
+        &self,
+        [v0, v1]: [usize; 2],
+        facet: usize,
+        triangulations: &[Vec<[usize; 3]>],
+    ) -> bool {
+        let tris = &triangulations[facet];
+        self.edge_crosses_tris(v0, v1, tris)
+            || self.facets[facet]
+                .edges()
+                .any(|edge| self.edges_intersect([v0, v1], edge))
+            || self.edge_inside_facet(v0, v1, &self.facets[facet], tris)
+    }
+
+    /// Whether the closed triangle contains a point. The test is exact.
+    fn tri_contains(&self, tri: [usize; 3], p: Point3<f64>) -> bool {
+        let [t0, t1, t2] = tri.map(|v| self.vertices[v]);
+        orient_3d(t0, t1, t2, p) == 0.0
+            && (0..3).all(|axis| {
+                let [t0, t1, t2, p] = [t0, t1, t2, p].map(|p| project(p, axis));
+                let sides = [
+                    orient_2d(t0, t1, p),
+                    orient_2d(t1, t2, p),
+                    orient_2d(t2, t0, p),
+                ];
+                sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+            })
+    }
+
+    /// Whether 2 edges that don't share a vertex intersect. The test is exact.
+    fn edges_intersect(&self, [v0, v1]: [usize; 2], [w0, w1]: [usize; 2]) -> bool {
+        if [v0, v1].contains(&w0) || [v0, v1].contains(&w1) {
+            return false;
+        }
+
+        let [a0, a1, b0, b1] = [v0, v1, w0, w1].map(|v| self.vertices[v]);
+        orient_3d(a0, a1, b0, b1) == 0.0
+            && (0..3).all(|axis| {
+                let [a0, a1, b0, b1] = [a0, a1, b0, b1].map(|p| project(p, axis));
+                segments_intersect(a0, a1, b0, b1)
+            })
+    }
+
+    /// Whether the edge between 2 vertices lies in the plane of a facet
+    /// and overlaps its inside. The test is exact.
+    fn edge_inside_facet(&self, v0: usize, v1: usize, facet: &Facet, tris: &[[usize; 3]]) -> bool {
+        let [p0, p1] = [self.vertices[v0], self.vertices[v1]];
+        let boundary = facet.edges().collect::<Vec<_>>();
+
+        tris.iter().any(|&tri| {
+            let [t0, t1, t2] = tri.map(|v| self.vertices[v]);
+            if orient_3d(t0, t1, t2, p0) != 0.0 || orient_3d(t0, t1, t2, p1) != 0.0 {
+                return false;
+            }
+
+            let inner_edge_overlaps = (0..3).any(|k| {
+                let [e0, e1] = [tri[k], tri[(k + 1) % 3]];
+                !boundary.contains(&[e0, e1])
+                    && !boundary.contains(&[e1, e0])
+                    && segments_overlap(p0, p1, self.vertices[e0], self.vertices[e1])
+            });
+            inner_edge_overlaps || segment_overlaps_tri(p0, p1, [t0, t1, t2])
+        })
+    }
+
+    /// Whether the edge between 2 vertices crosses through any of the triangles.
+    /// Touching doesn't count.
+    fn edge_crosses_tris(&self, v0: usize, v1: usize, tris: &[[usize; 3]]) -> bool {
+        let pos = &self.vertices;
+        tris.iter().any(|tri| {
+            let [t0, t1, t2] = [pos[tri[0]], pos[tri[1]], pos[tri[2]]];
+            let s0 = orient_3d(t0, t1, t2, pos[v0]);
+            let s1 = orient_3d(t0, t1, t2, pos[v1]);
+            if !(s0 > 0.0 && s1 < 0.0 || s0 < 0.0 && s1 > 0.0) {
+                return false;
+            }
+
+            let sides = [
+                orient_3d(pos[v0], pos[v1], t0, t1),
+                orient_3d(pos[v0], pos[v1], t1, t2),
+                orient_3d(pos[v0], pos[v1], t2, t0),
+            ];
+            sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+        })
+    }
+
+    /// Whether a triangle of one list overlaps the inside of a triangle of the other
+    /// while lying in the same plane. Touching doesn't count.
+    fn coplanar_tris_overlap(&self, tris0: &[[usize; 3]], tris1: &[[usize; 3]]) -> bool {
+        tris0.iter().any(|tri0| {
+            tris1
+                .iter()
+                .any(|tri1| coplanar_tris_overlap(&self.vertices, *tri0, *tri1))
+        })
+    }
+}
+
+/// Whether 2 triangles lie in the same plane and their insides overlap.
+fn coplanar_tris_overlap(vertices: &[Point3<f64>], tri0: [usize; 3], tri1: [usize; 3]) -> bool {
+    let [t0, t1, t2] = [vertices[tri0[0]], vertices[tri0[1]], vertices[tri0[2]]];
+    if tri1
+        .iter()
+        .any(|v| orient_3d(t0, t1, t2, vertices[*v]) != 0.0)
+    {
+        return false;
+    }
+
+    // Project onto the axis plane the first triangle is most aligned with,
+    // keeping both triangles counterclockwise.
+    let normal = (t1 - t0).cross(&(t2 - t0));
+    let axis = normal.iamax();
+    let (mut i, mut j) = ((axis + 1) % 3, (axis + 2) % 3);
+    if normal[axis] < 0.0 {
+        std::mem::swap(&mut i, &mut j);
+    }
+    let project = |tri: [usize; 3]| {
+        let mut tri = [0, 1, 2].map(|k| Point2::new(vertices[tri[k]][i], vertices[tri[k]][j]));
+        if orient_2d(tri[0], tri[1], tri[2]) < 0.0 {
+            tri.swap(1, 2);
+        }
+        tri
+    };
+    let [tri0, tri1] = [project(tri0), project(tri1)];
+
+    // Convex polygons with disjoint insides are separated by a line through an edge of one of them
+    let separates = |tri: [Point2<f64>; 3], other: [Point2<f64>; 3]| {
+        (0..3).any(|k| {
+            other
+                .iter()
+                .all(|p| orient_2d(tri[k], tri[(k + 1) % 3], *p) <= 0.0)
+        })
+    };
+    !separates(tri0, tri1) && !separates(tri1, tri0)
+}
+
+/// Finds a tetrahedron containing some point, if any, by walking to it.
+/// A point on a triangle, edge, or vertex gets one of the tetrahedrons touching it.
+fn tet_containing_point<M: HasTets + HasPosition<V = Point3<f64>>>(
+    mesh: &M,
+    point: Point3<f64>,
+) -> Option<TetId> {
+    match mesh.locate_point_in_tets(point, None)? {
+        TetLocation::Tet(tet) => Some(tet),
+        TetLocation::Tri(tri) => mesh.tri_tets(tri).next(),
+        TetLocation::Edge(edge) => mesh.edge_tets(edge).next(),
+        TetLocation::Vertex(vertex) => mesh.vertex_tets(vertex).next(),
+        TetLocation::Outside => None,
+    }
+}
+
+/// Positive if `p3` is on the side of the plane through `p0`, `p1`, and `p2`
+/// that the right-hand normal points to. The sign is exact.
+fn orient_3d(p0: Point3<f64>, p1: Point3<f64>, p2: Point3<f64>, p3: Point3<f64>) -> f64 {
+    -exact::orient_3d(p0.coords, p1.coords, p2.coords, p3.coords)
+}
+
+/// Positive if `p0`, `p1`, and `p2` are in counterclockwise order. The sign is exact.
+fn orient_2d(p0: Point2<f64>, p1: Point2<f64>, p2: Point2<f64>) -> f64 {
+    exact::orient_2d(p0.coords, p1.coords, p2.coords)
+}
+
+/// Drops a coordinate of a point.
+fn project(p: Point3<f64>, axis: usize) -> Point2<f64> {
+    Point2::new(p[(axis + 1) % 3], p[(axis + 2) % 3])
+}
+
+/// Whether 3 points lie on a line. The test is exact.
+fn collinear(p0: Point3<f64>, p1: Point3<f64>, p2: Point3<f64>) -> bool {
+    (0..3).all(|axis| orient_2d(project(p0, axis), project(p1, axis), project(p2, axis)) == 0.0)
+}
+
+/// Whether a point lies on the closed segment between `p0` and `p1`. The test is exact.
+fn on_segment(p0: Point3<f64>, p1: Point3<f64>, p: Point3<f64>) -> bool {
+    collinear(p0, p1, p) && (0..3).all(|k| p[k] >= p0[k].min(p1[k]) && p[k] <= p0[k].max(p1[k]))
+}
+
+/// Whether 2 segments lie on the same line and overlap by more than a point.
+fn segments_overlap(a0: Point3<f64>, a1: Point3<f64>, b0: Point3<f64>, b1: Point3<f64>) -> bool {
+    if !collinear(a0, a1, b0) || !collinear(a0, a1, b1) {
+        return false;
+    }
+    let axis = match (0..3).find(|k| a0[*k] != a1[*k]) {
+        Some(axis) => axis,
+        None => return false,
+    };
+    let min = a0[axis].min(a1[axis]).max(b0[axis].min(b1[axis]));
+    let max = a0[axis].max(a1[axis]).min(b0[axis].max(b1[axis]));
+    min < max
+}
+
+/// Whether a segment in the plane of a triangle overlaps the inside of the triangle.
+/// The test is exact.
+fn segment_overlaps_tri(p0: Point3<f64>, p1: Point3<f64>, tri: [Point3<f64>; 3]) -> bool {
+    // Project onto an axis plane the triangle doesn't collapse in, keeping it counterclockwise
+    let axis = match (0..3).find(|axis| {
+        let [t0, t1, t2] = tri.map(|p| project(p, *axis));
+        orient_2d(t0, t1, t2) != 0.0
+    }) {
+        Some(axis) => axis,
+        None => return false,
+    };
+    let mut tri = tri.map(|p| project(p, axis));
+    if orient_2d(tri[0], tri[1], tri[2]) < 0.0 {
+        tri.swap(1, 2);
+    }
+    let [p0, p1] = [project(p0, axis), project(p1, axis)];
+
+    // Convex sets with disjoint insides are separated by a line through an edge of the triangle
+    // or through the segment
+    let separated_by_tri = (0..3).any(|k| {
+        [p0, p1]
+            .iter()
+            .all(|p| orient_2d(tri[k], tri[(k + 1) % 3], *p) <= 0.0)
+    });
+    let sides = tri.map(|p| orient_2d(p0, p1, p));
+    let separated_by_segment = sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0);
+    !separated_by_tri && !separated_by_segment
+}
+
+/// Whether 2 closed line segments intersect.
+fn segments_intersect(a0: Point2<f64>, a1: Point2<f64>, b0: Point2<f64>, b1: Point2<f64>) -> bool {
+    let on_segment = |p0: Point2<f64>, p1: Point2<f64>, p: Point2<f64>| {
+        p.x >= p0.x.min(p1.x)
+            && p.x <= p0.x.max(p1.x)
+            && p.y >= p0.y.min(p1.y)
+            && p.y <= p0.y.max(p1.y)
+    };
+
+    let sa0 = orient_2d(b0, b1, a0);
+    let sa1 = orient_2d(b0, b1, a1);
+    let sb0 = orient_2d(a0, a1, b0);
+    let sb1 = orient_2d(a0, a1, b1);
+
+    (sa0.signum() * sa1.signum() < 0.0 && sb0.signum() * sb1.signum() < 0.0)
+        || (sa0 == 0.0 && on_segment(b0, b1, a0))
+        || (sa1 == 0.0 && on_segment(b0, b1, a1))
+        || (sb0 == 0.0 && on_segment(a0, a1, b0))
+        || (sb1 == 0.0 && on_segment(a0, a1, b1))
+}
+
+/// Whether a point is inside a polygon, by ray casting.
+/// An edge crosses the ray to the right of the point if the point is on the left of the edge
+/// going up, or on its right going down.
+fn polygon_contains_point(polygon: &[Point2<f64>], p: Point2<f64>) -> bool {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .filter(|(p0, p1)| {
+            (p0.y > p.y) != (p1.y > p.y) && (orient_2d(**p0, **p1, p) > 0.0) == (p1.y > p0.y)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+/// Validates a facet and triangulates it by ear clipping,
+/// after bridging the holes to the boundary.
+/// `index` is the index of the facet, for errors.
+fn triangulate_facet(
+    vertices: &[Point3<f64>],
+    facet: &Facet,
+    index: usize,
+) -> Result<Vec<[usize; 3]>, PlcError> {
+    let mut all = facet.loops().flatten().copied().collect::<Vec<_>>();
+    let num_vertices = all.len();
+    all.sort_unstable();
+    all.dedup();
+    if all.len() != num_vertices || facet.loops().any(|l| l.len() < 3) {
+        return Err(PlcError::DegenerateFacet(index));
+    }
+
+    // Newell's method
+    let normal = facet
+        .boundary
+        .iter()
+        .zip(facet.boundary.iter().cycle().skip(1))
+        .map(|(v0, v1)| vertices[*v0].coords.cross(&vertices[*v1].coords))
+        .sum::<Vector3<f64>>();
+
+    let (min, max) = all
+        .iter()
+        .fold((vertices[all[0]], vertices[all[0]]), |(min, max), v| {
+            (min.inf(&vertices[*v]), max.sup(&vertices[*v]))
+        });
+    let size = (max - min).norm();
+    if normal.norm() <= PLANARITY_TOLERANCE * size * size {
+        return Err(PlcError::DegenerateFacet(index));
+    }
+
+    let unit_normal = normal.normalize();
+    let origin = vertices[facet.boundary[0]];
+    if all
+        .iter()
+        .any(|v| unit_normal.dot(&(vertices[*v] - origin)).abs() > PLANARITY_TOLERANCE * size)
+    {
+        return Err(PlcError::NonPlanarFacet(index));
+    }
+
+    // Project onto the axis plane the facet is most aligned with,
+    // keeping the boundary counterclockwise.
+    let axis = normal.iamax();
+    let (mut i, mut j) = ((axis + 1) % 3, (axis + 2) % 3);
+    if normal[axis] < 0.0 {
+        std::mem::swap(&mut i, &mut j);
+    }
+    let project = |v: usize| Point2::new(vertices[v][i], vertices[v][j]);
+
+    let edges = facet.edges().collect::<Vec<_>>();
+    for (k, [a0, a1]) in edges.iter().enumerate() {
+        for [b0, b1] in &edges[k + 1..] {
+            if a0 != b0
+                && a0 != b1
+                && a1 != b0
+                && a1 != b1
+                && segments_intersect(project(*a0), project(*a1), project(*b0), project(*b1))
+            {
+                return Err(PlcError::SelfIntersectingFacet(index));
+            }
+        }
+    }
+
+    let boundary = facet
+        .boundary
+        .iter()
+        .map(|v| project(*v))
+        .collect::<Vec<_>>();
+    if facet
+        .holes
+        .iter()
+        .flatten()
+        .any(|v| !polygon_contains_point(&boundary, project(*v)))
+    {
+        return Err(PlcError::HoleOutsideFacet(index));
+    }
+
+    // Bridge each hole, oriented clockwise, to the closest visible polygon vertex.
+    // Bridges can't cross the edges or the bridges before them.
+    let mut polygon = facet.boundary.clone();
+    let mut bridges = vec![];
+    for hole in &facet.holes {
+        let mut hole = hole.clone();
+        let area = hole
+            .iter()
+            .zip(hole.iter().cycle().skip(1))
+            .map(|(v0, v1)| project(*v0).coords.perp(&project(*v1).coords))
+            .sum::<f64>();
+        if area > 0.0 {
+            hole.reverse();
+        }
+
+        let bridge = hole
+            .iter()
+            .enumerate()
+            .flat_map(|(h, hv)| {
+                polygon
+                    .iter()
+                    .enumerate()
+                    .map(move |(p, pv)| (h, *hv, p, *pv))
+            })
+            .filter(|(_, hv, _, pv)| {
+                edges.iter().chain(bridges.iter()).all(|[e0, e1]| {
+                    [*hv, *pv].contains(e0)
+                        || [*hv, *pv].contains(e1)
+                        || !segments_intersect(
+                            project(*hv),
+                            project(*pv),
+                            project(*e0),
+                            project(*e1),
+                        )
+                })
+            })
+            .min_by(|(_, hv0, _, pv0), (_, hv1, _, pv1)| {
+                let d0 = (project(*hv0) - project(*pv0)).norm_squared();
+                let d1 = (project(*hv1) - project(*pv1)).norm_squared();
+                d0.partial_cmp(&d1).unwrap()
+            });
+        let (h, hv, p, pv) = bridge.ok_or(PlcError::SelfIntersectingFacet(index))?;
+        bridges.push([hv, pv]);
+
+        let mut bridged = polygon[..=p].to_vec();
+        bridged.extend(hole[h..].iter().chain(hole[..=h].iter()));
+        bridged.extend(polygon[p..].iter());
+        polygon = bridged;
+    }
+
+    // Ear clipping
+    let mut tris = vec![];
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        let ear = (0..n)
+            .find(|k| {
+                let [v0, v1, v2] = [polygon[(k + n - 1) % n], polygon[*k], polygon[(k + 1) % n]];
+                let [p0, p1, p2] = [project(v0), project(v1), project(v2)];
+                orient_2d(p0, p1, p2) > 0.0
+                    && polygon.iter().all(|v| {
+                        let p = project(*v);
+                        [v0, v1, v2].contains(v)
+                            || orient_2d(p0, p1, p) < 0.0
+                            || orient_2d(p1, p2, p) < 0.0
+                            || orient_2d(p2, p0, p) < 0.0
+                    })
+            })
+            .ok_or(PlcError::SelfIntersectingFacet(index))?;
+
+        tris.push([
+            polygon[(ear + n - 1) % n],
+            polygon[ear],
+            polygon[(ear + 1) % n],
+        ]);
+        polygon.remove(ear);
+    }
+    tris.push([polygon[0], polygon[1], polygon[2]]);
+
+    Ok(tris)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Volume of the tetrahedralization
+    fn volume(mesh: &MwbComboMesh3<Point3<f64>, (), (), ()>) -> f64 {
+        mesh.tet_ids()
+            .map(|tet| {
+                let [v0, v1, v2, v3] = tet.vertices();
+                orient_3d(
+                    mesh.position(v0),
+                    mesh.position(v1),
+                    mesh.position(v2),
+                    mesh.position(v3),
+                )
+                .abs()
+                    / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_validate_cube() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 3, 2, 1]));
+        plc.add_facet(Facet::new(vec![4, 5, 6, 7]));
+        plc.add_facet(Facet::new(vec![0, 1, 5, 4]));
+        plc.add_facet(Facet::new(vec![1, 2, 6, 5]));
+        plc.add_facet(Facet::new(vec![2, 3, 7, 6]));
+        plc.add_facet(Facet::new(vec![3, 0, 4, 7]));
+        plc.add_segment([0, 6]);
+        assert_eq!(plc.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_out_of_range() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0)]);
+        plc.add_segment([0, 2]);
+        assert_eq!(plc.validate(), Err(PlcError::VertexOutOfRange(2)));
+    }
+
+    #[test]
+    fn test_validate_duplicate_vertex() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 2.0, 3.0),
+            Point3::new(1.0, 2.0, 3.0),
+        ]);
+        assert_eq!(plc.validate(), Err(PlcError::DuplicateVertex(1, 2)));
+
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(-0.0, 1.0, -0.0),
+        ]);
+        assert_eq!(plc.validate(), Err(PlcError::DuplicateVertex(0, 2)));
+    }
+
+    #[test]
+    fn test_validate_non_planar() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.5),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2, 3]));
+        assert_eq!(plc.validate(), Err(PlcError::NonPlanarFacet(0)));
+    }
+
+    #[test]
+    fn test_validate_self_intersecting() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(3.0, 2.0, 0.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2, 3, 4]));
+        assert_eq!(plc.validate(), Err(PlcError::SelfIntersectingFacet(0)));
+    }
+
+    #[test]
+    fn test_validate_segment_crosses_facet() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.5, 0.5, -1.0),
+            Point3::new(0.5, 0.5, 1.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2]));
+        plc.add_segment([3, 4]);
+        assert_eq!(
+            plc.validate(),
+            Err(PlcError::SegmentCrossesFacet {
+                segment: 0,
+                facet: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_segments_intersect() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 2.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 2.0),
+        ]);
+        plc.add_segment([0, 1]);
+        plc.add_segment([2, 3]);
+        assert_eq!(plc.validate(), Err(PlcError::SegmentsIntersect(0, 1)));
+    }
+
+    #[test]
+    fn test_validate_vertex_on_segment() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 2.0),
+            Point3::new(1.0, 1.0, 1.0),
+        ]);
+        plc.add_segment([0, 1]);
+        assert_eq!(
+            plc.validate(),
+            Err(PlcError::VertexOnSegment {
+                vertex: 2,
+                segment: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_segment_end_on_facet() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.5, 0.5, 0.0),
+            Point3::new(0.5, 0.5, 1.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2]));
+        plc.add_segment([3, 4]);
+        assert_eq!(
+            plc.validate(),
+            Err(PlcError::VertexOnFacet {
+                vertex: 3,
+                facet: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_facet_vertex_on_facet() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.5, 0.5, 0.0),
+            Point3::new(1.5, 0.5, 1.0),
+            Point3::new(0.5, 1.5, 1.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2]));
+        plc.add_facet(Facet::new(vec![3, 4, 5]));
+        assert_eq!(
+            plc.validate(),
+            Err(PlcError::VertexOnFacet {
+                vertex: 3,
+                facet: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_segment_inside_facet() {
+        // A diagonal of a square facet
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2, 3]));
+        plc.add_segment([0, 2]);
+        assert_eq!(
+            plc.validate(),
+            Err(PlcError::SegmentCrossesFacet {
+                segment: 0,
+                facet: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_facets_intersect() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.5, -1.0, -1.0),
+            Point3::new(0.5, 2.0, -1.0),
+            Point3::new(0.5, 0.5, 1.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2]));
+        plc.add_facet(Facet::new(vec![3, 4, 5]));
+        assert_eq!(plc.validate(), Err(PlcError::FacetsIntersect(0, 1)));
+    }
+
+    #[test]
+    fn test_validate_coplanar_facets_overlap() {
+        // Triangles crossing like a hexagram, with no vertex inside the other triangle
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(4.0, 0.0, 0.0),
+            Point3::new(2.0, 4.0, 0.0),
+            Point3::new(0.0, 3.0, 0.0),
+            Point3::new(2.0, -1.0, 0.0),
+            Point3::new(4.0, 3.0, 0.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2]));
+        plc.add_facet(Facet::new(vec![3, 4, 5]));
+        assert_eq!(plc.validate(), Err(PlcError::FacetsIntersect(0, 1)));
+
+        // The same triangle twice
+        plc.facets[1] = Facet::new(vec![0, 2, 1]);
+        assert_eq!(plc.validate(), Err(PlcError::FacetsIntersect(0, 1)));
+    }
+
+    #[test]
+    fn test_validate_tilted_facet_with_hole() {
+        // A triangle with a triangular hole in the plane z = x
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(6.0, 0.0, 6.0),
+            Point3::new(0.0, 6.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(3.0, 1.0, 3.0),
+            Point3::new(1.0, 3.0, 1.0),
+            Point3::new(1.5, 1.5, 0.0),
+            Point3::new(1.5, 1.5, 3.0),
+            Point3::new(4.0, 1.0, 0.0),
+            Point3::new(4.0, 1.0, 8.0),
+        ]);
+        plc.add_facet(Facet::with_holes(vec![0, 1, 2], vec![vec![3, 4, 5]]));
+
+        // Through the hole
+        plc.add_segment([6, 7]);
+        assert_eq!(plc.validate(), Ok(()));
+
+        // Through the facet itself
+        plc.add_segment([8, 9]);
+        assert_eq!(
+            plc.validate(),
+            Err(PlcError::SegmentCrossesFacet {
+                segment: 1,
+                facet: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_triangulate_facet_with_hole() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(3.0, 0.0, 0.0),
+            Point3::new(3.0, 3.0, 0.0),
+            Point3::new(0.0, 3.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(1.0, 2.0, 0.0),
+        ]);
+        plc.add_facet(Facet::with_holes(vec![0, 1, 2, 3], vec![vec![4, 5, 6, 7]]));
+
+        let tris = &plc.triangulate_facets().unwrap()[0];
+        assert_eq!(tris.len(), 8);
+        let area = tris
+            .iter()
+            .map(|[v0, v1, v2]| {
+                let [p0, p1, p2] = [plc.vertices[*v0], plc.vertices[*v1], plc.vertices[*v2]];
+                (p1 - p0).cross(&(p2 - p0)).z / 2.0
+            })
+            .sum::<f64>();
+        assert!((area - 8.0).abs() < 1e-9, "Area is {}", area);
+    }
+
+    #[test]
+    fn test_triangulate_facet_with_holes_bridges_dont_cross() {
+        // The closest bridge for the last hole crosses the bridge of the first hole
+        let mut plc = Plc3::new();
+        plc.extend_vertices(
+            [
+                (0.0, 0.0),
+                (20.0, 0.0),
+                (20.0, 20.0),
+                (0.0, 20.0),
+                (11.0, 10.0),
+                (5.0, 9.0),
+                (4.0, 12.0),
+                (2.0, 5.0),
+                (1.0, 19.0),
+                (2.0, 6.0),
+                (10.0, 2.0),
+                (3.0, 5.0),
+                (11.0, 6.0),
+            ]
+            .iter()
+            .map(|(x, y)| Point3::new(*x, *y, 0.0)),
+        );
+        plc.add_facet(Facet::with_holes(
+            vec![0, 1, 2, 3],
+            vec![vec![4, 5, 6], vec![7, 8, 9], vec![10, 11, 12]],
+        ));
+
+        let tris = &plc.triangulate_facets().unwrap()[0];
+        let areas = tris
+            .iter()
+            .map(|[v0, v1, v2]| {
+                let [p0, p1, p2] = [plc.vertices[*v0], plc.vertices[*v1], plc.vertices[*v2]];
+                (p1 - p0).cross(&(p2 - p0)).z / 2.0
+            })
+            .collect::<Vec<_>>();
+        assert!(areas.iter().all(|area| *area > 0.0));
+        let area = areas.iter().sum::<f64>();
+        assert!((area - 374.5).abs() < 1e-9, "Area is {}", area);
+    }
+
+    #[test]
+    fn test_validate_hole_outside() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(3.0, 0.0, 0.0),
+            Point3::new(3.0, 1.0, 0.0),
+        ]);
+        plc.add_facet(Facet::with_holes(vec![0, 1, 2], vec![vec![3, 4, 5]]));
+        assert_eq!(plc.validate(), Err(PlcError::HoleOutsideFacet(0)));
+    }
+
+    #[test]
+    fn test_tetrahedralize_cube() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 3, 2, 1]));
+        plc.add_facet(Facet::new(vec![4, 5, 6, 7]));
+        plc.add_facet(Facet::new(vec![0, 1, 5, 4]));
+        plc.add_facet(Facet::new(vec![1, 2, 6, 5]));
+        plc.add_facet(Facet::new(vec![2, 3, 7, 6]));
+        plc.add_facet(Facet::new(vec![3, 0, 4, 7]));
+
+        let mesh = plc.tetrahedralize().unwrap();
+        let volume = volume(&mesh);
+        assert!((volume - 1.0).abs() < 1e-9, "Volume is {}", volume);
+    }
+
+    #[test]
+    fn test_tetrahedralize_region_seed() {
+        // A box of height 2 split in two by a facet at height 1
+        let mut plc = Plc3::new();
+        for z in &[0.0, 2.0, 1.0] {
+            plc.extend_vertices(vec![
+                Point3::new(0.0, 0.0, *z),
+                Point3::new(1.0, 0.0, *z),
+                Point3::new(1.0, 1.0, *z),
+                Point3::new(0.0, 1.0, *z),
+            ]);
+        }
+        plc.add_facet(Facet::new(vec![0, 3, 2, 1]));
+        plc.add_facet(Facet::new(vec![4, 5, 6, 7]));
+        plc.add_facet(Facet::new(vec![8, 9, 10, 11]));
+        for k in 0..4 {
+            let [v0, v1] = [k, (k + 1) % 4];
+            plc.add_facet(Facet::new(vec![v0, v1, v1 + 8, v0 + 8]));
+            plc.add_facet(Facet::new(vec![v0 + 8, v1 + 8, v1 + 4, v0 + 4]));
+        }
+
+        let mesh = plc.tetrahedralize().unwrap();
+        let volume_all = volume(&mesh);
+        assert!((volume_all - 2.0).abs() < 1e-9, "Volume is {}", volume_all);
+
+        plc.add_region_seed(Point3::new(0.25, 0.5, 0.4));
+        let mesh = plc.tetrahedralize().unwrap();
+        let volume_seeded = volume(&mesh);
+        assert!(
+            (volume_seeded - 1.0).abs() < 1e-9,
+            "Volume is {}",
+            volume_seeded
+        );
+    }
+
+    #[test]
+    fn test_tetrahedralize_seed_outside() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 2, 1]));
+        plc.add_facet(Facet::new(vec![0, 1, 3]));
+        plc.add_facet(Facet::new(vec![1, 2, 3]));
+        plc.add_facet(Facet::new(vec![2, 0, 3]));
+        plc.add_region_seed(Point3::new(5.0, 5.0, 5.0));
+        assert_eq!(
+            plc.tetrahedralize().unwrap_err(),
+            PlcError::RegionSeedOutside(0)
+        );
+    }
+
+    #[test]
+    fn test_tetrahedralize_flat() {
+        let mut plc = Plc3::new();
+        plc.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+        plc.add_facet(Facet::new(vec![0, 1, 2, 3]));
+        assert_eq!(
+            plc.tetrahedralize().unwrap_err(),
+            PlcError::Delaunay(DelaunayTetsError::AllCoplanar)
+        );
+    }
+}
@@ -1,9 +1,10 @@
 //! Traits and structs related to tetrahedrons
 
-use fnv::FnvHashMap;
+use float_ord::FloatOrd;
+use fnv::{FnvHashMap, FnvHashSet};
 use idmap::OrderedIdMap;
 use nalgebra::dimension::U3;
-use nalgebra::{allocator::Allocator, DefaultAllocator};
+use nalgebra::{allocator::Allocator, DefaultAllocator, Point3};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use simplicity as sim;
@@ -13,11 +14,16 @@ use std::iter::Map;
 use std::vec;
 use typenum::{Bit, B0, B1};
 
+use crate::alpha::{self, AlphaComplex, AlphaFiltration};
+use crate::error::MeshError;
+use crate::exact;
 use crate::private::{Key, Lock};
 use crate::tetrahedralize::index_fn;
-use crate::tri::{EdgeVertexOpps, Tri};
+use crate::tri::{check_new_tri, EdgeVertexOpps, Tri};
 use crate::tri::{HasTris, TriId, TriWalker};
-use crate::vertex::VertexId;
+use crate::validate::{self, ValidationReport};
+use crate::vertex::{Skeleton, VertexId};
+use crate::voronoi::{self, Cell};
 use crate::{
     edge::Link,
     vertex::{HasPosition, HasPositionDim, HasPositionPoint, Position},
@@ -40,13 +46,13 @@ use crate::{
 pub struct TetId(pub(crate) [VertexId; 4]);
 
 impl TryFrom<[VertexId; 4]> for TetId {
-    type Error = &'static str;
+    type Error = MeshError;
 
     fn try_from(mut v: [VertexId; 4]) -> Result<Self, Self::Error> {
         v = Self::canonicalize(v);
 
         if v[0] == v[1] || v[1] == v[2] || v[3] == v[1] || v[2] == v[3] {
-            Err("Vertices are not allowed to be the same")
+            Err(MeshError::DegenerateSimplex(v.to_vec()))
         } else {
             Ok(TetId(v))
         }
@@ -302,12 +308,37 @@ pub trait WithTets<V, E, F, T> {
     type WithTets: HasVertices<V = V> + HasEdges<E = E> + HasTris<F = F> + HasTets<T = T>;
 }
 
+/// The 2-skeleton is the vertices, edges, and triangles.
+impl<M: HasTets> Skeleton<2> for M
+where
+    M::V: Clone,
+    M::E: Clone,
+    M::F: Clone,
+{
+    type Skeleton = M::WithoutTets;
+
+    fn skeleton_r<L: Lock>(&self) -> Self::Skeleton {
+        let mut mesh = M::WithoutTets::from_vef_r::<_, _, _, Key>(
+            self.vertices().map(|(id, v)| (*id, v.clone())),
+            self.edges().map(|(id, e)| (*id, e.clone())),
+            self.tris().map(|(id, f)| (*id, f.clone())),
+            self.default_v_r::<Key>(),
+            self.default_e_r::<Key>(),
+            self.default_f_r::<Key>(),
+        );
+        *mesh.next_vertex_id_mut::<Key>() = self.next_vertex_id::<Key>();
+        mesh
+    }
+}
+
 /// For simplicial complexes that can have tetrahedrons.
 pub trait HasTets: HasTris<HigherF = B1> {
     type Tet: Tet<T = Self::T, Mwb = Self::MwbT>;
     type T;
     type MwbT: Bit;
-    type WithoutTets: HasVertices<V = Self::V> + HasEdges<E = Self::E> + HasTris<F = Self::F>;
+    type WithoutTets: HasVertices<V = Self::V>
+        + HasEdges<E = Self::E>
+        + HasTris<F = Self::F, HigherF = B0>;
     type WithMwbT: HasVertices<V = Self::V>
         + HasEdges<E = Self::E>
         + HasTris<F = Self::F>
@@ -361,10 +392,9 @@ pub trait HasTets: HasTris<HigherF = B1> {
     fn default_t_r<L: Lock>(&self) -> fn() -> Self::T;
 
     #[doc(hidden)]
-    #[cfg(feature = "obj")]
     fn obj_with_tets<L: Lock>(
         &self,
-        _data: &mut obj::ObjData,
+        _obj: &mut crate::io::obj::Obj,
         _v_inv: &FnvHashMap<VertexId, usize>,
     ) {
         panic!("Can't export tet mesh as obj");
@@ -588,6 +618,88 @@ pub trait HasTets: HasTris<HigherF = B1> {
         self.add_tet(TetId::from_valid([v3, v2, v1, vn]), self.default_tet());
     }
 
+    /// Flips in a new vertex `vn` by splitting the tet [v1, v2, v3, v4],
+    /// or reports why that can't be done.
+    /// This checks that the tet exists and that the new ones can replace it
+    /// without replacing or removing anything else, but it doesn't check the geometry.
+    fn try_flip14(
+        &mut self,
+        v1: VertexId,
+        v2: VertexId,
+        v3: VertexId,
+        v4: VertexId,
+        vn: VertexId,
+    ) -> Result<(), MeshError>
+    where
+        Self: HasTets<MwbT = B1>,
+    {
+        if !self.contains_vertex(vn) {
+            return Err(MeshError::MissingVertex(vn));
+        }
+        check_flip(
+            self,
+            &[[v1, v2, v3, v4]],
+            &[
+                [v1, v2, v3, vn],
+                [v4, v3, v2, vn],
+                [v3, v4, v1, vn],
+                [v2, v1, v4, vn],
+            ],
+        )?;
+        self.flip14(v1, v2, v3, v4, vn);
+        Ok(())
+    }
+
+    /// Flips away the triangle of the first 3 vertices,
+    /// creating an edge connecting the last 2 vertices,
+    /// or reports why that can't be done.
+    /// This checks that the tets to flip exist and that the new ones can replace them
+    /// without replacing or removing anything else, but it doesn't check the geometry.
+    fn try_flip23(
+        &mut self,
+        v1: VertexId,
+        v2: VertexId,
+        v3: VertexId,
+        vp: VertexId,
+        vn: VertexId,
+    ) -> Result<(), MeshError>
+    where
+        Self: HasTets<MwbT = B1>,
+    {
+        check_flip(
+            self,
+            &[[v1, v2, v3, vp], [v3, v2, v1, vn]],
+            &[[v1, v2, vn, vp], [v2, v3, vn, vp], [v3, v1, vn, vp]],
+        )?;
+        self.flip23(v1, v2, v3, vp, vn);
+        Ok(())
+    }
+
+    /// Flips away the edge connecting the last 2 vertices,
+    /// creating a triangle of the first 3 vertices,
+    /// or reports why that can't be done.
+    /// This checks that the tets to flip exist and that the new ones can replace them
+    /// without replacing or removing anything else, but it doesn't check the geometry.
+    fn try_flip32(
+        &mut self,
+        v1: VertexId,
+        v2: VertexId,
+        v3: VertexId,
+        vp: VertexId,
+        vn: VertexId,
+    ) -> Result<(), MeshError>
+    where
+        Self: HasTets<MwbT = B1>,
+    {
+        check_flip(
+            self,
+            &[[v1, v2, vn, vp], [v2, v3, vn, vp], [v3, v1, vn, vp]],
+            &[[v1, v2, v3, vp], [v3, v2, v1, vn]],
+        )?;
+        self.flip32(v1, v2, v3, vp, vn);
+        Ok(())
+    }
+
     /// Attempts to remove a triangle with flips and returns whether this succeeded.
     fn remove_tri_via_flips<FI: TryInto<TriId>>(
         &mut self,
@@ -857,6 +969,34 @@ pub trait HasTets: HasTris<HigherF = B1> {
         })
     }
 
+    /// Adds a tetrahedron to the mesh,
+    /// or reports why it can't be added without touching the rest of the mesh.
+    /// Unlike `add_tet`, this fails instead of replacing an existing tetrahedron
+    /// or removing the tetrahedrons in the way in a mwb tet mesh.
+    fn try_add_tet<TI>(&mut self, vertices: TI, value: Self::T) -> Result<(), MeshError>
+    where
+        TI: TryInto<TetId>,
+        MeshError: From<TI::Error>,
+    {
+        let id = vertices.try_into()?;
+        check_new_tet(self, id)?;
+        self.add_tet(id, value);
+        Ok(())
+    }
+
+    /// Extends the tetrahedron list with an iterator,
+    /// stopping at the first tetrahedron that can't be added.
+    /// The tetrahedrons before that one stay added.
+    fn try_extend_tets<TI, I>(&mut self, iter: I) -> Result<(), MeshError>
+    where
+        TI: TryInto<TetId>,
+        MeshError: From<TI::Error>,
+        I: IntoIterator<Item = (TI, Self::T)>,
+    {
+        iter.into_iter()
+            .try_for_each(|(id, value)| self.try_add_tet(id, value))
+    }
+
     /// Removes an tetrahedron from the mesh and returns the value that was there,
     /// or None if there was nothing there.
     /// Removes the edges and tris that are part of the tetrahedron if they are part of no other tetrahedrons
@@ -970,6 +1110,72 @@ pub trait HasTets: HasTris<HigherF = B1> {
         }
     }
 
+    /// Turns this mesh into a tri mesh,
+    /// keeping the ids and values of the vertices, edges, and triangles.
+    fn into_without_tets(self) -> Self::WithoutTets
+    where
+        Self: Sized,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let default_e = self.default_e_r::<Key>();
+        let default_f = self.default_f_r::<Key>();
+        let next_vertex_id = self.next_vertex_id::<Key>();
+        let (vertices, edges, tris, _) = self.into_veft_r::<Key>();
+        let mut mesh = Self::WithoutTets::from_vef_r::<_, _, _, Key>(
+            vertices, edges, tris, default_v, default_e, default_f,
+        );
+        *mesh.next_vertex_id_mut::<Key>() = next_vertex_id;
+        mesh
+    }
+
+    /// Gets the boundary surface of this mesh,
+    /// which has the triangles that are part of a tetrahedron
+    /// while their twins aren't, along with their edges and vertices.
+    /// Ids and values are kept, and the triangles face out of the tetrahedrons.
+    fn boundary_surface(&self) -> Self::WithoutTets
+    where
+        Self::V: Clone,
+        Self::E: Clone,
+        Self::F: Clone,
+    {
+        let tris = self
+            .tris()
+            .filter(|(id, _)| {
+                self.tri_vertex_opps(**id).next().is_some()
+                    && self.tri_vertex_opps(id.twin()).next().is_none()
+            })
+            .map(|(id, f)| (*id, f.clone()))
+            .collect::<Vec<_>>();
+
+        let edges = tris
+            .iter()
+            .flat_map(|(id, _)| id.edges())
+            .collect::<FnvHashSet<_>>()
+            .into_iter()
+            .map(|id| (id, self.edge(id).unwrap().clone()))
+            .collect::<Vec<_>>();
+
+        let boundary_vertices = tris
+            .iter()
+            .flat_map(|(id, _)| id.0)
+            .collect::<FnvHashSet<_>>();
+        let vertices = self
+            .vertices()
+            .filter(|(id, _)| boundary_vertices.contains(*id))
+            .map(|(id, v)| (*id, v.clone()));
+
+        let mut mesh = Self::WithoutTets::from_vef_r::<_, _, _, Key>(
+            vertices,
+            edges,
+            tris,
+            self.default_v_r::<Key>(),
+            self.default_e_r::<Key>(),
+            self.default_f_r::<Key>(),
+        );
+        *mesh.next_vertex_id_mut::<Key>() = self.next_vertex_id::<Key>();
+        mesh
+    }
+
     /// Gets a tetrahedron walker that starts at the given edge with a given vertex
     /// to form the starting triangle.
     /// Returns None if the triangle formed has no tetrahedron.
@@ -1007,6 +1213,10 @@ pub trait HasTets: HasTris<HigherF = B1> {
     /// It must actually exist.
     /// Be warned that this does not preserve the order of the vertices
     /// because the tetrahedron id is canonicalized.
+    ///
+    /// # Panics
+    /// Panics if the vertices don't form a valid tetrahedron id.
+    /// See `try_tet_walker_from_tet` for a version that reports errors instead.
     fn tet_walker_from_tet<TI: TryInto<TetId>>(&self, tet: TI) -> TetWalker<Self> {
         let tet = tet.try_into().ok().unwrap();
         TetWalker::new(
@@ -1016,6 +1226,22 @@ pub trait HasTets: HasTris<HigherF = B1> {
         )
     }
 
+    /// Gets a tetrahedron walker that starts at the given tetrahedron,
+    /// or reports why it can't: the vertices are degenerate or the tetrahedron doesn't exist.
+    /// Be warned that this does not preserve the order of the vertices
+    /// because the tetrahedron id is canonicalized.
+    fn try_tet_walker_from_tet<TI>(&self, tet: TI) -> Result<TetWalker<'_, Self>, MeshError>
+    where
+        TI: TryInto<TetId>,
+        MeshError: From<TI::Error>,
+    {
+        let tet = tet.try_into()?;
+        if !self.contains_tet(tet) {
+            return Err(MeshError::MissingSimplex(tet.into()));
+        }
+        Ok(self.tet_walker_from_tet(tet))
+    }
+
     /// Converts this into a triangle mesh where each tetrahedron
     /// turns into 4 separate vertices and 4 triangles with those vertices.
     fn to_separate_tets(&self) -> Self::WithoutTets
@@ -1060,6 +1286,63 @@ pub trait HasTets: HasTris<HigherF = B1> {
     }
 }
 
+/// Checks that a tetrahedron can be added without replacing or removing anything.
+pub(crate) fn check_new_tet<M: HasTets + ?Sized>(mesh: &M, id: TetId) -> Result<(), MeshError> {
+    check_new_tet_after_removing(mesh, id, &[])
+}
+
+/// Checks that a tetrahedron can be added without replacing or removing anything
+/// once the tetrahedrons in `removed` are gone.
+fn check_new_tet_after_removing<M: HasTets + ?Sized>(
+    mesh: &M,
+    id: TetId,
+    removed: &[TetId],
+) -> Result<(), MeshError> {
+    if let Some(v) = id.0.iter().find(|v| !mesh.contains_vertex(**v)) {
+        return Err(MeshError::MissingVertex(*v));
+    }
+    if mesh.contains_tet(id) && !removed.contains(&id) {
+        return Err(MeshError::DuplicateId(id.into()));
+    }
+
+    for tri in &id.tris() {
+        if !mesh.contains_tri(*tri) {
+            check_new_tri(mesh, *tri)?;
+        } else if <M::MwbT as Bit>::BOOL {
+            let kept = mesh
+                .tri_vertex_opps(*tri)
+                .map(|opp| TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], opp]))
+                .find(|tet| !removed.contains(tet));
+            if let Some(tet) = kept {
+                return Err(MeshError::NonManifold(tet.into()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the tets to remove in a flip exist and that the tets to add
+/// can be added once those are gone, without replacing or removing anything else.
+fn check_flip<M: HasTets + ?Sized>(
+    mesh: &M,
+    removed: &[[VertexId; 4]],
+    added: &[[VertexId; 4]],
+) -> Result<(), MeshError> {
+    let removed = removed
+        .iter()
+        .map(|tet| TetId::try_from(*tet))
+        .collect::<Result<Vec<_>, _>>()?;
+    for tet in &removed {
+        if !mesh.contains_tet(*tet) {
+            return Err(MeshError::MissingSimplex((*tet).into()));
+        }
+    }
+    for tet in added {
+        check_new_tet_after_removing(mesh, TetId::try_from(*tet)?, &removed)?;
+    }
+    Ok(())
+}
+
 /// A walker for navigating a simplicial complex by tetrahedron.
 #[derive(Debug)]
 pub struct TetWalker<'a, M: ?Sized>
@@ -1462,6 +1745,80 @@ macro_rules! impl_index_tet {
     };
 }
 
+/// Where a point is relative to a tetrahedralization
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TetLocation {
+    /// Strictly inside a tetrahedron
+    Tet(TetId),
+    /// On a triangle, but not on its edges.
+    /// The triangle is the face of a tetrahedron the point is in.
+    Tri(TriId),
+    /// On an edge, but not on its vertices
+    Edge(EdgeId),
+    /// At a vertex
+    Vertex(VertexId),
+    /// Outside the tetrahedralization
+    Outside,
+}
+
+/// Gets where a point is in a tetrahedron, or some triangle of it the point is strictly past.
+/// `start` picks which triangle to check first, so walks don't go in circles.
+fn point_location_in_tet<M>(
+    mesh: &M,
+    tet: TetId,
+    point: Point3<f64>,
+    start: usize,
+) -> Result<TetLocation, TriId>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let tris = tet.tris_and_opp();
+    let mut on = vec![];
+
+    for i in 0..4 {
+        let (tri, opp) = tris[(i + start) % 4];
+        let [a, b, c] = [
+            index_fn(mesh, tri.0[0]),
+            index_fn(mesh, tri.0[1]),
+            index_fn(mesh, tri.0[2]),
+        ];
+        let side = exact::orient_3d(a, b, c, point.coords);
+        let opp_side = exact::orient_3d(a, b, c, index_fn(mesh, opp));
+        let inside = if opp_side == 0.0 {
+            // Flat tetrahedron
+            sim::orient_3d(mesh, index_fn, tri.0[0], tri.0[1], tri.0[2], opp)
+        } else {
+            opp_side > 0.0
+        };
+
+        if side == 0.0 {
+            on.push(tri);
+        } else if (side > 0.0) != inside {
+            return Err(tri);
+        }
+    }
+
+    match on[..] {
+        [] => Ok(TetLocation::Tet(tet)),
+        [tri] => Ok(TetLocation::Tri(tri)),
+        [t0, t1] => Ok(TetLocation::Edge(
+            *t0.edges()
+                .iter()
+                .find(|edge| t1.contains_vertex(edge.0[0]) && t1.contains_vertex(edge.0[1]))
+                .unwrap(),
+        )),
+        // The point can be on all 4 planes of a flat tetrahedron without being in it
+        _ => on[0]
+            .vertices()
+            .iter()
+            .find(|v| on.iter().all(|tri| tri.contains_vertex(**v)))
+            .map(|v| TetLocation::Vertex(*v))
+            .ok_or(on[0]),
+    }
+}
+
 /// For concrete simplicial complexes with tetrahedrons
 pub trait HasPositionAndTets: HasTets + HasPosition
 where
@@ -1477,6 +1834,195 @@ where
         let v3 = self.position(tet.0[3]);
         [v0, v1, v2, v3]
     }
+
+    /// Checks the internal structure of this mesh, like `validate` on a concrete mesh does,
+    /// and also checks that every tetrahedron has a positive orientation.
+    fn validate_with_positions(&self) -> ValidationReport
+    where
+        Self: Sized + HasPosition3D,
+        Self::V: Position<Dim = U3>,
+    {
+        let mut report = ValidationReport::default();
+        validate::check_tets(self, &mut report);
+        validate::check_tet_orientations(self, &mut report);
+        report
+    }
+
+    /// Gets the alphas at which the simplices of this Delaunay tetrahedralization
+    /// enter its alpha complex
+    fn alpha_filtration(&self) -> AlphaFiltration
+    where
+        Self: Sized + HasPosition3D,
+        Self::V: Position<Dim = U3>,
+    {
+        alpha::alpha_filtration(self)
+    }
+
+    /// Gets the alpha complex of this Delaunay tetrahedralization,
+    /// which has the simplices whose critical alphas are below `alpha`.
+    /// Use `alpha_filtration` to get complexes for many alphas.
+    fn alpha_complex(&self, alpha: f64) -> AlphaComplex
+    where
+        Self: Sized + HasPosition3D,
+        Self::V: Position<Dim = U3>,
+    {
+        alpha::alpha_filtration(self).complex(alpha)
+    }
+
+    /// Gets the boundary of the alpha shape of this Delaunay tetrahedralization
+    /// as a triangle mesh with outward-pointing triangles.
+    /// Vertices and edges of the alpha complex that aren't on any of its tetrahedrons or triangles
+    /// are part of the boundary too, so every vertex outside the tetrahedrons is kept.
+    /// Vertices inside the alpha shape are left out.
+    fn alpha_shape(&self, alpha: f64) -> Self::WithoutTets
+    where
+        Self: Sized + HasPosition3D,
+        Self::V: Position<Dim = U3> + Clone,
+        Self::E: Clone,
+        Self::F: Clone,
+        Self::WithoutTets: HasTris<HigherF = B0>,
+    {
+        let complex = self.alpha_complex(alpha);
+        let tris = complex.boundary_tris(self);
+        let tet_vertices = complex
+            .tets
+            .iter()
+            .flat_map(|tet| tet.vertices().to_vec())
+            .collect::<FnvHashSet<_>>();
+        let tri_edges = complex
+            .tris
+            .iter()
+            .flat_map(|tri| {
+                tri.edges()
+                    .iter()
+                    .map(|edge| edge.undirected())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<FnvHashSet<_>>();
+
+        let mut mesh = <Self::WithoutTets as HasTris>::from_vef_r::<_, _, _, Key>(
+            vec![],
+            vec![],
+            vec![],
+            self.default_v_r::<Key>(),
+            self.default_e_r::<Key>(),
+            self.default_f_r::<Key>(),
+        );
+        mesh.extend_vertices_with_ids(
+            tris.iter()
+                .flat_map(|tri| tri.0.to_vec())
+                .chain(
+                    self.vertex_ids()
+                        .copied()
+                        .filter(|v| !tet_vertices.contains(v)),
+                )
+                .collect::<FnvHashSet<_>>()
+                .into_iter()
+                .map(|v| (v, self.vertex(v).unwrap().clone())),
+        );
+        for tri in tris {
+            for edge in &tri.edges() {
+                if let Some(value) = self.edge(*edge) {
+                    mesh.add_edge(*edge, value.clone());
+                }
+            }
+            mesh.add_tri(
+                tri,
+                self.tri(tri)
+                    .cloned()
+                    .unwrap_or_else(self.default_f_r::<Key>()),
+            );
+        }
+        for edge in complex
+            .edges
+            .iter()
+            .filter(|edge| !tri_edges.contains(&edge.undirected()))
+        {
+            mesh.add_edge(*edge, self.edge(*edge).unwrap().clone());
+        }
+        mesh
+    }
+
+    /// Gets the cells of the Voronoi diagram dual to this Delaunay tetrahedralization,
+    /// clipped to a box given as [minimum coordinates, maximum coordinates].
+    /// Cells that miss the box are skipped, and so are vertices without tetrahedrons.
+    fn voronoi_cells(&self, bounds: [Point3<f64>; 2]) -> Vec<Cell>
+    where
+        Self: Sized + HasPosition3D,
+        Self::V: Position<Dim = U3>,
+    {
+        voronoi::voronoi_cells(self, bounds)
+    }
+
+    /// Gets the cells of the power diagram dual to this weighted Delaunay tetrahedralization,
+    /// where `weight` gives the squared radius of a vertex.
+    /// Only vertices surrounded by tetrahedrons have bounded cells, so the rest are skipped.
+    fn power_cells(&self, weight: impl Fn(&Self::V) -> f64) -> Vec<Cell>
+    where
+        Self: Sized + HasPosition3D,
+        Self::V: Position<Dim = U3>,
+    {
+        voronoi::power_cells(self, weight)
+    }
+
+    /// Finds where a point is in this tetrahedralization by walking
+    /// from tetrahedron to tetrahedron toward it, using exact orientation tests.
+    /// The walk starts at `hint` if it's given and exists,
+    /// and at the closest of a few tetrahedrons sampled evenly across the mesh otherwise.
+    /// Returns None if there are no tetrahedrons.
+    ///
+    /// The tetrahedralization should be convex, like a Delaunay one.
+    /// Otherwise the walk can leave it on the way to the point and report it outside.
+    ///
+    /// This isn't just `locate_point` because meshes with tetrahedrons also have triangles,
+    /// so it would clash with `HasPositionAndTris::locate_point_in_tris`.
+    /// Each step only needs the tetrahedron across a triangle,
+    /// so it uses `tri_tets` instead of a `TetWalker`.
+    fn locate_point_in_tets(&self, point: Point3<f64>, hint: Option<TetId>) -> Option<TetLocation>
+    where
+        Self: Sized + HasPosition3D,
+        Self::V: Position<Dim = U3>,
+    {
+        let distance_squared =
+            |tet: &TetId| FloatOrd((self.position(tet.0[0]) - point).norm_squared());
+
+        // Jump
+        let num_samples = (self.num_tets() as f64).powf(0.25).ceil() as usize;
+        let mut tet = hint.filter(|tet| self.contains_tet(*tet)).or_else(|| {
+            // Samples spread over all the tets, not just the first ones
+            let stride = (self.num_tets() / num_samples.max(1)).max(1);
+            self.tet_ids()
+                .step_by(stride)
+                .take(num_samples)
+                .copied()
+                .min_by_key(distance_squared)
+        })?;
+
+        // Walk
+        for step in 0..=self.num_tets() {
+            match point_location_in_tet(self, tet, point, step) {
+                Ok(location) => return Some(location),
+                Err(tri) => match self.tri_tets(tri.twin()).next() {
+                    Some(next) => tet = next,
+                    None => return Some(TetLocation::Outside),
+                },
+            }
+        }
+
+        // The walk went in circles, so the tetrahedralization isn't Delaunay
+        Some(
+            self.tet_ids()
+                .find_map(|tet| point_location_in_tet(self, *tet, point, 0).ok())
+                .unwrap_or(TetLocation::Outside),
+        )
+    }
+}
+
+impl<M: HasTets + HasPosition> HasPositionAndTets for M
+where
+    Self::V: Position,
+    DefaultAllocator: Allocator<f64, HasPositionDim<Self>>,
+{
 }
 
 #[macro_export]
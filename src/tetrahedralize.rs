@@ -1,15 +1,506 @@
 use crate::{edge::{EdgeId, HasEdges, HasPositionAndEdges}, iter, tri::{HasTris, TriId, HasPositionAndTris}, vertex::HasVertices};
 use crate::{
-    tet::{HasTets, TetId},
-    vertex::{HasPosition3D, Position, VertexId},
+    exact,
+    mesh3::MwbComboMesh3,
+    private::Key,
+    tet::{HasTets, TetId, WithTets},
+    vertex::{HasPosition3D, IdType, Position, VertexId},
 };
 use float_ord::FloatOrd;
 use fnv::{FnvHashMap, FnvHashSet};
-use nalgebra::{Point1, Point3, Vector3, dimension::U3};
+use nalgebra::{dimension::U3, Point1, Point3, Vector2, Vector3};
+use num_bigint::{BigInt, Sign};
 use simplicity as sim;
 use typenum::B1;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
 use std::iter as it;
 
+/// A mesh along with a map from split edges to Steiner points
+/// and a map from split triangles to Steiner points.
+pub type WithSteinerPoints<M> = (M, FnvHashMap<EdgeId, VertexId>, FnvHashMap<TriId, VertexId>);
+
+/// Why a Delaunay tetrahedralization failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DelaunayTetsError {
+    /// There are fewer than 4 vertices
+    TooFewVertices,
+    /// The vertices are all at the same position
+    AllSamePosition,
+    /// The vertices are all on the same line
+    AllCollinear,
+    /// The vertices are all on the same plane
+    AllCoplanar,
+    /// Some vertex has a coordinate that is infinite or NaN
+    NonFinitePosition(VertexId),
+    /// Some vertex couldn't be inserted, which means the predicates failed
+    VertexNotInserted(VertexId),
+    /// The hole left by removing some vertex couldn't be filled, which means the predicates failed
+    VertexNotRemoved(VertexId),
+}
+
+impl fmt::Display for DelaunayTetsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFewVertices => write!(f, "fewer than 4 vertices"),
+            Self::AllSamePosition => write!(f, "all vertices are at the same position"),
+            Self::AllCollinear => write!(f, "all vertices are collinear"),
+            Self::AllCoplanar => write!(f, "all vertices are coplanar"),
+            Self::NonFinitePosition(v) => write!(f, "vertex {:?} has a non-finite position", v),
+            Self::VertexNotInserted(v) => write!(f, "vertex {:?} could not be inserted", v),
+            Self::VertexNotRemoved(v) => {
+                write!(f, "the hole left by vertex {:?} could not be filled", v)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DelaunayTetsError {}
+
+impl DelaunayTetsError {
+    /// Whether this means the vertices don't span a tetrahedralization,
+    /// as opposed to some vertex being bad
+    fn is_degenerate(self) -> bool {
+        matches!(
+            self,
+            Self::TooFewVertices | Self::AllSamePosition | Self::AllCollinear | Self::AllCoplanar
+        )
+    }
+}
+
+/// Treats vertices that don't span a tetrahedralization as having no tets instead of failing
+fn ok_if_degenerate(result: Result<(), DelaunayTetsError>) -> Result<(), DelaunayTetsError> {
+    result.or_else(|err| {
+        if err.is_degenerate() {
+            Ok(())
+        } else {
+            Err(err)
+        }
+    })
+}
+
+/// Why recovering constraint edges and triangles in a triangulation or tetrahedralization failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RecoveryError {
+    /// Recovery needed more than `MAX_STEINER_VERTICES` Steiner points
+    TooManySteinerPoints,
+    /// The tets some edge passes through couldn't be found, which means the predicates failed
+    EdgeNotTraced(EdgeId),
+    /// The edges crossing some triangle couldn't be found, which means the predicates failed
+    TriNotTraced(TriId),
+    /// Some Steiner point couldn't be inserted, which means the predicates failed
+    SteinerPointNotInserted(VertexId),
+    /// Two edges cross where rounding can't put a vertex inside the triangles around the crossing
+    CrossingNotSplit(EdgeId, EdgeId),
+    /// None of the edges crossing some edge could be flipped away,
+    /// which means the predicates failed
+    EdgeNotFlipped(EdgeId),
+}
+
+impl fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManySteinerPoints => write!(f, "over {} Steiner points", MAX_STEINER_VERTICES),
+            Self::EdgeNotTraced(e) => {
+                write!(f, "edge {:?} could not be traced through the tets", e)
+            }
+            Self::TriNotTraced(t) => write!(f, "tri {:?} could not be traced through the tets", t),
+            Self::SteinerPointNotInserted(v) => {
+                write!(f, "Steiner point {:?} could not be inserted", v)
+            }
+            Self::CrossingNotSplit(e0, e1) => write!(
+                f,
+                "edges {:?} and {:?} could not be split where they cross",
+                e0, e1
+            ),
+            Self::EdgeNotFlipped(e) => write!(
+                f,
+                "the edges crossing edge {:?} could not be flipped away",
+                e
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecoveryError {}
+
+/// Why a constrained Delaunay tetrahedralization failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConstrainedTetsError {
+    /// The Delaunay tetrahedralization of the vertices failed
+    Delaunay(DelaunayTetsError),
+    /// Recovering the surface failed
+    Recovery(RecoveryError),
+}
+
+impl fmt::Display for ConstrainedTetsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Delaunay(err) => write!(f, "Delaunay tetrahedralization failed: {}", err),
+            Self::Recovery(err) => write!(f, "surface recovery failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConstrainedTetsError {}
+
+impl From<DelaunayTetsError> for ConstrainedTetsError {
+    fn from(err: DelaunayTetsError) -> Self {
+        Self::Delaunay(err)
+    }
+}
+
+impl From<RecoveryError> for ConstrainedTetsError {
+    fn from(err: RecoveryError) -> Self {
+        Self::Recovery(err)
+    }
+}
+
+/// Steiner points past this many means edge recovery is going nowhere.
+const MAX_STEINER_VERTICES: usize = 10000;
+
+/// Refinement stops after inserting this many vertices,
+/// in case small input angles make it go on forever.
+const MAX_REFINE_VERTICES: usize = 1000000;
+
+/// How far a point may be from the plane of a boundary triangle,
+/// relative to the triangle's size, to count as lying on it.
+const COPLANAR_TOLERANCE: f64 = 1e-9;
+
+/// Parallel Delaunay tetrahedralization inserts at least this many vertices at a time
+#[cfg(feature = "rayon")]
+const MIN_PARALLEL_BATCH: usize = 256;
+
+/// Bits per coordinate of the grid that vertices get snapped to when sorting along a Hilbert curve
+const HILBERT_BITS: u32 = 21;
+
+/// The order to insert vertices in when building a Delaunay tetrahedralization.
+/// Inserting vertices close to the previous ones keeps the search for the tets
+/// to replace short.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SpatialSort {
+    /// The order of the vertex ids
+    None,
+    /// Along a Hilbert curve
+    Hilbert,
+    /// Biased randomized insertion order: rounds of doubling size,
+    /// each sorted along a Hilbert curve
+    /// (https://people.eecs.berkeley.edu/~jrs/meshpapers/delnotes.pdf, section 3.6).
+    /// Vertices get assigned to rounds by hashing their ids, so the order is deterministic.
+    #[default]
+    Brio,
+}
+
+impl SpatialSort {
+    /// Gets the vertices of a mesh in the order to insert them in
+    fn sort<M>(self, mesh: &M) -> Vec<VertexId>
+    where
+        M: HasPosition3D,
+        M::V: Position<Dim = U3>,
+    {
+        let mut v_ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        if self == SpatialSort::None {
+            return v_ids;
+        }
+
+        let hilbert = hilbert_key_fn(mesh);
+        match self {
+            SpatialSort::None => unreachable!(),
+            SpatialSort::Hilbert => v_ids.sort_by_cached_key(|v| hilbert(*v)),
+            SpatialSort::Brio => {
+                // Round k has about half the vertices of round k + 1
+                let max_round = (v_ids.len() as f64).log2() as u32;
+                v_ids.sort_by_cached_key(|v| {
+                    let round = max_round - hash_id(v.0).trailing_zeros().min(max_round);
+                    (round, hilbert(*v))
+                })
+            }
+        }
+        v_ids
+    }
+}
+
+/// Gets a function that gives the position of a vertex along a Hilbert curve
+/// through the bounding box of the mesh
+fn hilbert_key_fn<M>(mesh: &M) -> impl Fn(VertexId) -> u64 + '_
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let [min, max] = mesh.bounding_box().unwrap();
+    let scale = ((1u64 << HILBERT_BITS) - 1) as f64 / (max - min).max().max(f64::MIN_POSITIVE);
+    move |v| {
+        let coords = (mesh.position(v) - min) * scale;
+        hilbert_index([coords.x as u32, coords.y as u32, coords.z as u32])
+    }
+}
+
+/// Mixes up the bits of an id (SplitMix64)
+fn hash_id(id: IdType) -> u64 {
+    let mut x = (id as u64).wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// The position of a grid point along the 3D Hilbert curve,
+/// using Skilling's algorithm (https://doi.org/10.1063/1.1751381).
+/// Coordinates must have at most `HILBERT_BITS` bits.
+fn hilbert_index(mut x: [u32; 3]) -> u64 {
+    // Inverse undo
+    let mut q = 1 << (HILBERT_BITS - 1);
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..3 {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    // Gray encode
+    x[1] ^= x[0];
+    x[2] ^= x[1];
+    let mut t = 0;
+    let mut q = 1 << (HILBERT_BITS - 1);
+    while q > 1 {
+        if x[2] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for coord in &mut x {
+        *coord ^= t;
+    }
+
+    // Interleave
+    (0..HILBERT_BITS).rev().fold(0, |index, bit| {
+        x.iter().fold(index, |index, coord| {
+            index << 1 | (*coord >> bit & 1) as u64
+        })
+    })
+}
+
+/// Builds Delaunay tetrahedralizations with custom options.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DelaunayTetsBuilder {
+    spatial_sort: SpatialSort,
+}
+
+impl DelaunayTetsBuilder {
+    /// Creates a builder with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the order to insert vertices in. Defaults to `SpatialSort::Brio`.
+    pub fn spatial_sort(mut self, spatial_sort: SpatialSort) -> Self {
+        self.spatial_sort = spatial_sort;
+        self
+    }
+
+    /// Turns a mesh into a Delaunay tetrahedralization of its vertices.
+    /// There are no tets if there are fewer than 4 vertices or they're all coplanar.
+    /// Of vertices with the same position, only one is part of any tet.
+    ///
+    /// # Panics
+    /// Panics if some vertex can't be inserted, which happens if some position isn't finite.
+    /// Use `try_build` to get an error instead.
+    pub fn build<M, E, F, T>(
+        self,
+        mesh: M,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> <M::WithTets as HasTets>::WithMwbT
+    where
+        M: HasPosition3D + WithTets<<M as HasVertices>::V, E, F, T>,
+        M::V: Position<Dim = U3>,
+    {
+        let mut tets = without_tets(mesh, default_edge, default_tri, default_tet);
+
+        if let Err(err) =
+            ok_if_degenerate(delaunay_tets_in_place(&mut tets, self.spatial_sort, None))
+        {
+            panic!("{}", err);
+        }
+        tets
+    }
+
+    /// Turns a mesh into a Delaunay tetrahedralization of its vertices using multiple threads.
+    /// Gives the same tets as `build`.
+    ///
+    /// # Panics
+    /// Panics if some vertex can't be inserted, which happens if some position isn't finite.
+    /// Use `try_build_parallel` to get an error instead.
+    #[cfg(feature = "rayon")]
+    pub fn build_parallel<M, E, F, T>(
+        self,
+        mesh: M,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> <M::WithTets as HasTets>::WithMwbT
+    where
+        M: HasPosition3D + WithTets<<M as HasVertices>::V, E, F, T>,
+        M::V: Position<Dim = U3>,
+        <M::WithTets as HasTets>::WithMwbT: Sync,
+    {
+        let mut tets = without_tets(mesh, default_edge, default_tri, default_tet);
+
+        if let Err(err) = ok_if_degenerate(delaunay_tets_parallel_in_place(
+            &mut tets,
+            self.spatial_sort,
+        )) {
+            panic!("{}", err);
+        }
+        tets
+    }
+
+    /// Turns a mesh into a Delaunay tetrahedralization of its vertices using multiple threads,
+    /// or reports why that can't be done, like `try_build`.
+    #[cfg(feature = "rayon")]
+    pub fn try_build_parallel<M, E, F, T>(
+        self,
+        mesh: M,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> Result<<M::WithTets as HasTets>::WithMwbT, DelaunayTetsError>
+    where
+        M: HasPosition3D + WithTets<<M as HasVertices>::V, E, F, T>,
+        M::V: Position<Dim = U3>,
+        <M::WithTets as HasTets>::WithMwbT: Sync,
+    {
+        let mut tets = without_tets(mesh, default_edge, default_tri, default_tet);
+        delaunay_tets_parallel_in_place(&mut tets, self.spatial_sort)?;
+        Ok(tets)
+    }
+
+    /// Turns a mesh into a Delaunay tetrahedralization of its vertices,
+    /// or reports why that can't be done instead of working around degenerate input.
+    /// Unlike `build`, this fails if there are fewer than 4 vertices, if they're all coplanar,
+    /// or if some position isn't finite.
+    pub fn try_build<M, E, F, T>(
+        self,
+        mesh: M,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> Result<<M::WithTets as HasTets>::WithMwbT, DelaunayTetsError>
+    where
+        M: HasPosition3D + WithTets<<M as HasVertices>::V, E, F, T>,
+        M::V: Position<Dim = U3>,
+    {
+        let mut tets = without_tets(mesh, default_edge, default_tri, default_tet);
+        delaunay_tets_in_place(&mut tets, self.spatial_sort, None)?;
+        Ok(tets)
+    }
+
+    /// Turns a mesh into a weighted Delaunay (regular) tetrahedralization of its vertices,
+    /// where the weight of a vertex is its squared radius.
+    /// Redundant vertices, whose power cells are empty, are kept but are not part of any tet.
+    /// There are no tets if there are fewer than 4 vertices or they're all coplanar.
+    ///
+    /// # Panics
+    /// Panics if some vertex can't be inserted, which happens if some position isn't finite.
+    /// Use `try_build_weighted` to get an error instead.
+    pub fn build_weighted<M, E, F, T>(
+        self,
+        mesh: M,
+        weight: impl Fn(&M::V) -> f64,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> <M::WithTets as HasTets>::WithMwbT
+    where
+        M: HasPosition3D + WithTets<<M as HasVertices>::V, E, F, T>,
+        M::V: Position<Dim = U3>,
+    {
+        let weights = mesh
+            .vertices()
+            .map(|(id, v)| (*id, weight(v)))
+            .collect::<Weights>();
+        let mut tets = without_tets(mesh, default_edge, default_tri, default_tet);
+
+        if let Err(err) = ok_if_degenerate(weighted_delaunay_tets_in_place(
+            &mut tets,
+            self.spatial_sort,
+            &weights,
+        )) {
+            panic!("{}", err);
+        }
+        tets
+    }
+
+    /// Turns a mesh into a weighted Delaunay (regular) tetrahedralization of its vertices,
+    /// or reports why that can't be done, like `try_build`.
+    pub fn try_build_weighted<M, E, F, T>(
+        self,
+        mesh: M,
+        weight: impl Fn(&M::V) -> f64,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> Result<<M::WithTets as HasTets>::WithMwbT, DelaunayTetsError>
+    where
+        M: HasPosition3D + WithTets<<M as HasVertices>::V, E, F, T>,
+        M::V: Position<Dim = U3>,
+    {
+        let weights = mesh
+            .vertices()
+            .map(|(id, v)| (*id, weight(v)))
+            .collect::<Weights>();
+        let mut tets = without_tets(mesh, default_edge, default_tri, default_tet);
+        weighted_delaunay_tets_in_place(&mut tets, self.spatial_sort, &weights)?;
+        Ok(tets)
+    }
+}
+
+/// Checks that the vertices of a mesh have finite positions, which the predicates need.
+fn check_finite<M>(mesh: &M) -> Result<(), DelaunayTetsError>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    match mesh
+        .vertex_ids()
+        .find(|v| !mesh.position(**v).iter().all(|x| x.is_finite()))
+    {
+        Some(v) => Err(DelaunayTetsError::NonFinitePosition(*v)),
+        None => Ok(()),
+    }
+}
+
+/// Moves the vertices of a mesh into an empty mesh that can have tets.
+fn without_tets<M, E, F, T>(
+    mesh: M,
+    default_edge: fn() -> E,
+    default_tri: fn() -> F,
+    default_tet: fn() -> T,
+) -> <M::WithTets as HasTets>::WithMwbT
+where
+    M: HasVertices + WithTets<<M as HasVertices>::V, E, F, T>,
+{
+    let default_v = mesh.default_v_r::<Key>();
+    <M::WithTets as HasTets>::WithMwbT::from_veft_r::<_, _, _, _, Key>(
+        mesh.into_v_r::<Key>(),
+        vec![],
+        vec![],
+        vec![],
+        default_v,
+        default_edge,
+        default_tri,
+        default_tet,
+    )
+}
+
 pub(crate) fn index_fn<M>(mesh: &M, i: VertexId) -> Vector3<f64>
 where
     M: HasPosition3D,
@@ -18,19 +509,261 @@ where
     mesh.position(i).coords
 }
 
+/// Weights of the vertices of a weighted Delaunay tetrahedralization.
+/// Missing vertices have weight 0.
+type Weights = FnvHashMap<VertexId, f64>;
+
 /// Modified in-sphere test to deal with the ghost vertex.
 /// `m` is the point to test the in-sphere of; it cannot be the ghost.
-fn in_sphere_with_ghosts<M>(mesh: &M, tet: TetId, m: VertexId, ghost: VertexId) -> bool
+/// With weights, this is the power test against the orthosphere of the tet instead.
+fn in_sphere_with_ghosts<M>(
+    mesh: &M,
+    tet: TetId,
+    m: VertexId,
+    ghost: VertexId,
+    weights: Option<&Weights>,
+) -> bool
 where
-    M: HasTets<MwbT = B1> + HasPosition3D,
+    M: HasTets + HasPosition3D,
     M::V: Position<Dim = U3>,
 {
     if tet.contains_vertex(ghost) {
         let tri = tet.opp_tri(ghost);
-        sim::orient_3d(mesh, index_fn, tri.0[0], tri.0[1], tri.0[2], m)
+        let orient = exact::orient_3d(
+            index_fn(mesh, tri.0[0]),
+            index_fn(mesh, tri.0[1]),
+            index_fn(mesh, tri.0[2]),
+            index_fn(mesh, m),
+        );
+        if orient != 0.0 {
+            return orient > 0.0;
+        }
+
+        // On the plane of the hull tri, the ghost tet's circumsphere
+        // is the circumcircle of the hull tri, which is where the circumsphere of the tet on the other side meets the plane.
+        // Using that tet's in-sphere test keeps the two tests consistent,
+        // so a vertex in the circumcircle replaces both tets, and one outside it replaces neither,
+        // instead of making a flat tet with the hull tri.
+        // With weights, the power circle plays the part of the circumcircle.
+        mesh.tri_tets(tri.twin()).next().map_or(false, |tet| {
+            in_sphere_or_power_sphere(mesh, tet, m, weights)
+        })
     } else {
-        sim::in_sphere(mesh, index_fn, tet.0[0], tet.0[1], tet.0[2], tet.0[3], m)
+        in_sphere_or_power_sphere(mesh, tet, m, weights)
+    }
+}
+
+/// The perturbed in-sphere test, or the power test if there are weights.
+fn in_sphere_or_power_sphere<M>(
+    mesh: &M,
+    tet: TetId,
+    m: VertexId,
+    weights: Option<&Weights>,
+) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    match weights {
+        Some(weights) => in_power_sphere(mesh, tet, m, weights),
+        None => in_sphere(mesh, tet, m),
+    }
+}
+
+/// Whether vertex `m` is in the circumsphere of a positive tet.
+/// Ties get broken like in `in_power_sphere`, as if every vertex had an infinitesimal weight.
+/// Unlike perturbing the positions, that never makes a flat tet Delaunay,
+/// so cospherical vertices don't leave slivers of zero volume.
+fn in_sphere<M>(mesh: &M, tet: TetId, m: VertexId) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let vertices = [tet.0[0], tet.0[1], tet.0[2], tet.0[3], m];
+    let positions = vertices.map(|v| index_fn(mesh, v));
+    let det = exact::in_sphere(
+        positions[0],
+        positions[1],
+        positions[2],
+        positions[3],
+        positions[4],
+    );
+    if det != 0.0 {
+        return det > 0.0;
+    }
+    break_lifted_tie(mesh, &vertices, &positions)
+}
+
+/// Breaks a tie in the in-sphere or power test of the tet made of the first 4 vertices
+/// and the last vertex by perturbing the lifted coordinate of each vertex,
+/// by more for vertices with smaller ids, so all the tests agree on one perturbed input.
+fn break_lifted_tie<M>(mesh: &M, vertices: &[VertexId; 5], positions: &[Vector3<f64>; 5]) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    // The derivative of the determinant with respect to the lifted coordinate of row r
+    // is (-1)^r times the orientation of the other 4 points.
+    // All of those are 0 only if the tet is flat.
+    let mut order = [0, 1, 2, 3, 4];
+    order.sort_by_key(|r| vertices[*r]);
+    order
+        .iter()
+        .find_map(|r| {
+            let others = (0..5)
+                .filter(|j| j != r)
+                .map(|j| positions[j])
+                .collect::<Vec<_>>();
+            let orient = exact::orient_3d(others[0], others[1], others[2], others[3]);
+            let cofactor = if r % 2 == 0 { orient } else { -orient };
+            Some(cofactor < 0.0).filter(|_| cofactor != 0.0)
+        })
+        .unwrap_or_else(|| {
+            sim::in_sphere(
+                mesh,
+                index_fn,
+                vertices[0],
+                vertices[1],
+                vertices[2],
+                vertices[3],
+                vertices[4],
+            )
+        })
+}
+
+/// Whether the power distance from vertex `m` to the orthosphere of a positive tet is negative.
+/// The floating-point estimate is used when it's safe, and exact arithmetic otherwise.
+/// Ties get broken by `break_lifted_tie`.
+fn in_power_sphere<M>(mesh: &M, tet: TetId, m: VertexId, weights: &Weights) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let weight = |v| weights.get(&v).copied().unwrap_or(0.0);
+    let wm = weight(m);
+
+    // Lifted orientation test, relative to m
+    let pm = mesh.position(m);
+    let rows = tet
+        .0
+        .iter()
+        .map(|v| {
+            let d = mesh.position(*v) - pm;
+            let lift = d.norm_squared() - weight(*v) + wm;
+            (d, lift, d.norm_squared() + weight(*v).abs() + wm.abs())
+        })
+        .collect::<Vec<_>>();
+
+    // Expand along the lifted column
+    let (det, magnitude) = (0..4).fold((0.0, 0.0), |(det, magnitude), i| {
+        let minor = rows
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, row)| row.0)
+            .collect::<Vec<_>>();
+        let (a, b, c) = (minor[0], minor[1], minor[2].abs());
+        let (a_abs, b_abs) = (a.abs(), b.abs());
+        let permanent = a_abs.x * (b_abs.y * c.z + b_abs.z * c.y)
+            + a_abs.y * (b_abs.x * c.z + b_abs.z * c.x)
+            + a_abs.z * (b_abs.x * c.y + b_abs.y * c.x);
+        let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+        (
+            det + sign * rows[i].1 * a.dot(&b.cross(&minor[2])),
+            magnitude + rows[i].2 * permanent,
+        )
+    });
+
+    if det.abs() > 16.0 * f64::EPSILON * magnitude {
+        return det < 0.0;
     }
+
+    let vertices = [tet.0[0], tet.0[1], tet.0[2], tet.0[3], m];
+    let positions = vertices.map(|v| index_fn(mesh, v));
+    match exact_power_det_sign(&positions, &vertices.map(weight)) {
+        Ordering::Less => true,
+        Ordering::Greater => false,
+        Ordering::Equal => break_lifted_tie(mesh, &vertices, &positions),
+    }
+}
+
+/// The exact sign of the determinant that `in_power_sphere` estimates.
+/// The positions are the tet's followed by the tested point's.
+fn exact_power_det_sign(positions: &[Vector3<f64>; 5], weights: &[f64; 5]) -> Ordering {
+    // Every finite float is an integer times a power of 2,
+    // so scaling by the smallest power of 2 makes everything an integer.
+    // Weights are squared lengths, so they get the square of the scale.
+    let min_exponent =
+        |xs: &mut dyn Iterator<Item = f64>| xs.filter(|x| *x != 0.0).map(|x| decode_f64(x).1).min();
+    let coord_exp =
+        min_exponent(&mut positions.iter().flat_map(|p| p.iter().copied())).unwrap_or(0);
+    let exp = match min_exponent(&mut weights.iter().copied()) {
+        Some(weight_exp) => coord_exp.min(weight_exp.div_euclid(2)),
+        None => coord_exp,
+    };
+    let to_int = |x: f64, exp: i32| {
+        let (mantissa, x_exp) = decode_f64(x);
+        if mantissa == 0 {
+            BigInt::from(0)
+        } else {
+            BigInt::from(mantissa) << (x_exp - exp) as usize
+        }
+    };
+
+    let coords = positions
+        .iter()
+        .map(|p| p.iter().map(|x| to_int(*x, exp)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let rows = (0..4)
+        .map(|i| {
+            let d = (0..3)
+                .map(|k| &coords[i][k] - &coords[4][k])
+                .collect::<Vec<_>>();
+            let lift = d.iter().map(|x| x * x).sum::<BigInt>() - to_int(weights[i], 2 * exp)
+                + to_int(weights[4], 2 * exp);
+            (d, lift)
+        })
+        .collect::<Vec<_>>();
+
+    let det3 = |a: &[BigInt], b: &[BigInt], c: &[BigInt]| {
+        &a[0] * (&b[1] * &c[2] - &b[2] * &c[1]) - &a[1] * (&b[0] * &c[2] - &b[2] * &c[0])
+            + &a[2] * (&b[0] * &c[1] - &b[1] * &c[0])
+    };
+    let det = (0..4).fold(BigInt::from(0), |det, i| {
+        let minor = rows
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, row)| &row.0)
+            .collect::<Vec<_>>();
+        let term = &rows[i].1 * det3(minor[0], minor[1], minor[2]);
+        if i % 2 == 0 {
+            det + term
+        } else {
+            det - term
+        }
+    });
+    det.sign().cmp(&Sign::NoSign)
+}
+
+/// Splits a finite float into an integer mantissa and a power of 2
+fn decode_f64(x: f64) -> (i64, i32) {
+    let bits = x.to_bits();
+    let exp_bits = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = (bits & ((1 << 52) - 1)) as i64;
+    let (mantissa, exp) = if exp_bits == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | 1 << 52, exp_bits - 1075)
+    };
+    (
+        if x.is_sign_negative() {
+            -mantissa
+        } else {
+            mantissa
+        },
+        exp,
+    )
 }
 
 /// Whether some tet contains some vertex inside it.
@@ -74,13 +807,73 @@ where
         && sim::orient_3d(mesh, index_fn, v3, v1, vn, vp) == keep
 }
 
-fn find_tet_to_delete<M>(mesh: &M, new_vertex: VertexId, ghost: VertexId) -> TetId
+/// Gets the tetrahedrons that are adjacent to this tetrahedron,
+/// including ghost tets past the hull that aren't actually in the mesh.
+fn adjacent_tets_with_ghosts<M>(mesh: &M, tet: TetId, ghost: VertexId) -> Vec<TetId>
 where
-    M: HasTets<MwbT = B1> + HasPosition3D,
+    M: HasTets,
+{
+    let ghost_of = |hull: TriId| {
+        let twin = hull.twin();
+        TetId::from_valid([twin.0[0], twin.0[1], twin.0[2], ghost])
+    };
+    let is_hull = |tri: TriId| {
+        mesh.tri_tets(tri).next().is_some() && mesh.tri_tets(tri.twin()).next().is_none()
+    };
+
+    tet.tris()
+        .iter()
+        .flat_map(|tri| {
+            let adjacent = mesh.tri_tets(tri.twin()).collect::<Vec<_>>();
+            if !adjacent.is_empty() {
+                adjacent
+            } else if !tri.contains_vertex(ghost) {
+                vec![ghost_of(*tri)]
+            } else {
+                // The ghost tet on the other side comes from a hull tri on the same edge
+                let edge = tri.opp_edge(ghost);
+                let twin = tri.twin();
+                mesh.edge_vertex_opps(edge)
+                    .map(|opp| TriId::from_valid([edge.0[0], edge.0[1], opp]))
+                    .chain(
+                        mesh.edge_vertex_opps(edge.twin())
+                            .map(|opp| TriId::from_valid([edge.0[1], edge.0[0], opp])),
+                    )
+                    .filter(|hull| is_hull(*hull))
+                    .map(ghost_of)
+                    .filter(|tet| tet.tris().contains(&twin))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Finds a tet whose circumsphere contains the new vertex.
+/// The search starts at `hint` if given, which should be a vertex with tets near the new vertex.
+/// With weights, the new vertex might be redundant, in which case there is none.
+/// Fails if the mesh has no tets or the predicates fail to find one.
+fn find_tet_to_delete<M>(
+    mesh: &M,
+    new_vertex: VertexId,
+    ghost: VertexId,
+    hint: Option<VertexId>,
+    weights: Option<&Weights>,
+) -> Result<Option<TetId>, DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
     M::V: Position<Dim = U3>,
 {
-    // Look for closest vertex to the new vertex to add
-    let mut vertex = (mesh.tets().next().unwrap().0).0[0];
+    // Look for closest vertex to the new vertex to add.
+    // Redundant vertices have no tets, so they can't be started at.
+    let mut vertex = match hint.filter(|hint| mesh.vertex_tets(*hint).next().is_some()) {
+        Some(hint) => hint,
+        None => {
+            mesh.tet_ids()
+                .next()
+                .ok_or(DelaunayTetsError::VertexNotInserted(new_vertex))?
+                .0[0]
+        }
+    };
     while let Some(closer) = mesh
         .vertex_targets(vertex)
         .filter(|target| {
@@ -91,58 +884,302 @@ where
         vertex = closer;
     }
 
-    // The new vertex is in the circumsphere of some tet on that vertex.
-    // If not, there's a floating-point error and we search further.
-
-    iter::bfs(
+    let mut tets = iter::bfs(
         mesh.vertex_tets(vertex),
-        |tet| mesh.adjacent_tets(*tet),
+        |tet| adjacent_tets_with_ghosts(mesh, *tet, ghost),
         |_| true,
-    )
-    .find(|tet| in_sphere_with_ghosts(mesh, *tet, new_vertex, ghost))
-    .unwrap()
+    );
+
+    if weights.is_some() {
+        // The new vertex is redundant iff it's not in the orthosphere of the tet containing it.
+        // A vertex outside the hull is in some ghost tet's half-space, so it's never redundant.
+        // One on the hull has the same power test for the ghost tet and the tet inside,
+        // so missing the tet inside because of how ties are broken doesn't matter.
+        let tet = tets
+            .find(|tet| {
+                if tet.contains_vertex(ghost) {
+                    in_sphere_with_ghosts(mesh, *tet, new_vertex, ghost, weights)
+                } else {
+                    tet_contains_vertex(mesh, tet.0[0], tet.0[1], tet.0[2], tet.0[3], new_vertex)
+                }
+            })
+            .ok_or(DelaunayTetsError::VertexNotInserted(new_vertex))?;
+        return Ok(
+            Some(tet).filter(|tet| in_sphere_with_ghosts(mesh, *tet, new_vertex, ghost, weights))
+        );
+    }
+
+    // The new vertex is in the circumsphere of some tet on that vertex.
+    // If not, there's a floating-point error and we search further.
+    // A vertex at the same position as another is in none of them if the tie is broken against it,
+    // in which case it's left without tets.
+    match tets.find(|tet| in_sphere_with_ghosts(mesh, *tet, new_vertex, ghost, None)) {
+        Some(tet) => Ok(Some(tet)),
+        None if mesh.position(vertex) == mesh.position(new_vertex) => Ok(None),
+        None => Err(DelaunayTetsError::VertexNotInserted(new_vertex)),
+    }
 }
 
 fn tets_to_delete<'a, M>(
     mesh: &'a M,
     new_vertex: VertexId,
     ghost: VertexId,
-) -> impl Iterator<Item = TetId> + 'a
+    hint: Option<VertexId>,
+    weights: Option<&'a Weights>,
+) -> Result<impl Iterator<Item = TetId> + 'a, DelaunayTetsError>
 where
-    M: HasTets<MwbT = B1> + HasPosition3D,
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    Ok(iter::bfs(
+        find_tet_to_delete(mesh, new_vertex, ghost, hint, weights)?,
+        move |tet| adjacent_tets_with_ghosts(mesh, *tet, ghost),
+        move |tet| in_sphere_with_ghosts(mesh, *tet, new_vertex, ghost, weights),
+    ))
+}
+
+/// Bowyer-Watson step that retetrahedralizes the cavity of tets
+/// whose circumspheres contain a new vertex.
+/// If `real_ghosts` is false, the ghost tets aren't in the mesh and new ones don't get added.
+/// With weights, redundant vertices don't get added,
+/// and vertices the new vertex makes redundant lose their tets.
+/// Returns whether the vertex got added, which it doesn't if it's redundant.
+/// Fails without changing the mesh if there's no tet to replace.
+fn insert_vertex_with_ghosts<M>(
+    mesh: &mut M,
+    vertex: VertexId,
+    ghost: VertexId,
+    real_ghosts: bool,
+    hint: Option<VertexId>,
+    weights: Option<&Weights>,
+) -> Result<bool, DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let to_delete = tets_to_delete(mesh, vertex, ghost, hint, weights)?.collect::<Vec<_>>();
+    if to_delete.is_empty() {
+        return Ok(false);
+    }
+
+    // Get boundary
+    let tris = to_delete
+        .iter()
+        .flat_map(|tet| tet.tris().to_vec())
+        .collect::<FnvHashSet<_>>();
+    let boundary = tris
+        .iter()
+        .copied()
+        .filter(|tri| !tris.contains(&tri.twin()))
+        .filter(|tri| real_ghosts || !tri.contains_vertex(ghost))
+        .collect::<Vec<_>>();
+
+    // Retetrahedralize region
+    mesh.remove_tets(
+        to_delete
+            .into_iter()
+            .filter(|tet| real_ghosts || !tet.contains_vertex(ghost)),
+    );
+    mesh.extend_tets(
+        boundary
+            .into_iter()
+            .map(|tri| {
+                (
+                    TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], vertex]),
+                    mesh.default_tet(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+    Ok(true)
+}
+
+/// Inserts a vertex at some position into a Delaunay tetrahedralization, keeping it Delaunay.
+/// If the position is outside the hull, the tets it sees on the hull get replaced too.
+/// If the mesh has no tets yet, it gets tetrahedralized once its vertices aren't all coplanar.
+/// If that fails, the new vertex and the tets made so far get removed.
+pub(crate) fn insert_vertex_delaunay<M>(
+    mesh: &mut M,
+    position: Point3<f64>,
+) -> Result<VertexId, DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let vertex = mesh.add_with_position(position);
+    if !position.iter().all(|x| x.is_finite()) {
+        mesh.remove_vertex(vertex);
+        return Err(DelaunayTetsError::NonFinitePosition(vertex));
+    }
+
+    if mesh.num_tets() == 0 {
+        if let Err(err) = ok_if_degenerate(delaunay_tets_in_place(mesh, SpatialSort::Hilbert, None))
+        {
+            mesh.clear_tets();
+            mesh.remove_vertex(vertex);
+            return Err(err);
+        }
+        return Ok(vertex);
+    }
+
+    // Ghost vertex, just so ghost tets have an id
+    let ghost = mesh.add_with_position(Point1::new(f64::INFINITY).xxx());
+    let inserted = insert_vertex_with_ghosts(mesh, vertex, ghost, false, None, None);
+    mesh.remove_vertex(ghost);
+    if let Err(err) = inserted {
+        mesh.remove_vertex(vertex);
+        return Err(err);
+    }
+    Ok(vertex)
+}
+
+/// Removes a vertex from a Delaunay tetrahedralization, keeping it Delaunay,
+/// and returns its value.
+/// The cavity of tets the vertex was part of gets filled with the tets inside it
+/// from the Delaunay tetrahedralization of its neighbors.
+/// If the neighbors are coplanar or there are fewer than 4 of them, the cavity is flat
+/// and doesn't get filled.
+/// If filling fails, the mesh is left unchanged.
+pub(crate) fn remove_vertex_delaunay<M>(
+    mesh: &mut M,
+    vertex: VertexId,
+) -> Result<Option<M::V>, DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
     M::V: Position<Dim = U3>,
 {
-    iter::bfs(
-        std::iter::once(find_tet_to_delete(mesh, new_vertex, ghost)),
-        move |tet| mesh.adjacent_tets(*tet),
-        move |tet| in_sphere_with_ghosts(mesh, *tet, new_vertex, ghost),
+    let link = mesh
+        .vertex_tets(vertex)
+        .map(|tet| tet.opp_tri(vertex))
+        .collect::<FnvHashSet<_>>();
+    let link_vertices = link
+        .iter()
+        .flat_map(|tri| tri.vertices().to_vec())
+        .collect::<FnvHashSet<_>>();
+
+    // Keep the ids so simulation of simplicity breaks ties the same way
+    let mut neighbors = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+        Point3::origin,
+        || (),
+        || (),
+        || (),
+    );
+    neighbors.extend_vertices_with_ids(link_vertices.into_iter().map(|v| (v, mesh.position(v))));
+    ok_if_degenerate(delaunay_tets_in_place(
+        &mut neighbors,
+        SpatialSort::None,
+        None,
+    ))?;
+
+    // The tets inside the link, which separates them from the rest
+    let cavity = iter::bfs(
+        link.iter()
+            .flat_map(|tri| neighbors.tri_tet(*tri))
+            .collect::<Vec<_>>(),
+        |tet| {
+            tet.tris()
+                .iter()
+                .filter(|tri| !link.contains(tri))
+                .flat_map(|tri| neighbors.tri_tet(tri.twin()))
+                .collect::<Vec<_>>()
+        },
+        |_| true,
     )
+    .collect::<Vec<_>>();
+
+    // Unless the neighbors are degenerate,
+    // the filling has to match the link and can't have flat tets
+    let mismatched = neighbors.num_tets() > 0
+        && link
+            .iter()
+            .any(|tri| !neighbors.contains_tri(*tri) && !neighbors.contains_tri(tri.twin()));
+    let flat = cavity.iter().any(|tet| {
+        let [p0, p1, p2, p3] = tet.0.map(|v| index_fn(&neighbors, v));
+        exact::orient_3d(p0, p1, p2, p3) <= 0.0
+    });
+    if mismatched || flat {
+        return Err(DelaunayTetsError::VertexNotRemoved(vertex));
+    }
+
+    let value = mesh.remove_vertex(vertex);
+    mesh.extend_tets(
+        cavity
+            .into_iter()
+            .map(|tet| (tet, mesh.default_tet()))
+            .collect::<Vec<_>>(),
+    );
+    Ok(value)
 }
 
 /// Implementation of the Bowyer-Watson algorithm,
 /// with ghost tetrahedrons 👻 (https://people.eecs.berkeley.edu/~jrs/meshpapers/delnotes.pdf, section 3.4)
 /// to avoid the concave tetrahedralization problem that happens with a super tet.
-pub(crate) fn delaunay_tets<M>(mut mesh: M) -> M
+pub(crate) fn delaunay_tets<M>(mut mesh: M) -> Result<M, DelaunayTetsError>
 where
-    M: HasTets<MwbT = B1> + HasPosition3D,
+    M: HasTets + HasPosition3D,
     M::V: Position<Dim = U3>,
 {
-    // It takes 4 vertices to make a tet
-    if mesh.num_vertices() < 4 {
-        return mesh;
+    delaunay_tets_in_place(&mut mesh, SpatialSort::default(), None)?;
+    Ok(mesh)
+}
+
+/// Finds the indexes of 4 vertices that aren't coplanar, looking from the end of the list,
+/// or reports how the vertices are degenerate if there are none.
+fn find_first_tet<M>(mesh: &M, v_ids: &[VertexId]) -> Result<[usize; 4], DelaunayTetsError>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    if v_ids.len() < 4 {
+        return Err(DelaunayTetsError::TooFewVertices);
     }
 
-    let mut v_ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+    let pos = |i: usize| index_fn(mesh, v_ids[i]);
+    let collinear = |a: Vector3<f64>, b: Vector3<f64>, c: Vector3<f64>| {
+        let xy = |v: Vector3<f64>| Vector2::new(v.x, v.y);
+        let yz = |v: Vector3<f64>| Vector2::new(v.y, v.z);
+        let zx = |v: Vector3<f64>| Vector2::new(v.z, v.x);
+        exact::orient_2d(xy(a), xy(b), xy(c)) == 0.0
+            && exact::orient_2d(yz(a), yz(b), yz(c)) == 0.0
+            && exact::orient_2d(zx(a), zx(b), zx(c)) == 0.0
+    };
+
+    let i0 = v_ids.len() - 1;
+    let i1 = (0..i0)
+        .rev()
+        .find(|i| pos(*i) != pos(i0))
+        .ok_or(DelaunayTetsError::AllSamePosition)?;
+    let i2 = (0..i0)
+        .rev()
+        .find(|i| !collinear(pos(i0), pos(i1), pos(*i)))
+        .ok_or(DelaunayTetsError::AllCollinear)?;
+    let i3 = (0..i0)
+        .rev()
+        .find(|i| exact::orient_3d(pos(i0), pos(i1), pos(i2), pos(*i)) != 0.0)
+        .ok_or(DelaunayTetsError::AllCoplanar)?;
+    Ok([i0, i1, i2, i3])
+}
+
+/// Adds the first tet of the Bowyer-Watson algorithm using vertices near the end of the list
+/// and surrounds it with ghost tets. Returns the ghost vertex and the first tet.
+/// Fails without changing the mesh if the vertices are all coplanar.
+fn add_first_tet<M>(
+    mesh: &mut M,
+    v_ids: &mut Vec<VertexId>,
+) -> Result<(VertexId, TetId), DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let indexes = find_first_tet(mesh, v_ids)?;
+    let [v0, v1, mut v2, mut v3] = indexes.map(|i| v_ids[i]);
+    v_ids.retain(|v| ![v0, v1, v2, v3].contains(v));
 
     // Ghost vertex
     let ghost = mesh.add_with_position(Point1::new(f64::INFINITY).xxx());
 
     // First tet
-    let v0 = v_ids.pop().unwrap();
-    let v1 = v_ids.pop().unwrap();
-    let mut v2 = v_ids.pop().unwrap();
-    let mut v3 = v_ids.pop().unwrap();
-    if !sim::orient_3d(&mesh, index_fn, v0, v1, v2, v3) {
+    if !sim::orient_3d(mesh, index_fn, v0, v1, v2, v3) {
         std::mem::swap(&mut v2, &mut v3);
     }
     let first = TetId::from_valid([v0, v1, v2, v3]);
@@ -152,52 +1189,298 @@ where
     for tri in &first.tris() {
         mesh.add_tet([tri.0[0], tri.0[2], tri.0[1], ghost], mesh.default_tet());
     }
+    Ok((ghost, first))
+}
 
-    while let Some(vertex) = v_ids.pop() {
-        let to_delete = tets_to_delete(&mesh, vertex, ghost).collect::<Vec<_>>();
-
-        // Get boundary
-        let tris = to_delete
-            .iter()
-            .flat_map(|tet| tet.tris().to_vec())
-            .collect::<FnvHashSet<_>>();
-        let boundary = tris
-            .iter()
-            .copied()
-            .filter(|tri| !tris.contains(&tri.twin()))
-            .collect::<Vec<_>>();
+/// Bowyer-Watson. With weights, this makes the weighted Delaunay tetrahedralization,
+/// and redundant vertices are left without tets.
+/// Fails without adding any tets if the vertices don't span a tetrahedralization.
+fn delaunay_tets_in_place<M>(
+    mesh: &mut M,
+    spatial_sort: SpatialSort,
+    weights: Option<&Weights>,
+) -> Result<(), DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    check_finite(mesh)?;
+    // It takes 4 vertices to make a tet
+    if mesh.num_vertices() < 4 {
+        return Err(DelaunayTetsError::TooFewVertices);
+    }
 
-        // Retetrahedralize region
-        mesh.remove_tets(to_delete);
-        mesh.extend_tets(
-            boundary
-                .into_iter()
-                .map(|tri| {
-                    (
-                        TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], vertex]),
-                        mesh.default_tet(),
-                    )
-                })
-                .collect::<Vec<_>>(),
-        );
+    // Vertices get popped from the back
+    let mut v_ids = spatial_sort.sort(mesh);
+    v_ids.reverse();
+    let (ghost, _) = add_first_tet(mesh, &mut v_ids)?;
+
+    // The last vertex inserted is close to the next one if the vertices are sorted.
+    // Redundant vertices don't get inserted, which isn't an error.
+    let mut hint = None;
+    let mut result = Ok(());
+    while let Some(vertex) = v_ids.pop() {
+        if let Err(err) = insert_vertex_with_ghosts(mesh, vertex, ghost, true, hint, weights) {
+            result = Err(err);
+            break;
+        }
+        hint = Some(vertex);
     }
 
-    //assert!(mesh.tri_ids().all(|tri| mesh.contains_tri(tri.twin())), "Delaunay tetrahedralization is not manifold");
+    mesh.remove_vertex(ghost);
+    result
+}
+
+/// Bowyer-Watson for the weighted Delaunay tetrahedralization
+fn weighted_delaunay_tets_in_place<M>(
+    mesh: &mut M,
+    spatial_sort: SpatialSort,
+    weights: &Weights,
+) -> Result<(), DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    // With all the weights the same, the power test is the in-sphere test
+    let first = weights.values().next().copied();
+    let weights = Some(weights).filter(|weights| weights.values().any(|w| Some(*w) != first));
+    delaunay_tets_in_place(mesh, spatial_sort, weights)
+}
+
+/// The tris of the convex hull of the vertices of a mesh, pointing outward.
+/// These are the tris on the boundary of the Delaunay tetrahedralization.
+pub(crate) fn convex_hull_tris<M>(mesh: &M) -> Result<Vec<TriId>, DelaunayTetsError>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    // Keep the ids so simulation of simplicity breaks ties the same way
+    let mut tets = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+        Point3::origin,
+        || (),
+        || (),
+        || (),
+    );
+    tets.extend_vertices_with_ids(mesh.vertex_ids().map(|v| (*v, mesh.position(*v))));
+    ok_if_degenerate(delaunay_tets_in_place(
+        &mut tets,
+        SpatialSort::default(),
+        None,
+    ))?;
+
+    Ok(tets
+        .tri_ids()
+        .copied()
+        .filter(|tri| !tets.contains_tri(tri.twin()))
+        .collect())
+}
+
+/// Parallel Bowyer-Watson. The first vertices get inserted sequentially.
+/// After that, the cavities of batches of vertices spread through the mesh get found in parallel,
+/// and the ones that don't touch the cavities before them in the batch get retetrahedralized.
+/// The rest wait for the next batch.
+/// Since the Delaunay tetrahedralization is unique (with simulation of simplicity),
+/// this gives the same tets as the sequential version.
+#[cfg(feature = "rayon")]
+fn delaunay_tets_parallel_in_place<M>(
+    mesh: &mut M,
+    spatial_sort: SpatialSort,
+) -> Result<(), DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D + Sync,
+    M::V: Position<Dim = U3>,
+{
+    check_finite(mesh)?;
+    // It takes 4 vertices to make a tet
+    if mesh.num_vertices() < 4 {
+        return Err(DelaunayTetsError::TooFewVertices);
+    }
 
+    let keys = {
+        let hilbert = hilbert_key_fn(mesh);
+        mesh.vertex_ids()
+            .map(|v| (*v, hilbert(*v)))
+            .collect::<FnvHashMap<_, _>>()
+    };
+    let mut v_ids = spatial_sort.sort(mesh);
+    v_ids.reverse();
+    let (ghost, first) = add_first_tet(mesh, &mut v_ids)?;
+    v_ids.reverse();
+
+    let result = insert_vertices_parallel(mesh, v_ids, ghost, first, &keys);
     mesh.remove_vertex(ghost);
-    mesh
+    result
+}
+
+/// Inserts vertices in order into a Delaunay tetrahedralization with ghost tets
+/// that starts with the tet `first`, in parallel batches after the first few.
+/// `keys` has the positions of the vertices along a Hilbert curve.
+#[cfg(feature = "rayon")]
+fn insert_vertices_parallel<M>(
+    mesh: &mut M,
+    mut v_ids: Vec<VertexId>,
+    ghost: VertexId,
+    first: TetId,
+    keys: &FnvHashMap<VertexId, u64>,
+) -> Result<(), DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D + Sync,
+    M::V: Position<Dim = U3>,
+{
+    use rayon::prelude::*;
+    use std::collections::BTreeMap;
+
+    // Inserted vertices by position along a Hilbert curve,
+    // to find nearby vertices to start searches at
+    let mut inserted = first
+        .0
+        .iter()
+        .map(|v| (keys[v], *v))
+        .collect::<BTreeMap<_, _>>();
+    let nearby = |inserted: &BTreeMap<u64, VertexId>, v: VertexId| {
+        inserted
+            .range(..keys[&v])
+            .next_back()
+            .or_else(|| inserted.range(keys[&v]..).next())
+            .map(|(_, v)| *v)
+    };
+
+    let num_sequential = v_ids.len().min(MIN_PARALLEL_BATCH * 4);
+    for vertex in v_ids.drain(..num_sequential) {
+        insert_vertex_with_ghosts(mesh, vertex, ghost, true, nearby(&inserted, vertex), None)?;
+        inserted.insert(keys[&vertex], vertex);
+    }
+
+    let mut pending = v_ids;
+    while !pending.is_empty() {
+        // Spread the batch out over a window of upcoming vertices so cavities rarely touch
+        let batch_size = (inserted.len() / 8)
+            .max(MIN_PARALLEL_BATCH)
+            .min(pending.len());
+        let window = pending.len().min(batch_size * 4);
+        let stride = window / batch_size;
+        let (batch, rest): (Vec<_>, Vec<_>) = pending
+            .drain(..window)
+            .enumerate()
+            .partition(|(i, _)| i % stride == 0 && i / stride < batch_size);
+        pending.splice(0..0, rest.into_iter().map(|(_, v)| v));
+
+        let cavities = {
+            let mesh = &*mesh;
+            let inserted = &inserted;
+            batch
+                .into_par_iter()
+                .map(|(_, vertex)| {
+                    let cavity =
+                        tets_to_delete(mesh, vertex, ghost, nearby(inserted, vertex), None)?
+                            .collect::<FnvHashSet<_>>();
+                    let adjacent = cavity
+                        .iter()
+                        .flat_map(|tet| mesh.adjacent_tets(*tet))
+                        .filter(|tet| !cavity.contains(tet))
+                        .collect::<Vec<_>>();
+                    Ok((vertex, cavity, adjacent))
+                })
+                .collect::<Result<Vec<_>, DelaunayTetsError>>()?
+        };
+
+        // A cavity that touches an earlier one might have changed because of it
+        let mut removed = FnvHashSet::default();
+        let mut touched = FnvHashSet::default();
+        let mut deferred = vec![];
+        for (vertex, cavity, adjacent) in cavities {
+            if cavity.iter().any(|tet| touched.contains(tet))
+                || adjacent.iter().any(|tet| removed.contains(tet))
+            {
+                deferred.push(vertex);
+                continue;
+            }
+            if cavity.is_empty() {
+                return Err(DelaunayTetsError::VertexNotInserted(vertex));
+            }
+
+            let tris = cavity
+                .iter()
+                .flat_map(|tet| tet.tris().to_vec())
+                .collect::<FnvHashSet<_>>();
+            let boundary = tris
+                .iter()
+                .copied()
+                .filter(|tri| !tris.contains(&tri.twin()))
+                .collect::<Vec<_>>();
+            mesh.remove_tets(cavity.iter().copied());
+            mesh.extend_tets(
+                boundary
+                    .into_iter()
+                    .map(|tri| {
+                        (
+                            TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], vertex]),
+                            mesh.default_tet(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            );
+
+            touched.extend(cavity.iter().copied().chain(adjacent));
+            removed.extend(cavity);
+            inserted.insert(keys[&vertex], vertex);
+        }
+        pending.splice(0..0, deferred);
+    }
+    Ok(())
+}
+
+/// The first tri that an edge crosses on its way out of its source vertex
+fn first_tri_crossed<M>(mesh: &M, edge: EdgeId) -> Result<TriId, RecoveryError>
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    mesh.vertex_tri_opps(edge.0[0])
+        .find(|tri| tri_intersects_edge(mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1]))
+        .ok_or(RecoveryError::EdgeNotTraced(edge))
+}
+
+/// Steps through the tet on the other side of a tri that an edge crosses.
+/// Returns the vertex of that tet opposite the tri, and the next tri the edge crosses
+/// unless that vertex is the target of the edge.
+fn next_tri_crossed<M>(
+    mesh: &M,
+    tri: TriId,
+    edge: EdgeId,
+) -> Result<(VertexId, Option<TriId>), RecoveryError>
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let twin = tri.twin();
+    let vertex = mesh
+        .tri_vertex_opp(twin)
+        .ok_or(RecoveryError::EdgeNotTraced(edge))?;
+    if vertex == edge.0[1] {
+        return Ok((vertex, None));
+    }
+    twin.edges()
+        .iter()
+        .map(|e| TriId::from_valid([e.0[1], e.0[0], vertex]))
+        .find(|tri| tri_intersects_edge(mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1]))
+        .map(|tri| (vertex, Some(tri)))
+        .ok_or(RecoveryError::EdgeNotTraced(edge))
 }
 
 /// Recover as many edges as possible in the tetrahedralization.
 /// Returns a map from split edges to Steiner points.
-pub(crate) fn recover_edges<M, EM>(mesh: &mut M, edge_mesh: &mut EM, specific_edges: Option<&[EdgeId]>) -> FnvHashMap<EdgeId, VertexId>
+///
+/// Fails if recovery needs more than `MAX_STEINER_VERTICES` Steiner points,
+/// or if the predicates fail.
+pub(crate) fn recover_edges<M, EM>(
+    mesh: &mut M,
+    edge_mesh: &mut EM,
+    specific_edges: Option<&[EdgeId]>,
+) -> Result<FnvHashMap<EdgeId, VertexId>, RecoveryError>
 where
     M: HasTets<MwbT = B1> + HasPosition3D,
     M::V: Position<Dim = U3> + Clone,
-    M::E: Clone,
-    M::F: Clone,
-    M::T: Clone,
-    M::WithoutTets: HasTris<HigherF = typenum::B0>,
     EM: HasVertices<V = M::V> + HasEdges + HasPosition3D,
     EM::V: Position<Dim = U3>,
     EM::E: Clone,
@@ -231,37 +1514,16 @@ where
                 while !mesh.contains_edge(edge) {
                     // Look for next triangle that intersects the edge to recover
                     if let Some(tri) = interfering_tris.last() {
-                        let twin = tri.twin();
-                        let vertex = mesh.tri_vertex_opp(twin).expect(
-                            &format!("Tri: {:?}, contains: {}, contains twin: {}", tri,
-                                mesh.contains_tri(*tri), mesh.contains_tri(tri.twin()))
-                        );
-                        if vertex == edge.0[1] {
-                            // Could not recover edge
-                            to_recover.push(edge);
-                            break;
+                        match next_tri_crossed(mesh, *tri, edge)? {
+                            (_, Some(next)) => interfering_tris.push(next),
+                            (_, None) => {
+                                // Could not recover edge
+                                to_recover.push(edge);
+                                break;
+                            }
                         }
-                        interfering_tris.push(
-                            twin.edges()
-                                .iter()
-                                .map(|edge| TriId::from_valid([edge.0[1], edge.0[0], vertex]))
-                                .find(|tri| {
-                                    tri_intersects_edge(
-                                        mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1],
-                                    )
-                                })
-                                .unwrap(),
-                        );
                     } else {
-                        interfering_tris.push(
-                            mesh.vertex_tri_opps(edge.0[0])
-                                .find(|tri| {
-                                    tri_intersects_edge(
-                                        mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1],
-                                    )
-                                })
-                                .unwrap(),
-                        ); // It must exist
+                        interfering_tris.push(first_tri_crossed(mesh, edge)?);
                     }
 
                     // Try to perform flips back to the source vertex of the edge to recover
@@ -290,10 +1552,8 @@ where
             }
         }
 
-        if mesh.num_vertices() >= 10000 {
-            println!("Giving up and writing current mesh");
-            mesh.to_separate_tets().write_obj("assets/too_much_steiner.obj").unwrap();
-            panic!("Over 10000 Steiner points!");
+        if num_steiner >= MAX_STEINER_VERTICES {
+            return Err(RecoveryError::TooManySteinerPoints);
         }
 
         // Add Steiner vertices
@@ -301,10 +1561,26 @@ where
         while let Some(edge) = curr_to_recover.pop() {
             // Check for sneaky recovery
             if !mesh.contains_edge(edge) {
+                // Find the tets the edge passes through before the mesh changes
+                let mut tri = first_tri_crossed(mesh, edge)?;
+                let mut start = vec![TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], edge.0[0]])];
+                loop {
+                    let (vertex, next) = next_tri_crossed(mesh, tri, edge)?;
+                    let twin = tri.twin();
+                    start.push(TetId::from_valid([twin.0[0], twin.0[1], twin.0[2], vertex]));
+                    match next {
+                        Some(next) => tri = next,
+                        None => break,
+                    }
+                }
+
                 let pos = [mesh.position(edge.0[0]), mesh.position(edge.0[1])];
-                let vertex = mesh.add_with_position(Point3::from((pos[0].coords + pos[1].coords) / 2.0));
+                let value = mesh
+                    .default_vertex()
+                    .with_position(Point3::from((pos[0].coords + pos[1].coords) / 2.0));
+                let vertex = mesh.add_vertex(value.clone());
 
-                edge_mesh.add_vertex_with_id(vertex, mesh.vertex(vertex).unwrap().clone());
+                edge_mesh.add_vertex_with_id(vertex, value);
                 edge_mesh.flip12(edge, vertex);
                 for new_edge in edge_mesh.vertex_edges_out(vertex) {
                     to_recover.push(new_edge);
@@ -312,79 +1588,45 @@ where
                 steiner_map.insert(edge, vertex);
                 num_steiner += 1;
 
-                // Find tet to remove
-                let mut tri = mesh.vertex_tri_opps(edge.0[0])
-                    .find(|tri| {
-                        tri_intersects_edge(
-                            mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1],
-                        )
+                let tet = iter::bfs(start, |tet| mesh.adjacent_tets(*tet), |_| true)
+                    .find(|tet| {
+                        tet_contains_vertex(mesh, tet.0[0], tet.0[1], tet.0[2], tet.0[3], vertex)
                     })
-                    .unwrap();
-                let mut end = false;
-
-                let start = it::successors(Some(TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], edge.0[0]])),
-                    |tet| {
-                        let twin = tri.twin();
-                        let vertex = mesh.tri_vertex_opp(twin).unwrap();
-                        if end {
-                            None
-                        } else if vertex == edge.0[1] {
-                            end = true;
-                            Some(TetId::from_valid([twin.0[0], twin.0[1], twin.0[2], vertex]))
-                        } else {
-                            tri = twin.edges()
-                                .iter()
-                                .map(|edge| TriId::from_valid([edge.0[1], edge.0[0], vertex]))
-                                .find(|tri| {
-                                    tri_intersects_edge(
-                                        mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1],
-                                    )
-                                })
-                                .unwrap();
-                            Some(TetId::from_valid([twin.0[0], twin.0[1], twin.0[2], vertex]))
-                        }
-                    });
-
-                let tet = iter::bfs(
-                    start,
-                    |tet| mesh.adjacent_tets(*tet),
-                    |_| true,
-                ).find(|tet| tet_contains_vertex(mesh, tet.0[0], tet.0[1], tet.0[2], tet.0[3], vertex)).expect(
-                    &format!("Failed to add tet to vertex {:?} at {:?}", vertex, mesh.position(vertex))
-                );
+                    .ok_or(RecoveryError::SteinerPointNotInserted(vertex))?;
 
                 mesh.flip14(tet.0[0], tet.0[1], tet.0[2], tet.0[3], vertex);
-            } else {
-                println!("Sneaky recovery of {:?}", edge);
             }
         }
     }
 
-    println!("Added {} Steiner vertices to recover edges.", num_steiner);
-
-    steiner_map
+    Ok(steiner_map)
 }
 
 /// Recover as many tris as possible in the tetrahedralization.
 /// Returns a map from split tris to Steiner points.
-pub(crate) fn recover_tris<M, FM>(mesh: &mut M, tri_mesh: &mut FM, edge_steiner_map: &mut FnvHashMap<EdgeId, VertexId>) -> FnvHashMap<TriId, VertexId>
+///
+/// Fails if recovery needs more than `MAX_STEINER_VERTICES` Steiner points,
+/// or if the predicates fail.
+pub(crate) fn recover_tris<M, FM>(
+    mesh: &mut M,
+    tri_mesh: &mut FM,
+    edge_steiner_map: &mut FnvHashMap<EdgeId, VertexId>,
+) -> Result<FnvHashMap<TriId, VertexId>, RecoveryError>
 where
     M: HasTets<MwbT = B1> + HasPosition3D,
     M::V: Position<Dim = U3> + Clone,
-    M::E: Clone,
-    M::F: Clone,
-    M::T: Clone,
-    M::WithoutTets: HasTris<HigherF = typenum::B0>,
     FM: HasVertices<V = M::V> + HasTris + HasPosition3D,
     FM::V: Position<Dim = U3>,
     FM::F: Clone,
     FM::E: Clone,
 {
-    let mut tris = tri_mesh.tri_ids().map(|tri| tri.undirected()).collect::<FnvHashSet<_>>();
+    let tris = tri_mesh
+        .tri_ids()
+        .map(|tri| tri.undirected())
+        .collect::<FnvHashSet<_>>();
     let mut to_recover = tris.into_iter().collect::<Vec<_>>();
 
     let mut curr_to_recover = vec![];
-    let mut num_steiner_vertices = 0;
     let mut steiner_map = FnvHashMap::default();
 
     while !to_recover.is_empty() {
@@ -401,7 +1643,8 @@ where
                     // Get edges intersecting triangle
                     let edge = mesh.edge_edge_opps(tri.edges()[0])
                         .find(|edge| tri_intersects_edge(mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1]))
-                        .unwrap().undirected();
+                        .ok_or(RecoveryError::TriNotTraced(tri))?
+                        .undirected();
                     let mut interfering_edges = iter::bfs(
                         it::once(edge),
                         |edge| {
@@ -445,16 +1688,28 @@ where
                 // Check for sneaky recovery
                 // Don't need to check twin since boundary tris are automatically recovered
                 if !mesh.contains_tri(tri) {
-                    let pos = [mesh.position(tri.0[0]), mesh.position(tri.0[1]), mesh.position(tri.0[2])];
-                    let vertex = mesh.add_with_position(Point3::from((pos[0].coords + pos[1].coords + pos[2].coords) / 3.0));
-
-                    tri_mesh.add_vertex_with_id(vertex, mesh.vertex(vertex).unwrap().clone());
-                    tri_mesh.flip13(tri, vertex);
+                    if steiner_map.len() + edge_steiner_map.len() >= MAX_STEINER_VERTICES {
+                        return Err(RecoveryError::TooManySteinerPoints);
+                    }
 
                     // Find tet to remove
                     let edge = mesh.edge_edge_opps(tri.edges()[0])
                         .find(|edge| tri_intersects_edge(mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1]))
-                        .unwrap().undirected();
+                        .ok_or(RecoveryError::TriNotTraced(tri))?
+                        .undirected();
+
+                    let pos = [
+                        mesh.position(tri.0[0]),
+                        mesh.position(tri.0[1]),
+                        mesh.position(tri.0[2]),
+                    ];
+                    let value = mesh.default_vertex().with_position(Point3::from(
+                        (pos[0].coords + pos[1].coords + pos[2].coords) / 3.0,
+                    ));
+                    let vertex = mesh.add_vertex(value.clone());
+
+                    tri_mesh.add_vertex_with_id(vertex, value);
+                    tri_mesh.flip13(tri, vertex);
                     let start = iter::bfs(
                         it::once(edge),
                         |edge| {
@@ -466,18 +1721,26 @@ where
                         |edge| tri_intersects_edge(mesh, tri.0[0], tri.0[1], tri.0[2], edge.0[0], edge.0[1])
                     ).flat_map(|edge| mesh.edge_tets(edge)).collect::<FnvHashSet<_>>();
 
-                    let tet = iter::bfs(
-                        start,
-                        |tet| mesh.adjacent_tets(*tet),
-                        |_| true
-                    ).find(|tet| tet_contains_vertex(mesh, tet.0[0], tet.0[1], tet.0[2], tet.0[3], vertex)).unwrap();
+                    let tet = iter::bfs(start, |tet| mesh.adjacent_tets(*tet), |_| true)
+                        .find(|tet| {
+                            tet_contains_vertex(
+                                mesh, tet.0[0], tet.0[1], tet.0[2], tet.0[3], vertex,
+                            )
+                        })
+                        .ok_or(RecoveryError::SteinerPointNotInserted(vertex))?;
 
                     mesh.flip14(tet.0[0], tet.0[1], tet.0[2], tet.0[3], vertex);
 
                     // Great. More edges to recover.
-                    let extra_steiner = recover_edges(mesh, tri_mesh, 
-                        Some(&[EdgeId([tri.0[0], vertex]), EdgeId([tri.0[1], vertex]), EdgeId([tri.0[2], vertex])]));
-                    num_steiner_vertices += 1 + extra_steiner.len();
+                    let extra_steiner = recover_edges(
+                        mesh,
+                        tri_mesh,
+                        Some(&[
+                            EdgeId([tri.0[0], vertex]),
+                            EdgeId([tri.0[1], vertex]),
+                            EdgeId([tri.0[2], vertex]),
+                        ]),
+                    )?;
 
                     let mut extra_tris = vec![
                         TriId::from_valid([tri.0[0], tri.0[1], vertex]),
@@ -498,27 +1761,20 @@ where
 
                     steiner_map.insert(tri, vertex);
                     edge_steiner_map.extend(extra_steiner);
-                } else {
-                    println!("Sneaky recovery of {:?}", tri);
                 }
             }
         }
     }
 
-    println!("Added {} Steiner points to recover tris", num_steiner_vertices);
-
-    steiner_map
+    Ok(steiner_map)
 }
 
 /// Remove all tetrahedrons that are outside the boundary marked by `tri_mesh`.
+/// The triangles of `tri_mesh` must point outward.
 pub(crate) fn remove_external_tets<M, FM>(mesh: &mut M, tri_mesh: &FM)
 where
     M: HasTets<MwbT = B1> + HasPosition3D,
-    M::V: Position<Dim = U3> + Clone,
-    M::E: Clone,
-    M::F: Clone,
-    M::T: Clone,
-    M::WithoutTets: HasTris<HigherF = typenum::B0>,
+    M::V: Position<Dim = U3>,
     FM: HasVertices<V = M::V> + HasTris + HasPosition3D,
     FM::V: Position<Dim = U3>,
 {
@@ -529,247 +1785,1753 @@ where
     // Full BFS over the tetrahedrons to determine which ones are external
     let external_tets = iter::bfs(
         external_tets,
-        |tet| tet.tris().to_vec().into_iter()
-            .filter(|tri| !tri_mesh.contains_tri(tri.twin()) && mesh.contains_tri(tri.twin()))
-            .map(|tri| TetId::from_valid([tri.0[0], tri.0[2], tri.0[1], mesh.tri_vertex_opp(tri.twin()).unwrap()])),
-        |_| true
-    ).collect::<Vec<_>>();
+        |tet| {
+            tet.tris()
+                .to_vec()
+                .into_iter()
+                .filter(|tri| !tri_mesh.contains_tri(tri.twin()))
+                .flat_map(|tri| mesh.tri_tet(tri.twin()))
+        },
+        |_| true,
+    )
+    .collect::<Vec<_>>();
 
     mesh.remove_tets(external_tets);
 }
 
-#[cfg(test)]
-mod tests {
-    use std::convert::TryInto;
-    use std::fmt::Debug;
-    use std::hash::Hash;
+/// Remove all tetrahedrons that aren't in a region containing one of the `seeds`,
+/// where regions are separated by the triangles of `tri_mesh` in either orientation.
+/// If there are no seeds, removes the tetrahedrons in regions touching the convex hull instead.
+pub(crate) fn remove_unseeded_tets<M, FM>(mesh: &mut M, tri_mesh: &FM, seeds: &[TetId])
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    FM: HasTris,
+{
+    let is_constraint =
+        |tri: &TriId| tri_mesh.contains_tri(*tri) || tri_mesh.contains_tri(tri.twin());
+    let region = |start: Vec<TetId>| {
+        iter::bfs(
+            start,
+            |tet| {
+                let mesh = &*mesh;
+                tet.tris()
+                    .to_vec()
+                    .into_iter()
+                    .filter(|tri| !is_constraint(tri))
+                    .flat_map(move |tri| mesh.tri_tet(tri.twin()))
+            },
+            |_| true,
+        )
+        .collect::<FnvHashSet<_>>()
+    };
+
+    let to_remove = if seeds.is_empty() {
+        let hull_tets = mesh
+            .tet_ids()
+            .filter(|tet| {
+                tet.tris()
+                    .iter()
+                    .any(|tri| !is_constraint(tri) && mesh.tri_tet(tri.twin()).is_none())
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        region(hull_tets).into_iter().collect::<Vec<_>>()
+    } else {
+        let keep = region(seeds.to_vec());
+        mesh.tet_ids()
+            .filter(|tet| !keep.contains(tet))
+            .copied()
+            .collect::<Vec<_>>()
+    };
 
-    use fnv::FnvHashSet;
-    use nalgebra::Point3;
+    mesh.remove_tets(to_remove);
+}
 
-    use super::*;
-    use crate::vertex::HasVertices;
-    use crate::{mesh3::MwbComboMesh3, ComboMesh0};
+/// Adds the corners of a box around the vertices of a mesh
+/// and turns it into their Delaunay tetrahedralization.
+/// That keeps constraints away from the convex hull,
+/// where coplanar hull tris would leave no tets to flip when recovering them.
+/// Returns the corners, which should get removed after the tets outside the constraints.
+/// Fails without changing the mesh if the vertices don't span a tetrahedralization.
+pub(crate) fn delaunay_tets_in_box<M>(mesh: &mut M) -> Result<Vec<VertexId>, DelaunayTetsError>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    check_finite(mesh)?;
+    find_first_tet(mesh, &mesh.vertex_ids().copied().collect::<Vec<_>>())?;
+
+    let [min, max] = mesh.bounding_box().unwrap();
+    let margin = Vector3::repeat((max - min).max());
+    let [min, max] = [min - margin, max + margin];
+    let corners = (0..8)
+        .map(|i| {
+            let pick = |bit: usize, min: f64, max: f64| if i & bit == 0 { min } else { max };
+            mesh.add_with_position(Point3::new(
+                pick(1, min.x, max.x),
+                pick(2, min.y, max.y),
+                pick(4, min.z, max.z),
+            ))
+        })
+        .collect::<Vec<_>>();
 
-    #[track_caller]
-    fn assert_tets_m<
-        V,
-        E,
-        F,
-        T: Clone + Debug + Eq + Hash,
-        TI: TryInto<TetId>,
-        I: IntoIterator<Item = (TI, T)>,
-    >(
-        mesh: &MwbComboMesh3<V, E, F, T>,
-        tets: I,
-    ) {
-        let result = mesh
-            .tets()
-            .map(|(id, f)| (*id, f.clone()))
-            .collect::<FnvHashSet<_>>();
-        let expect = tets
+    if let Err(err) = delaunay_tets_in_place(mesh, SpatialSort::default(), None) {
+        mesh.clear_tets();
+        mesh.remove_vertices(corners);
+        return Err(err);
+    }
+    Ok(corners)
+}
+
+/// Tetrahedralizes the inside of a closed triangle surface.
+/// Recovers the edges and triangles of the surface, adding Steiner points
+/// as needed, then removes the tetrahedrons outside the surface.
+/// Returns the tet mesh, a map from split edges to Steiner points,
+/// and a map from split triangles to Steiner points.
+/// If the vertices don't span a tetrahedralization, the mesh gets no tets.
+pub(crate) fn constrained_delaunay_tets<M, FM>(
+    mut mesh: M,
+    mut tri_mesh: FM,
+) -> Result<WithSteinerPoints<M>, ConstrainedTetsError>
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3> + Clone,
+    FM: HasVertices<V = M::V> + HasTris + HasPosition3D,
+    FM::V: Position<Dim = U3>,
+    FM::E: Clone,
+    FM::F: Clone,
+{
+    let corners = match delaunay_tets_in_box(&mut mesh) {
+        Ok(corners) => corners,
+        Err(err) if err.is_degenerate() => {
+            return Ok((mesh, FnvHashMap::default(), FnvHashMap::default()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut edge_steiner_map = recover_edges(&mut mesh, &mut tri_mesh, None)?;
+    let tri_steiner_map = recover_tris(&mut mesh, &mut tri_mesh, &mut edge_steiner_map)?;
+    remove_external_tets(&mut mesh, &tri_mesh);
+    mesh.remove_vertices(corners);
+
+    Ok((mesh, edge_steiner_map, tri_steiner_map))
+}
+
+/// The volume of a tet, ignoring orientation
+fn tet_volume<M>(mesh: &M, tet: TetId) -> f64
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let p0 = mesh.position(tet.0[0]);
+    (mesh.position(tet.0[1]) - p0)
+        .cross(&(mesh.position(tet.0[2]) - p0))
+        .dot(&(mesh.position(tet.0[3]) - p0))
+        .abs()
+        / 6.0
+}
+
+/// The circumcenter of a tet.
+/// If the tet is flat, its vertices are cocircular (it's Delaunay after all),
+/// so this returns the center of that circle instead.
+pub(crate) fn tet_circumcenter<M>(mesh: &M, tet: TetId) -> Point3<f64>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    if tet_volume(mesh, tet) == 0.0 {
+        return tri_circumcenter(mesh, tet.tris()[0]);
+    }
+
+    let p0 = mesh.position(tet.0[0]);
+    let a = mesh.position(tet.0[1]) - p0;
+    let b = mesh.position(tet.0[2]) - p0;
+    let c = mesh.position(tet.0[3]) - p0;
+    p0 + (b.cross(&c) * a.norm_squared()
+        + c.cross(&a) * b.norm_squared()
+        + a.cross(&b) * c.norm_squared())
+        / (2.0 * a.dot(&b.cross(&c)))
+}
+
+/// The circumcenter of a tri
+pub(crate) fn tri_circumcenter<M>(mesh: &M, tri: TriId) -> Point3<f64>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let p0 = mesh.position(tri.0[0]);
+    let a = mesh.position(tri.0[1]) - p0;
+    let b = mesh.position(tri.0[2]) - p0;
+    let n = a.cross(&b);
+    p0 + (b * a.norm_squared() - a * b.norm_squared()).cross(&n) / (2.0 * n.norm_squared())
+}
+
+/// Whether a point is strictly inside the diametral sphere of an edge
+fn encroaches_edge<M>(mesh: &M, edge: EdgeId, point: Point3<f64>) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let (p0, p1) = (mesh.position(edge.0[0]), mesh.position(edge.0[1]));
+    (point - nalgebra::center(&p0, &p1)).norm_squared()
+        < (p1 - p0).norm_squared() / 4.0 * (1.0 - COPLANAR_TOLERANCE)
+}
+
+/// Whether a point is strictly inside the equatorial sphere of a tri
+fn encroaches_tri<M>(mesh: &M, tri: TriId, point: Point3<f64>) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let center = tri_circumcenter(mesh, tri);
+    (point - center).norm_squared()
+        < (mesh.position(tri.0[0]) - center).norm_squared() * (1.0 - COPLANAR_TOLERANCE)
+}
+
+/// Whether a tri is on the boundary of the tetrahedralization
+fn is_boundary_tri<M>(mesh: &M, tri: TriId) -> bool
+where
+    M: HasTets<MwbT = B1>,
+{
+    mesh.contains_tri(tri) && !mesh.contains_tri(tri.twin())
+}
+
+/// Whether a tri is on the boundary of the tetrahedralization or is one of the constraints,
+/// which are stored undirected
+fn is_constraint_tri<M>(mesh: &M, constraints: &FnvHashSet<TriId>, tri: TriId) -> bool
+where
+    M: HasTets<MwbT = B1>,
+{
+    is_boundary_tri(mesh, tri)
+        || (mesh.contains_tri(tri) && constraints.contains(&tri.undirected()))
+}
+
+/// Whether a tet has a radius-edge ratio or volume over the bounds.
+/// Flat tets have an infinite radius-edge ratio.
+fn is_bad_tet<M>(mesh: &M, tet: TetId, max_radius_edge_ratio: f64, max_volume: f64) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let volume = tet_volume(mesh, tet);
+    if volume == 0.0 || volume > max_volume {
+        return true;
+    }
+
+    let radius_squared = (tet_circumcenter(mesh, tet) - mesh.position(tet.0[0])).norm_squared();
+    let min_edge_squared = tet
+        .edges()
+        .iter()
+        .map(|edge| FloatOrd(mesh.distance_squared(edge.0[0], edge.0[1])))
+        .min()
+        .unwrap()
+        .0;
+
+    radius_squared > max_radius_edge_ratio * max_radius_edge_ratio * min_edge_squared
+}
+
+/// Walks from a tet toward a vertex and returns the tet containing it,
+/// or the constraint tri the walk would have to cross.
+/// Returns None if the walk goes nowhere.
+fn walk_to_vertex<M>(
+    mesh: &M,
+    constraints: &FnvHashSet<TriId>,
+    start: TetId,
+    vertex: VertexId,
+) -> Option<Result<TetId, TriId>>
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let mut tet = start;
+    for _ in 0..=mesh.num_tets() {
+        let exit = tet
+            .tris_and_opp()
+            .iter()
+            .find(|(tri, opp)| {
+                sim::orient_3d(mesh, index_fn, tri.0[0], tri.0[1], tri.0[2], vertex)
+                    != sim::orient_3d(mesh, index_fn, tri.0[0], tri.0[1], tri.0[2], *opp)
+            })
+            .map(|(tri, _)| *tri);
+
+        match exit {
+            None => return Some(Ok(tet)),
+            Some(tri) if constraints.contains(&tri.undirected()) => return Some(Err(tri)),
+            Some(tri) => match mesh.tri_tet(tri.twin()) {
+                Some(next) => tet = next,
+                None => return Some(Err(tri)),
+            },
+        }
+    }
+    None
+}
+
+/// Whether a point lies on the plane of a tri, give or take floating-point error
+fn is_coplanar<M>(mesh: &M, tri: TriId, point: Point3<f64>) -> bool
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let p0 = mesh.position(tri.0[0]);
+    let normal = (mesh.position(tri.0[1]) - p0).cross(&(mesh.position(tri.0[2]) - p0));
+    let size = tri
+        .edges()
+        .iter()
+        .map(|edge| FloatOrd(mesh.distance_squared(edge.0[0], edge.0[1])))
+        .max()
+        .unwrap()
+        .0
+        .sqrt();
+    normal.dot(&(point - p0)).abs() <= COPLANAR_TOLERANCE * size * normal.norm()
+}
+
+/// What happened when trying to insert a vertex into the tetrahedralization
+enum Insertion {
+    Inserted,
+    /// The vertex encroaches on a constraint tri of its cavity.
+    Encroached(TriId),
+    /// The vertex can't see all of its cavity.
+    Failed,
+}
+
+/// Inserts an existing vertex with no tets by retetrahedralizing the cavity of tets
+/// whose circumspheres contain it, grown from `start` without crossing constraint tris.
+/// Constraint tris the vertex lies on get split, and their pieces replace them in `constraints`.
+/// If `protect_constraints` is set, the insertion is rejected if the vertex encroaches
+/// on a constraint tri of the cavity.
+/// The vertex gets removed if the insertion fails.
+fn insert_vertex_in_cavity<M>(
+    mesh: &mut M,
+    constraints: &mut FnvHashSet<TriId>,
+    vertex: VertexId,
+    start: Vec<TetId>,
+    protect_constraints: bool,
+) -> Insertion
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let position = mesh.position(vertex);
+    let mut cavity = iter::bfs(
+        start.clone(),
+        |tet| {
+            tet.tris()
+                .iter()
+                .filter(|tri| !constraints.contains(&tri.undirected()))
+                .flat_map(|tri| mesh.tri_tet(tri.twin()))
+                .collect::<Vec<_>>()
+        },
+        |tet| start.contains(tet) || in_sphere(mesh, *tet, vertex),
+    )
+    .collect::<FnvHashSet<_>>();
+
+    // Tets past cavity tris the vertex lies on would make flat tets, so they join the cavity.
+    let boundary = loop {
+        let tris = cavity
+            .iter()
+            .flat_map(|tet| tet.tris_and_opp().to_vec())
+            .collect::<FnvHashMap<_, _>>();
+        let boundary = tris
+            .iter()
+            .filter(|(tri, _)| !tris.contains_key(&tri.twin()))
+            .map(|(tri, opp)| (*tri, *opp))
+            .collect::<Vec<_>>();
+
+        // Protected constraint tris get checked for encroachment instead.
+        let flat = boundary
+            .iter()
+            .filter(|(tri, _)| !is_boundary_tri(mesh, *tri) && is_coplanar(mesh, *tri, position))
+            .filter(|(tri, _)| !(protect_constraints && constraints.contains(&tri.undirected())))
+            .flat_map(|(tri, _)| mesh.tri_tet(tri.twin()))
+            .collect::<Vec<_>>();
+
+        if flat.is_empty() {
+            break boundary;
+        }
+        cavity.extend(flat);
+    };
+
+    let mut new_tris = vec![];
+    for (tri, opp) in boundary {
+        if is_constraint_tri(mesh, constraints, tri) {
+            if protect_constraints && encroaches_tri(mesh, tri, position) {
+                mesh.remove_vertex(vertex);
+                return Insertion::Encroached(tri);
+            }
+
+            // Lying on a boundary tri splits it.
+            // Lying on an interior one would leave a flat tet on its other side.
+            if is_coplanar(mesh, tri, position) {
+                if !is_boundary_tri(mesh, tri) {
+                    mesh.remove_vertex(vertex);
+                    return Insertion::Failed;
+                }
+                continue;
+            }
+        }
+
+        if sim::orient_3d(mesh, index_fn, tri.0[0], tri.0[1], tri.0[2], vertex)
+            != sim::orient_3d(mesh, index_fn, tri.0[0], tri.0[1], tri.0[2], opp)
+        {
+            mesh.remove_vertex(vertex);
+            return Insertion::Failed;
+        }
+        new_tris.push(tri);
+    }
+
+    // Constraint tris inside the cavity get split by the vertex, so it has to lie on them.
+    let cavity_tris = cavity
+        .iter()
+        .flat_map(|tet| tet.tris().to_vec())
+        .collect::<FnvHashSet<_>>();
+    let split = cavity_tris
+        .iter()
+        .filter(|tri| cavity_tris.contains(&tri.twin()))
+        .map(|tri| tri.undirected())
+        .filter(|tri| constraints.contains(tri))
+        .collect::<FnvHashSet<_>>();
+    if split.iter().any(|tri| !is_coplanar(mesh, *tri, position)) {
+        mesh.remove_vertex(vertex);
+        return Insertion::Failed;
+    }
+
+    mesh.remove_tets(cavity);
+    mesh.extend_tets(
+        new_tris
             .into_iter()
-            .map(|(vertices, f)| (vertices.try_into().ok().unwrap(), f))
-            .collect::<FnvHashSet<_>>();
+            .map(|tri| {
+                (
+                    TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], vertex]),
+                    mesh.default_tet(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    // The pieces connect the vertex to the edges of the split tris.
+    let pieces = mesh
+        .vertex_tets(vertex)
+        .flat_map(|tet| tet.tris().to_vec())
+        .filter(|tri| tri.contains_vertex(vertex))
+        .map(|tri| tri.undirected())
+        .filter(|tri| {
+            let edge = tri.opp_edge(vertex).undirected();
+            split
+                .iter()
+                .any(|c| c.edges().iter().any(|e| e.undirected() == edge))
+        })
+        .collect::<Vec<_>>();
+    for tri in &split {
+        constraints.remove(tri);
+    }
+    constraints.extend(pieces);
+    Insertion::Inserted
+}
 
-        assert_eq!(result, expect);
-        assert_eq!(mesh.num_tets(), expect.len());
+/// Splits a constraint edge at its midpoint. Returns the new vertex if this succeeded.
+fn split_constraint_edge<M>(
+    mesh: &mut M,
+    constraints: &mut FnvHashSet<TriId>,
+    edge: EdgeId,
+) -> Option<VertexId>
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let start = mesh.edge_tets(edge).collect::<Vec<_>>();
+    let vertex = mesh.add_with_position(nalgebra::center(
+        &mesh.position(edge.0[0]),
+        &mesh.position(edge.0[1]),
+    ));
+
+    match insert_vertex_in_cavity(mesh, constraints, vertex, start, false) {
+        Insertion::Inserted => Some(vertex),
+        _ => None,
+    }
+}
+
+/// Splits a constraint tri at its circumcenter,
+/// or splits its longest edge if the circumcenter is outside it or encroaches on an edge.
+/// Returns the new vertex if this succeeded.
+fn split_constraint_tri<M>(
+    mesh: &mut M,
+    constraints: &mut FnvHashSet<TriId>,
+    tri: TriId,
+) -> Option<VertexId>
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let center = tri_circumcenter(mesh, tri);
+    let p = [
+        mesh.position(tri.0[0]),
+        mesh.position(tri.0[1]),
+        mesh.position(tri.0[2]),
+    ];
+    let normal = (p[1] - p[0]).cross(&(p[2] - p[0]));
+    let inside = (0..3).all(|i| (p[(i + 1) % 3] - p[i]).cross(&(center - p[i])).dot(&normal) > 0.0);
+
+    if !inside
+        || tri
+            .edges()
+            .iter()
+            .any(|edge| encroaches_edge(mesh, *edge, center))
+    {
+        let longest = *tri
+            .edges()
+            .iter()
+            .max_by_key(|edge| FloatOrd(mesh.distance_squared(edge.0[0], edge.0[1])))
+            .unwrap();
+        return split_constraint_edge(mesh, constraints, longest);
+    }
+
+    let start = mesh
+        .tri_tet(tri)
+        .into_iter()
+        .chain(mesh.tri_tet(tri.twin()))
+        .collect::<Vec<_>>();
+    let vertex = mesh.add_with_position(center);
+    match insert_vertex_in_cavity(mesh, constraints, vertex, start, false) {
+        Insertion::Inserted => Some(vertex),
+        _ => None,
+    }
+}
+
+/// Delaunay refinement (https://people.eecs.berkeley.edu/~jrs/meshpapers/delnotes.pdf, chapter 6).
+/// Splits encroached boundary edges and boundary tris, then inserts circumcenters of tets
+/// with a radius-edge ratio or volume over the bounds.
+/// The boundary tris of the tetrahedralization, the given tris and their edges are the constraints.
+/// Returns the number of vertices inserted.
+pub(crate) fn refine_tets<M, I>(
+    mesh: &mut M,
+    constraints: I,
+    max_radius_edge_ratio: f64,
+    max_volume: f64,
+) -> usize
+where
+    M: HasTets<MwbT = B1> + HasPosition3D,
+    M::V: Position<Dim = U3>,
+    I: IntoIterator<Item = TriId>,
+{
+    let mut constraints = constraints
+        .into_iter()
+        .filter(|tri| mesh.contains_tri(*tri) && mesh.contains_tri(tri.twin()))
+        .map(|tri| tri.undirected())
+        .collect::<FnvHashSet<_>>();
+
+    // Flat tets on the boundary add nothing and have useless boundary tris
+    while let Some(tet) = mesh.tet_ids().copied().find(|tet| {
+        tet_volume(mesh, *tet) == 0.0 && tet.tris().iter().any(|tri| is_boundary_tri(mesh, *tri))
+    }) {
+        mesh.remove_tet(tet);
+    }
+
+    let mut tets = mesh.tet_ids().copied().collect::<VecDeque<_>>();
+    let mut tris = mesh
+        .tri_ids()
+        .copied()
+        .filter(|tri| is_constraint_tri(mesh, &constraints, *tri))
+        .collect::<VecDeque<_>>();
+    let mut edges = tris
+        .iter()
+        .flat_map(|tri| tri.edges().to_vec())
+        .collect::<VecDeque<_>>();
+    let mut num_inserted = 0;
+
+    while num_inserted < MAX_REFINE_VERTICES {
+        let vertex = if let Some(edge) = edges.pop_front() {
+            // Encroached constraint edges
+            let is_constraint = mesh.edge_tets(edge).any(|tet| {
+                tet.tris().iter().any(|tri| {
+                    tri.contains_vertex(edge.0[0])
+                        && tri.contains_vertex(edge.0[1])
+                        && is_constraint_tri(mesh, &constraints, *tri)
+                })
+            });
+            let encroached = is_constraint
+                && mesh
+                    .edge_tets(edge)
+                    .flat_map(|tet| tet.vertices().to_vec())
+                    .any(|v| {
+                        v != edge.0[0]
+                            && v != edge.0[1]
+                            && encroaches_edge(mesh, edge, mesh.position(v))
+                    });
+
+            if !encroached {
+                continue;
+            }
+            split_constraint_edge(mesh, &mut constraints, edge)
+        } else if let Some(tri) = tris.pop_front() {
+            // Encroached constraint tris, from the side of the tet on this orientation
+            let encroached = is_constraint_tri(mesh, &constraints, tri)
+                && mesh
+                    .tri_vertex_opp(tri)
+                    .map_or(false, |opp| encroaches_tri(mesh, tri, mesh.position(opp)));
+
+            if !encroached {
+                continue;
+            }
+            split_constraint_tri(mesh, &mut constraints, tri)
+        } else if let Some(tet) = tets.pop_front() {
+            // Bad tets
+            if !mesh.contains_tet(tet) || !is_bad_tet(mesh, tet, max_radius_edge_ratio, max_volume)
+            {
+                continue;
+            }
+
+            let vertex = mesh.add_with_position(tet_circumcenter(mesh, tet));
+            // A flat tet's circumcenter is on its plane, where orientation tests
+            // don't say much, but it's known to be in the cavity anyway.
+            let start = if tet_volume(mesh, tet) == 0.0 {
+                Some(Ok(tet))
+            } else {
+                walk_to_vertex(mesh, &constraints, tet, vertex)
+            };
+            match start {
+                Some(Ok(start)) => {
+                    match insert_vertex_in_cavity(mesh, &mut constraints, vertex, vec![start], true)
+                    {
+                        Insertion::Inserted => Some(vertex),
+                        Insertion::Encroached(tri) => {
+                            split_constraint_tri(mesh, &mut constraints, tri)
+                        }
+                        Insertion::Failed => None,
+                    }
+                }
+                Some(Err(tri)) => {
+                    mesh.remove_vertex(vertex);
+                    split_constraint_tri(mesh, &mut constraints, tri)
+                }
+                None => {
+                    mesh.remove_vertex(vertex);
+                    None
+                }
+            }
+        } else {
+            break;
+        };
+
+        if let Some(vertex) = vertex {
+            num_inserted += 1;
+
+            for tet in mesh.vertex_tets(vertex).collect::<Vec<_>>() {
+                tets.push_back(tet);
+                for tri in tet
+                    .tris()
+                    .iter()
+                    .filter(|tri| is_constraint_tri(mesh, &constraints, **tri))
+                {
+                    tris.push_back(*tri);
+                    edges.extend(tri.edges().iter().copied());
+                }
+            }
+        }
+    }
+
+    num_inserted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+    use std::fmt::Debug;
+    use std::hash::Hash;
+
+    use fnv::FnvHashSet;
+    use nalgebra::Point3;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::tet::HasPositionAndTets;
+    use crate::validate::ValidationReport;
+    use crate::vertex::HasVertices;
+    use crate::{mesh3::MwbComboMesh3, ComboMesh0};
+
+    #[track_caller]
+    fn assert_tets_m<
+        V,
+        E,
+        F,
+        T: Clone + Debug + Eq + Hash,
+        TI: TryInto<TetId>,
+        I: IntoIterator<Item = (TI, T)>,
+    >(
+        mesh: &MwbComboMesh3<V, E, F, T>,
+        tets: I,
+    ) {
+        let result = mesh
+            .tets()
+            .map(|(id, f)| (*id, f.clone()))
+            .collect::<FnvHashSet<_>>();
+        let expect = tets
+            .into_iter()
+            .map(|(vertices, f)| (vertices.try_into().ok().unwrap(), f))
+            .collect::<FnvHashSet<_>>();
+
+        assert_eq!(result, expect);
+        assert_eq!(mesh.num_tets(), expect.len());
+    }
+
+    #[test]
+    fn test_in_sphere_ghost() {
+        let mut mesh = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            || Point3::origin(),
+            || (),
+            || (),
+            || (),
+        );
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.5, 0.3, 0.6),
+        ]);
+        let tet = TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]);
+        mesh.add_tet(tet, ());
+
+        assert!(!in_sphere_with_ghosts(&mesh, tet, ids[4], ids[0], None));
+        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], ids[1], None));
+        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], ids[2], None));
+        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], ids[3], None));
+    }
+
+    #[test]
+    fn test_in_sphere_no_ghost() {
+        let mut mesh = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            || Point3::origin(),
+            || (),
+            || (),
+            || (),
+        );
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.5, 0.3, 0.6),
+        ]);
+        let tet = TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]);
+        mesh.add_tet(tet, ());
+
+        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], VertexId(5), None));
+    }
+
+    #[test]
+    fn test_tets_to_delete() {
+        let mut mesh = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            || Point3::origin(),
+            || (),
+            || (),
+            || (),
+        );
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point1::new(f64::INFINITY).xxx(),
+            Point3::new(0.1, 0.1, 0.1),
+            Point3::new(0.1, -0.1, 0.1),
+            Point3::new(-1.0, -1.0, 0.1),
+        ]);
+        mesh.extend_tets(vec![
+            ([ids[0], ids[1], ids[3], ids[2]], ()),
+            ([ids[4], ids[1], ids[2], ids[3]], ()),
+            ([ids[5], ids[0], ids[1], ids[3]], ()),
+            ([ids[5], ids[3], ids[2], ids[0]], ()),
+            ([ids[5], ids[1], ids[0], ids[2]], ()),
+            ([ids[5], ids[4], ids[1], ids[2]], ()),
+            ([ids[5], ids[2], ids[3], ids[4]], ()),
+            ([ids[5], ids[1], ids[4], ids[3]], ()),
+        ]);
+
+        // In convex hull
+        let result = tets_to_delete(&mesh, ids[6], ids[5], None, None)
+            .unwrap()
+            .collect::<FnvHashSet<_>>();
+        assert_eq!(
+            result,
+            vec![
+                TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]),
+                TetId::from_valid([ids[4], ids[1], ids[2], ids[3]]),
+            ]
+            .into_iter()
+            .collect::<FnvHashSet<_>>()
+        );
+
+        // Remove both solid tetrahedrons and ghost tetrahedrons
+        let result = tets_to_delete(&mesh, ids[7], ids[5], None, None)
+            .unwrap()
+            .collect::<FnvHashSet<_>>();
+        assert_eq!(
+            result,
+            vec![
+                TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]),
+                TetId::from_valid([ids[4], ids[1], ids[2], ids[3]]),
+                TetId::from_valid([ids[5], ids[0], ids[1], ids[3]]),
+            ]
+            .into_iter()
+            .collect::<FnvHashSet<_>>()
+        );
+
+        // Remove only ghost tetrahedrons
+        let result = tets_to_delete(&mesh, ids[8], ids[5], None, None)
+            .unwrap()
+            .collect::<FnvHashSet<_>>();
+        assert_eq!(
+            result,
+            vec![
+                TetId::from_valid([ids[5], ids[0], ids[1], ids[3]]),
+                TetId::from_valid([ids[5], ids[3], ids[2], ids[0]]),
+            ]
+            .into_iter()
+            .collect::<FnvHashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_delaunay_tets_empty() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
+        mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ]);
+
+        let result = mesh.clone().delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
+            result.vertex_ids().collect::<FnvHashSet<_>>(),
+        );
+        assert_tets_m(&result, vec![] as Vec<(TetId, ())>);
+    }
+
+    #[test]
+    fn test_delaunay_tets_single() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ]);
+
+        let result = mesh.clone().delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
+            result.vertex_ids().collect::<FnvHashSet<_>>(),
+        );
+        assert_tets_m(
+            &result,
+            vec![(TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]), ())],
+        );
+    }
+
+    #[test]
+    fn test_delaunay_tets_multiple() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.5, 1.5, 1.0),
+            Point3::new(0.5, 0.5, 0.5),
+        ]);
+
+        let result = mesh.clone().delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
+            result.vertex_ids().collect::<FnvHashSet<_>>(),
+        );
+        assert_tets_m(
+            &result,
+            vec![
+                (TetId::from_valid([ids[0], ids[3], ids[2], ids[5]]), ()),
+                (TetId::from_valid([ids[0], ids[1], ids[3], ids[5]]), ()),
+                (TetId::from_valid([ids[1], ids[0], ids[2], ids[5]]), ()),
+                (TetId::from_valid([ids[1], ids[4], ids[3], ids[5]]), ()),
+                (TetId::from_valid([ids[3], ids[4], ids[2], ids[5]]), ()),
+                (TetId::from_valid([ids[2], ids[4], ids[1], ids[5]]), ()),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_delaunay_tets_multiple_valid() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.5, 1.5, 1.0),
+            Point3::new(0.5, 0.5, 0.5),
+        ]);
+
+        let result = mesh.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            result.validate_with_positions(),
+            ValidationReport::default()
+        );
+    }
+
+    #[test]
+    fn test_insert_vertex_delaunay_inside() {
+        use crate::vertex::HasPosition3D;
+
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.5, 1.5, 1.0),
+        ]);
+
+        let mut result = mesh.delaunay_tets(|| (), || (), || ());
+        let id = result.insert_vertex_delaunay(Point3::new(0.5, 0.5, 0.5));
+        assert_tets_m(
+            &result,
+            vec![
+                (TetId::from_valid([ids[0], ids[3], ids[2], id]), ()),
+                (TetId::from_valid([ids[0], ids[1], ids[3], id]), ()),
+                (TetId::from_valid([ids[1], ids[0], ids[2], id]), ()),
+                (TetId::from_valid([ids[1], ids[4], ids[3], id]), ()),
+                (TetId::from_valid([ids[3], ids[4], ids[2], id]), ()),
+                (TetId::from_valid([ids[2], ids[4], ids[1], id]), ()),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_insert_vertex_delaunay_outside() {
+        use crate::mesh3::ComboMesh3;
+        use crate::vertex::HasPosition3D;
+
+        let mut mesh = ComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            Point3::origin,
+            || (),
+            || (),
+            || (),
+        );
+        let ids = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.5, 1.5, 1.0),
+            Point3::new(0.5, 0.5, 0.5),
+        ]
+        .into_iter()
+        .map(|position| mesh.insert_vertex_delaunay(position))
+        .collect::<Vec<_>>();
+
+        let expect = vec![
+            TetId::from_valid([ids[0], ids[3], ids[2], ids[5]]),
+            TetId::from_valid([ids[0], ids[1], ids[3], ids[5]]),
+            TetId::from_valid([ids[1], ids[0], ids[2], ids[5]]),
+            TetId::from_valid([ids[1], ids[4], ids[3], ids[5]]),
+            TetId::from_valid([ids[3], ids[4], ids[2], ids[5]]),
+            TetId::from_valid([ids[2], ids[4], ids[1], ids[5]]),
+        ];
+        assert_eq!(
+            mesh.tet_ids().copied().collect::<FnvHashSet<_>>(),
+            expect.into_iter().collect::<FnvHashSet<_>>(),
+        );
+        assert_eq!(mesh.num_vertices(), 6);
+    }
+
+    #[test]
+    fn test_insert_vertex_delaunay_matches_rebuild() {
+        use crate::mesh3::ComboMesh3;
+        use crate::vertex::HasPosition3D;
+
+        let mut mesh = ComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            Point3::origin,
+            || (),
+            || (),
+            || (),
+        );
+        // Deterministic scattered points
+        let mut rng = Pcg64::seed_from_u64(12345);
+        for _ in 0..50 {
+            let position = Point3::new(rng.gen(), rng.gen(), rng.gen());
+            mesh.insert_vertex_delaunay(position);
+        }
+
+        assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+
+        let mut rebuilt = mesh.clone();
+        rebuilt.clear_tets();
+        let rebuilt = rebuilt.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            mesh.tet_ids().copied().collect::<FnvHashSet<_>>(),
+            rebuilt.tet_ids().copied().collect::<FnvHashSet<_>>(),
+        );
+    }
+
+    #[test]
+    fn test_remove_vertex_delaunay_inside() {
+        use crate::vertex::HasPosition3D;
+
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.5, 1.5, 1.0),
+            Point3::new(0.5, 0.5, 0.5),
+        ]);
+
+        let mut result = mesh.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            result.remove_vertex_delaunay(ids[5]),
+            Some(Point3::new(0.5, 0.5, 0.5))
+        );
+        assert_tets_m(
+            &result,
+            vec![
+                (TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]), ()),
+                (TetId::from_valid([ids[1], ids[2], ids[4], ids[3]]), ()),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_remove_vertex_delaunay_coplanar_link() {
+        use crate::vertex::HasPosition3D;
+
+        // Square bipyramid
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.5, 0.5, 1.0),
+            Point3::new(0.5, 0.5, -1.0),
+        ]);
+
+        let mut result = mesh.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            result.remove_vertex_delaunay(ids[4]),
+            Some(Point3::new(0.5, 0.5, 1.0))
+        );
+        assert_eq!(result.num_tets(), 2);
+        assert_eq!(
+            result.validate_with_positions(),
+            ValidationReport::default()
+        );
+
+        // Only the square is left, which is flat
+        assert_eq!(
+            result.remove_vertex_delaunay(ids[5]),
+            Some(Point3::new(0.5, 0.5, -1.0))
+        );
+        assert_eq!(result.num_tets(), 0);
+        assert_eq!(
+            result.validate_with_positions(),
+            ValidationReport::default()
+        );
+    }
+
+    #[test]
+    fn test_remove_vertex_delaunay_grid() {
+        use crate::mesh3::ComboMesh3;
+        use crate::vertex::HasPosition3D;
+
+        let mut mesh = ComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            Point3::origin,
+            || (),
+            || (),
+            || (),
+        );
+        let ids = (0..27)
+            .map(|i| {
+                mesh.insert_vertex_delaunay(Point3::new(
+                    (i % 3) as f64,
+                    (i / 3 % 3) as f64,
+                    (i / 9) as f64,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        for id in ids.into_iter().step_by(2) {
+            mesh.remove_vertex_delaunay(id);
+            assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+        }
+
+        let mut rebuilt = mesh.clone();
+        rebuilt.clear_tets();
+        let rebuilt = rebuilt.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            mesh.tet_ids().copied().collect::<FnvHashSet<_>>(),
+            rebuilt.tet_ids().copied().collect::<FnvHashSet<_>>(),
+        );
+    }
+
+    #[test]
+    fn test_remove_vertex_delaunay_matches_rebuild() {
+        use crate::mesh3::ComboMesh3;
+        use crate::vertex::HasPosition3D;
+
+        let mut mesh = ComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            Point3::origin,
+            || (),
+            || (),
+            || (),
+        );
+        // Deterministic scattered points
+        let mut rng = Pcg64::seed_from_u64(54321);
+        let ids = (0..50)
+            .map(|_| mesh.insert_vertex_delaunay(Point3::new(rng.gen(), rng.gen(), rng.gen())))
+            .collect::<Vec<_>>();
+
+        // Some of these are on the hull
+        for id in ids.into_iter().step_by(3) {
+            mesh.remove_vertex_delaunay(id);
+        }
+
+        assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+
+        let mut rebuilt = mesh.clone();
+        rebuilt.clear_tets();
+        let rebuilt = rebuilt.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            mesh.tet_ids().copied().collect::<FnvHashSet<_>>(),
+            rebuilt.tet_ids().copied().collect::<FnvHashSet<_>>(),
+        );
+    }
+
+    #[test]
+    fn test_locate_point() {
+        use crate::tet::TetLocation;
+
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.5, 1.5, 1.0),
+            Point3::new(0.5, 0.5, 0.5),
+        ]);
+        let result = mesh.delaunay_tets(|| (), || (), || ());
+        let far = TetId::from_valid([ids[1], ids[4], ids[3], ids[5]]);
+        let tri_vertices = |location| match location {
+            Some(TetLocation::Tri(tri)) => {
+                tri.vertices().iter().copied().collect::<FnvHashSet<_>>()
+            }
+            location => panic!("Expected a triangle, got {:?}", location),
+        };
+
+        for hint in [None, Some(far)] {
+            assert_eq!(
+                result.locate_point_in_tets(Point3::new(0.1, 0.3, 0.4), hint),
+                Some(TetLocation::Tet(TetId::from_valid([
+                    ids[0], ids[3], ids[2], ids[5]
+                ])))
+            );
+            assert_eq!(
+                result.locate_point_in_tets(Point3::new(1.0, 0.0, 0.0), hint),
+                Some(TetLocation::Vertex(ids[1]))
+            );
+            assert_eq!(
+                result.locate_point_in_tets(Point3::new(-1.0, -1.0, -1.0), hint),
+                Some(TetLocation::Outside)
+            );
+
+            match result.locate_point_in_tets(Point3::new(0.25, 0.25, 0.25), hint) {
+                Some(TetLocation::Edge(edge)) => {
+                    assert_eq!(edge.undirected(), EdgeId([ids[0], ids[5]]).undirected())
+                }
+                location => panic!("Expected an edge, got {:?}", location),
+            }
+            assert_eq!(
+                tri_vertices(result.locate_point_in_tets(Point3::new(0.25, 0.0, 0.25), hint)),
+                vec![ids[0], ids[1], ids[3]]
+                    .into_iter()
+                    .collect::<FnvHashSet<_>>()
+            );
+            assert_eq!(
+                tri_vertices(result.locate_point_in_tets(Point3::new(0.5, 0.2, 0.2), hint)),
+                vec![ids[0], ids[1], ids[5]]
+                    .into_iter()
+                    .collect::<FnvHashSet<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_hilbert_index_adjacent() {
+        let mut points = (0..64)
+            .map(|i| [i & 3, i >> 2 & 3, i >> 4])
+            .collect::<Vec<_>>();
+        points.sort_by_key(|point| hilbert_index(*point));
+
+        assert_eq!(points[0], [0, 0, 0]);
+        for pair in points.windows(2) {
+            let distance = (0..3)
+                .map(|i| (pair[0][i] as i32 - pair[1][i] as i32).abs())
+                .sum::<i32>();
+            assert_eq!(
+                distance, 1,
+                "{:?} and {:?} are not adjacent",
+                pair[0], pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_delaunay_tets_spatial_sort() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices((0..200).map(|i| {
+            Point3::new(
+                (i as f64 * 0.7).sin() * i as f64,
+                (i as f64 * 1.3).cos() * 50.0,
+                (i as f64 * 2.9).sin() * 30.0,
+            )
+        }));
+
+        let results = [SpatialSort::None, SpatialSort::Hilbert, SpatialSort::Brio]
+            .iter()
+            .map(|spatial_sort| {
+                let result = DelaunayTetsBuilder::new()
+                    .spatial_sort(*spatial_sort)
+                    .build(mesh.clone(), || (), || (), || ());
+                assert_eq!(
+                    result.validate_with_positions(),
+                    ValidationReport::default()
+                );
+                result.tet_ids().copied().collect::<FnvHashSet<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0], results[2]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_delaunay_tets_parallel() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices((0..3000).map(|i| {
+            Point3::new(
+                (i as f64 * 0.7).sin() * 100.0,
+                (i as f64 * 1.3).cos() * 50.0,
+                (i as f64 * 2.9).sin() * 30.0,
+            )
+        }));
+
+        let sequential = mesh.clone().delaunay_tets(|| (), || (), || ());
+        let parallel = mesh.delaunay_tets_parallel(|| (), || (), || ());
+        assert_eq!(
+            parallel.validate_with_positions(),
+            ValidationReport::default()
+        );
+
+        assert_eq!(
+            sequential.tet_ids().copied().collect::<FnvHashSet<_>>(),
+            parallel.tet_ids().copied().collect::<FnvHashSet<_>>()
+        );
+    }
+
+    /// Points with weights, from a deterministic generator
+    fn weighted_points(num: usize, max_weight: f64) -> ComboMesh0<(Point3<f64>, f64)> {
+        let mut mesh = ComboMesh0::<(Point3<f64>, f64)>::with_defaults(|| (Point3::origin(), 0.0));
+        let mut rng = Pcg64::seed_from_u64(4321);
+        mesh.extend_vertices((0..num).map(|_| {
+            (
+                Point3::new(rng.gen(), rng.gen(), rng.gen()),
+                rng.gen::<f64>() * max_weight,
+            )
+        }));
+        mesh
+    }
+
+    #[test]
+    fn test_in_power_sphere() {
+        use crate::vertex::HasPosition;
+
+        let mesh = weighted_points(40, 0.05);
+        let weights = mesh
+            .vertices()
+            .map(|(id, v)| (*id, v.1))
+            .collect::<Weights>();
+        let ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        let power = |v: VertexId, center: Point3<f64>| {
+            (mesh.position(v) - center).norm_squared() - weights[&v]
+        };
+
+        for chunk in ids.chunks(5).filter(|chunk| chunk.len() == 5) {
+            let (mut tet, m) = ([chunk[0], chunk[1], chunk[2], chunk[3]], chunk[4]);
+            if !sim::orient_3d(&mesh, index_fn, tet[0], tet[1], tet[2], tet[3]) {
+                tet.swap(2, 3);
+            }
+            let tet = TetId(tet);
+            let center = crate::voronoi::tet_orthocenter(&mesh, tet, |v| weights[&v]);
+            let expected = power(m, center) < power(tet.0[0], center);
+            assert_eq!(in_power_sphere(&mesh, tet, m, &weights), expected);
+        }
+    }
+
+    #[test]
+    fn test_weighted_delaunay_tets_zero_weights() {
+        let mesh = weighted_points(100, 0.0);
+        let weighted = mesh
+            .clone()
+            .weighted_delaunay_tets(|_| 0.0, || (), || (), || ());
+        let unweighted = mesh.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            weighted.tet_ids().copied().collect::<FnvHashSet<_>>(),
+            unweighted.tet_ids().copied().collect::<FnvHashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_weighted_delaunay_tets_regular() {
+        let mesh = weighted_points(100, 0.02).weighted_delaunay_tets(|v| v.1, || (), || (), || ());
+        let weights = mesh
+            .vertices()
+            .map(|(id, v)| (*id, v.1))
+            .collect::<Weights>();
+        let used = mesh
+            .tet_ids()
+            .flat_map(|tet| tet.0.to_vec())
+            .collect::<FnvHashSet<_>>();
+
+        // No vertex conflicts with a tet
+        for tet in mesh.tet_ids() {
+            for vertex in mesh.vertex_ids().filter(|v| !tet.contains_vertex(**v)) {
+                assert!(!in_sphere_with_ghosts(
+                    &mesh,
+                    *tet,
+                    *vertex,
+                    VertexId(IdType::MAX),
+                    Some(&weights)
+                ));
+            }
+        }
+
+        // Vertices left out are redundant,
+        // so they must be inside the hull and not conflict with its tet
+        for vertex in mesh.vertex_ids().filter(|v| !used.contains(v)) {
+            let tet = mesh
+                .tet_ids()
+                .find(|tet| {
+                    tet_contains_vertex(&mesh, tet.0[0], tet.0[1], tet.0[2], tet.0[3], *vertex)
+                })
+                .unwrap();
+            assert!(!in_sphere_with_ghosts(
+                &mesh,
+                *tet,
+                *vertex,
+                VertexId(IdType::MAX),
+                Some(&weights)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_weighted_delaunay_tets_redundant() {
+        let mut mesh = ComboMesh0::<(Point3<f64>, f64)>::with_defaults(|| (Point3::origin(), 0.0));
+        let corners = [0.0, 1.0]
+            .iter()
+            .flat_map(|x| {
+                [0.0, 1.0]
+                    .iter()
+                    .flat_map(move |y| [0.0, 1.0].iter().map(move |z| Point3::new(*x, *y, *z)))
+            })
+            .map(|p| (p, 1.0))
+            .collect::<Vec<_>>();
+        mesh.extend_vertices(corners);
+        let center = mesh.add_vertex((Point3::new(0.5, 0.5, 0.5), -1.0));
+
+        let mesh = mesh.weighted_delaunay_tets(|v| v.1, || (), || (), || ());
+        assert_eq!(mesh.num_vertices(), 9);
+        assert_eq!(mesh.vertex_tets(center).count(), 0);
+        let volume = mesh
+            .tet_ids()
+            .map(|tet| tet_volume(&mesh, *tet))
+            .sum::<f64>();
+        assert!((volume - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_exact_power_det_sign() {
+        let positions = [
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        ];
+        // The last point is on the orthosphere, and nudging its weight moves it off
+        assert_eq!(
+            exact_power_det_sign(&positions, &[0.1, 0.1, 0.1, 0.1, 0.1]),
+            Ordering::Equal
+        );
+        assert_eq!(
+            exact_power_det_sign(&positions, &[0.1, 0.1, 0.1, 0.1, 0.1 + 1e-17]),
+            Ordering::Less
+        );
+        assert_eq!(
+            exact_power_det_sign(&positions, &[0.1, 0.1, 0.1, 0.1, 0.1 - 1e-17]),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_weighted_delaunay_tets_grid() {
+        // Lots of vertices on shared orthospheres, which need the exact test and tie-breaking
+        let mut mesh = ComboMesh0::<(Point3<f64>, f64)>::with_defaults(|| (Point3::origin(), 0.0));
+        mesh.extend_vertices((0..64).map(|i| {
+            let (x, y, z) = (i % 4, i / 4 % 4, i / 16);
+            (
+                Point3::new(x as f64, y as f64, z as f64),
+                if (x + y + z) % 2 == 0 { 0.1 } else { 0.0 },
+            )
+        }));
+
+        let mesh = mesh.weighted_delaunay_tets(|v| v.1, || (), || (), || ());
+        assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+        let weights = mesh
+            .vertices()
+            .map(|(id, v)| (*id, v.1))
+            .collect::<Weights>();
+        let volume = mesh
+            .tet_ids()
+            .map(|tet| tet_volume(&mesh, *tet))
+            .sum::<f64>();
+        assert!((volume - 27.0).abs() < 1e-9);
+        for tet in mesh.tet_ids() {
+            for vertex in mesh.vertex_ids().filter(|v| !tet.contains_vertex(**v)) {
+                assert!(!in_sphere_with_ghosts(
+                    &mesh,
+                    *tet,
+                    *vertex,
+                    VertexId(IdType::MAX),
+                    Some(&weights)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        use crate::vertex::HasPosition;
+
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices((0..200).map(|i| {
+            Point3::new(
+                (i as f64 * 0.7).sin() * i as f64,
+                (i as f64 * 1.3).cos() * 50.0,
+                (i as f64 * 2.9).sin() * 30.0,
+            )
+        }));
+        let hull = mesh.clone().convex_hull(|| (), || ());
+        assert!(hull.num_tris() > 0);
+
+        // Every vertex is behind every hull tri, and the surface is closed
+        for tri in hull.tri_ids() {
+            assert!(tri
+                .edges()
+                .iter()
+                .all(|edge| hull.edge_vertex_opps(edge.twin()).count() == 1));
+            for v in mesh.vertex_ids().filter(|v| !tri.contains_vertex(**v)) {
+                assert!(sim::orient_3d(
+                    &mesh, index_fn, tri.0[0], tri.0[1], tri.0[2], *v
+                ));
+            }
+        }
+        let center = mesh
+            .vertex_ids()
+            .map(|v| mesh.position(*v).coords)
+            .sum::<Vector3<f64>>()
+            / 200.0;
+        for tri in hull.tri_ids() {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| hull.position(tri.0[i]));
+            assert!((p1 - p0).cross(&(p2 - p0)).dot(&(p0.coords - center)) > 0.0);
+        }
     }
 
     #[test]
-    fn test_in_sphere_ghost() {
-        let mut mesh = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
-            || Point3::origin(),
-            || (),
-            || (),
-            || (),
+    fn test_convex_hull_cube() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices(
+            (0..27).map(|i| Point3::new((i % 3) as f64, (i / 3 % 3) as f64, (i / 9) as f64)),
         );
-        let ids = mesh.extend_vertices(vec![
+        let hull = mesh.convex_hull(|| (), || ());
+
+        // Only the center is inside; the rest are on the sides, which are coplanar hull tris
+        assert_eq!(hull.num_vertices(), 26);
+        assert_eq!(hull.num_tris(), 2 * hull.num_vertices() - 4);
+        assert_eq!(hull.num_edges(), 3 * hull.num_tris());
+    }
+
+    #[test]
+    fn test_delaunay_tets_same_position() {
+        // Simulation of simplicity is used. This should be perfectly fine.
+
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
+        mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
             Point3::new(0.0, 0.0, 0.0),
             Point3::new(1.0, 0.0, 0.0),
             Point3::new(0.0, 1.0, 0.0),
             Point3::new(0.0, 0.0, 1.0),
-            Point3::new(0.5, 0.3, 0.6),
         ]);
-        let tet = TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]);
-        mesh.add_tet(tet, ());
-
-        assert!(!in_sphere_with_ghosts(&mesh, tet, ids[4], ids[0]));
-        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], ids[1]));
-        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], ids[2]));
-        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], ids[3]));
+        mesh.delaunay_tets(|| (), || (), || ());
     }
 
     #[test]
-    fn test_in_sphere_no_ghost() {
-        let mut mesh = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
-            || Point3::origin(),
-            || (),
-            || (),
-            || (),
-        );
-        let ids = mesh.extend_vertices(vec![
+    fn test_delaunay_tets_same_position_valid() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
             Point3::new(0.0, 0.0, 0.0),
             Point3::new(1.0, 0.0, 0.0),
             Point3::new(0.0, 1.0, 0.0),
             Point3::new(0.0, 0.0, 1.0),
-            Point3::new(0.5, 0.3, 0.6),
         ]);
-        let tet = TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]);
-        mesh.add_tet(tet, ());
 
-        assert!(in_sphere_with_ghosts(&mesh, tet, ids[4], VertexId(5)));
+        let result = mesh.delaunay_tets(|| (), || (), || ());
+        assert_eq!(
+            result.validate_with_positions(),
+            ValidationReport::default()
+        );
     }
 
     #[test]
-    fn test_tets_to_delete() {
-        let mut mesh = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
-            || Point3::origin(),
-            || (),
-            || (),
-            || (),
-        );
-        let ids = mesh.extend_vertices(vec![
-            Point3::new(0.0, 0.0, 0.0),
+    fn test_delaunay_tets_no_flat_tets() {
+        // A grid has coplanar hull tris and cospherical cells,
+        // and the points on a sphere of radius 3 with integer coordinates are all cospherical.
+        let grid = (0..27).map(|i| Point3::new((i % 3) as f64, (i / 3 % 3) as f64, (i / 9) as f64));
+        let sphere = (0..343)
+            .map(|i| {
+                Point3::new(
+                    (i % 7 - 3) as f64,
+                    (i / 7 % 7 - 3) as f64,
+                    (i / 49 - 3) as f64,
+                )
+            })
+            .filter(|p| p.coords.norm_squared() == 9.0);
+
+        for positions in vec![grid.collect::<Vec<_>>(), sphere.collect()] {
+            let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+            mesh.extend_vertices(positions);
+            let mesh = mesh.delaunay_tets(|| (), || (), || ());
+
+            assert!(mesh.num_tets() > 0);
+            for tet in mesh.tet_ids() {
+                let [p0, p1, p2, p3] = tet.0.map(|v| index_fn(&mesh, v));
+                assert!(
+                    exact::orient_3d(p0, p1, p2, p3) > 0.0,
+                    "{:?} is not positive",
+                    tet
+                );
+            }
+            assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+        }
+    }
+
+    #[test]
+    fn test_delaunay_tets_coplanar() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices((0..9).map(|i| Point3::new((i % 3) as f64, (i / 3) as f64, 1.0)));
+        let mesh = mesh.delaunay_tets(|| (), || (), || ());
+        assert_eq!(mesh.num_vertices(), 9);
+        assert_eq!(mesh.num_tets(), 0);
+    }
+
+    #[test]
+    fn test_delaunay_tets_duplicate_positions() {
+        // The inside vertex is duplicated, so one copy is left out either way
+        for first in [true, false] {
+            let mut positions = vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+                Point3::new(0.0, 0.0, 1.0),
+                Point3::new(1.0, 1.0, 1.0),
+                Point3::new(0.3, 0.3, 0.3),
+            ];
+            positions.insert(
+                if first { 0 } else { positions.len() },
+                Point3::new(0.3, 0.3, 0.3),
+            );
+            let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+            mesh.extend_vertices(positions);
+
+            let mut mesh = mesh.try_delaunay_tets(|| (), || (), || ()).unwrap();
+            assert_eq!(mesh.num_tets(), 6);
+            assert_eq!(
+                mesh.vertex_ids()
+                    .filter(|v| mesh.vertex_tets(**v).next().is_none())
+                    .count(),
+                1
+            );
+            assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+
+            // A duplicate of a hull vertex
+            mesh.try_insert_vertex_delaunay(Point3::new(1.0, 1.0, 1.0))
+                .unwrap();
+            assert_eq!(mesh.num_tets(), 6);
+            assert_eq!(
+                mesh.vertex_ids()
+                    .filter(|v| mesh.vertex_tets(**v).next().is_none())
+                    .count(),
+                2
+            );
+            assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+        }
+    }
+
+    #[test]
+    fn test_try_delaunay_tets_errors() {
+        let with = |positions: Vec<Point3<f64>>| {
+            let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+            let ids = mesh.extend_vertices(positions);
+            (mesh, ids)
+        };
+
+        let (mesh, _) = with(vec![
+            Point3::origin(),
             Point3::new(1.0, 0.0, 0.0),
             Point3::new(0.0, 1.0, 0.0),
-            Point3::new(0.0, 0.0, 1.0),
-            Point3::new(1.0, 1.0, 1.0),
-            Point1::new(f64::INFINITY).xxx(),
-            Point3::new(0.1, 0.1, 0.1),
-            Point3::new(0.1, -0.1, 0.1),
-            Point3::new(-1.0, -1.0, 0.1),
-        ]);
-        mesh.extend_tets(vec![
-            ([ids[0], ids[1], ids[3], ids[2]], ()),
-            ([ids[4], ids[1], ids[2], ids[3]], ()),
-            ([ids[5], ids[0], ids[1], ids[3]], ()),
-            ([ids[5], ids[3], ids[2], ids[0]], ()),
-            ([ids[5], ids[1], ids[0], ids[2]], ()),
-            ([ids[5], ids[4], ids[1], ids[2]], ()),
-            ([ids[5], ids[2], ids[3], ids[4]], ()),
-            ([ids[5], ids[1], ids[4], ids[3]], ()),
         ]);
+        assert_eq!(
+            mesh.try_delaunay_tets(|| (), || (), || ()).err(),
+            Some(DelaunayTetsError::TooFewVertices)
+        );
 
-        // In convex hull
-        let result = tets_to_delete(&mesh, ids[6], ids[5]).collect::<FnvHashSet<_>>();
+        let (mesh, _) = with(vec![Point3::new(1.0, 2.0, 3.0); 5]);
         assert_eq!(
-            result,
-            vec![
-                TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]),
-                TetId::from_valid([ids[4], ids[1], ids[2], ids[3]]),
-            ]
-            .into_iter()
-            .collect::<FnvHashSet<_>>()
+            mesh.try_delaunay_tets(|| (), || (), || ()).err(),
+            Some(DelaunayTetsError::AllSamePosition)
         );
 
-        // Remove both solid tetrahedrons and ghost tetrahedrons
-        let result = tets_to_delete(&mesh, ids[7], ids[5]).collect::<FnvHashSet<_>>();
+        let (mesh, _) = with(
+            (0..5)
+                .map(|i| Point3::new(i as f64, 2.0 * i as f64, 1.0))
+                .collect(),
+        );
         assert_eq!(
-            result,
-            vec![
-                TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]),
-                TetId::from_valid([ids[4], ids[1], ids[2], ids[3]]),
-                TetId::from_valid([ids[5], ids[0], ids[1], ids[3]]),
-            ]
-            .into_iter()
-            .collect::<FnvHashSet<_>>()
+            mesh.try_delaunay_tets(|| (), || (), || ()).err(),
+            Some(DelaunayTetsError::AllCollinear)
         );
 
-        // Remove only ghost tetrahedrons
-        let result = tets_to_delete(&mesh, ids[8], ids[5]).collect::<FnvHashSet<_>>();
+        let (mesh, _) = with(vec![
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.5, 0.3, 1.0),
+        ]);
         assert_eq!(
-            result,
-            vec![
-                TetId::from_valid([ids[5], ids[0], ids[1], ids[3]]),
-                TetId::from_valid([ids[5], ids[3], ids[2], ids[0]]),
-            ]
-            .into_iter()
-            .collect::<FnvHashSet<_>>()
+            mesh.try_delaunay_tets(|| (), || (), || ()).err(),
+            Some(DelaunayTetsError::AllCoplanar)
         );
-    }
 
-    #[test]
-    fn test_delaunay_tets_empty() {
-        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
-        mesh.extend_vertices(vec![
+        let (mesh, ids) = with(vec![
             Point3::new(0.0, 0.0, 0.0),
             Point3::new(1.0, 0.0, 0.0),
-            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, f64::NAN, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
         ]);
-
-        let result = mesh.clone().delaunay_tets(|| (), || (), || ());
         assert_eq!(
-            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
-            result.vertex_ids().collect::<FnvHashSet<_>>(),
+            mesh.try_delaunay_tets(|| (), || (), || ()).err(),
+            Some(DelaunayTetsError::NonFinitePosition(ids[2]))
         );
-        assert_tets_m(&result, vec![] as Vec<(TetId, ())>);
     }
 
     #[test]
-    fn test_delaunay_tets_single() {
-        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
-        let ids = mesh.extend_vertices(vec![
+    fn test_try_delaunay_tets_degenerate_start() {
+        // The last vertices are coplanar, so the first tet has to be searched for.
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 1.0),
             Point3::new(0.0, 0.0, 0.0),
             Point3::new(1.0, 0.0, 0.0),
             Point3::new(0.0, 1.0, 0.0),
-            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.5, 0.5, 0.0),
         ]);
-
-        let result = mesh.clone().delaunay_tets(|| (), || (), || ());
-        assert_eq!(
-            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
-            result.vertex_ids().collect::<FnvHashSet<_>>(),
-        );
-        assert_tets_m(
-            &result,
-            vec![(TetId::from_valid([ids[0], ids[1], ids[3], ids[2]]), ())],
-        );
+        let mesh = mesh.try_delaunay_tets(|| (), || (), || ()).unwrap();
+        assert_eq!(mesh.num_vertices(), 6);
+        let volume = mesh
+            .tet_ids()
+            .map(|tet| tet_volume(&mesh, *tet))
+            .sum::<f64>();
+        assert!((volume - 1.0 / 3.0).abs() < 1e-12);
     }
 
     #[test]
-    fn test_delaunay_tets_multiple() {
-        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
-        let ids = mesh.extend_vertices(vec![
+    fn test_try_insert_vertex_delaunay_error() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices(vec![
             Point3::new(0.0, 0.0, 0.0),
             Point3::new(1.0, 0.0, 0.0),
             Point3::new(0.0, 1.0, 0.0),
             Point3::new(0.0, 0.0, 1.0),
             Point3::new(1.5, 1.5, 1.0),
-            Point3::new(0.5, 0.5, 0.5),
         ]);
+        let mut mesh = mesh.delaunay_tets(|| (), || (), || ());
+        let expected = mesh.tet_ids().copied().collect::<FnvHashSet<_>>();
 
-        let result = mesh.clone().delaunay_tets(|| (), || (), || ());
+        let result = mesh.try_insert_vertex_delaunay(Point3::new(f64::NAN, 0.5, 0.5));
+        assert!(matches!(
+            result,
+            Err(DelaunayTetsError::NonFinitePosition(_))
+        ));
+        assert_eq!(mesh.num_vertices(), 5);
+        assert_eq!(mesh.tet_ids().copied().collect::<FnvHashSet<_>>(), expected);
+        assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+    }
+
+    #[test]
+    fn test_try_weighted_delaunay_tets_errors() {
+        let mut mesh = weighted_points(20, 0.05);
+        let ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        mesh.vertex_mut(ids[3]).unwrap().0.y = f64::INFINITY;
         assert_eq!(
-            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
-            result.vertex_ids().collect::<FnvHashSet<_>>(),
+            mesh.try_weighted_delaunay_tets(|v| v.1, || (), || (), || ())
+                .err(),
+            Some(DelaunayTetsError::NonFinitePosition(ids[3]))
         );
-        assert_tets_m(
-            &result,
-            vec![
-                (TetId::from_valid([ids[0], ids[3], ids[2], ids[5]]), ()),
-                (TetId::from_valid([ids[0], ids[1], ids[3], ids[5]]), ()),
-                (TetId::from_valid([ids[1], ids[0], ids[2], ids[5]]), ()),
-                (TetId::from_valid([ids[1], ids[4], ids[3], ids[5]]), ()),
-                (TetId::from_valid([ids[3], ids[4], ids[2], ids[5]]), ()),
-                (TetId::from_valid([ids[2], ids[4], ids[1], ids[5]]), ()),
-            ],
+
+        let mesh = weighted_points(20, 0.05);
+        let result = mesh
+            .try_weighted_delaunay_tets(|v| v.1, || (), || (), || ())
+            .unwrap();
+        assert_eq!(
+            result.validate_with_positions(),
+            ValidationReport::default()
         );
     }
 
     #[test]
-    fn test_delaunay_tets_same_position() {
-        // Simulation of simplicity is used. This should be perfectly fine.
-
-        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(|| Point3::origin());
+    fn test_try_convex_hull_error() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
         mesh.extend_vertices(vec![
-            Point3::new(0.0, 0.0, 0.0),
             Point3::new(0.0, 0.0, 0.0),
             Point3::new(1.0, 0.0, 0.0),
             Point3::new(0.0, 1.0, 0.0),
             Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.5, f64::NAN, 0.5),
         ]);
-        mesh.delaunay_tets(|| (), || (), || ());
+        let ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+        assert_eq!(
+            mesh.try_convex_hull(|| (), || ()).err(),
+            Some(DelaunayTetsError::NonFinitePosition(ids[4]))
+        );
     }
 
     #[test]
@@ -862,19 +3624,286 @@ mod tests {
 
     #[test]
     fn test_import() {
+        use crate::io::obj::{load_obj, save_obj};
         use crate::mesh2::ComboMesh2;
-        let mut mesh =
-            ComboMesh2::read_obj("assets/holey.obj", || Point3::origin(), || (), || ()).unwrap();
+        let mut mesh = ComboMesh2::with_defaults(Point3::origin, || (), || ());
+        load_obj(&mut mesh, "assets/holey.obj").unwrap();
         let mut tets = mesh.clone().delaunay_tets(|| (), || (), || ());
-        let mut steiner_map = recover_edges(&mut tets, &mut mesh, None);
+        let mut steiner_map = recover_edges(&mut tets, &mut mesh, None).unwrap();
         //for (edge, vertex) in &steiner_map {
         //    println!("Steiner vertex {:?} at edge {:?}", vertex, edge);
         //}
-        recover_tris(&mut tets, &mut mesh, &mut steiner_map);
+        recover_tris(&mut tets, &mut mesh, &mut steiner_map).unwrap();
         remove_external_tets(&mut tets, &mut mesh);
 
-        tets.to_separate_tets()
-            .write_obj("assets/holey_out.obj")
-            .unwrap();
+        save_obj(&tets.to_separate_tets(), "assets/holey_out.obj").unwrap();
+    }
+
+    #[test]
+    fn test_constrained_delaunay_tets_dented_cube() {
+        use crate::mesh2::ComboMesh2;
+        use crate::vertex::{HasPosition, HasPosition3D};
+
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.5, 0.5, 0.5),
+        ]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[2], ids[1]], ()),
+            ([ids[1], ids[2], ids[3]], ()),
+            ([ids[0], ids[1], ids[4]], ()),
+            ([ids[1], ids[5], ids[4]], ()),
+            ([ids[1], ids[3], ids[5]], ()),
+            ([ids[3], ids[7], ids[5]], ()),
+            ([ids[3], ids[2], ids[7]], ()),
+            ([ids[2], ids[6], ids[7]], ()),
+            ([ids[2], ids[0], ids[6]], ()),
+            ([ids[0], ids[4], ids[6]], ()),
+            // Dented top
+            ([ids[4], ids[5], ids[8]], ()),
+            ([ids[5], ids[7], ids[8]], ()),
+            ([ids[7], ids[6], ids[8]], ()),
+            ([ids[6], ids[4], ids[8]], ()),
+        ]);
+
+        let (result, _, tri_steiner_map) = mesh.clone().constrained_delaunay_tets(|| ());
+
+        for tri in mesh.tri_ids() {
+            assert!(
+                tri_steiner_map.contains_key(tri) || result.contains_tri(*tri),
+                "Triangle {:?} was not recovered",
+                tri
+            );
+        }
+
+        let volume = result
+            .tet_ids()
+            .map(|tet| {
+                let [v0, v1, v2, v3] = tet.vertices();
+                let p0 = result.position(v0);
+                (result.position(v1) - p0)
+                    .cross(&(result.position(v2) - p0))
+                    .dot(&(result.position(v3) - p0))
+                    .abs()
+                    / 6.0
+            })
+            .sum::<f64>();
+        assert!((volume - 5.0 / 6.0).abs() < 1e-9, "Volume is {}", volume);
+    }
+
+    #[test]
+    fn test_try_constrained_delaunay_tets_non_finite() {
+        use crate::mesh2::ComboMesh2;
+
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, f64::NAN),
+        ]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[2], ids[1]], ()),
+            ([ids[0], ids[1], ids[3]], ()),
+            ([ids[1], ids[2], ids[3]], ()),
+            ([ids[2], ids[0], ids[3]], ()),
+        ]);
+
+        let err = mesh.try_constrained_delaunay_tets(|| ()).unwrap_err();
+        assert_eq!(
+            err,
+            ConstrainedTetsError::Delaunay(DelaunayTetsError::NonFinitePosition(ids[3]))
+        );
+    }
+
+    #[test]
+    fn test_tet_circumcenter() {
+        use crate::vertex::HasPosition;
+
+        let mut mesh = MwbComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            Point3::origin,
+            || (),
+            || (),
+            || (),
+        );
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(0.0, 0.0, 2.0),
+        ]);
+        let tet = TetId::from_valid([ids[0], ids[1], ids[2], ids[3]]);
+
+        let center = tet_circumcenter(&mesh, tet);
+        assert!((center - Point3::new(1.0, 1.0, 1.0)).norm() < 1e-12);
+
+        let center = tri_circumcenter(&mesh, tet.tris()[0]);
+        for v in &tet.tris()[0].0 {
+            assert!(
+                ((mesh.position(*v) - center).norm()
+                    - (mesh.position(tet.tris()[0].0[0]) - center).norm())
+                .abs()
+                    < 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_refine_tets_cube() {
+        use crate::mesh2::ComboMesh2;
+        use crate::vertex::{HasPosition, HasPosition3D};
+
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(0.0, 1.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+        ]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[2], ids[1]], ()),
+            ([ids[1], ids[2], ids[3]], ()),
+            ([ids[0], ids[1], ids[4]], ()),
+            ([ids[1], ids[5], ids[4]], ()),
+            ([ids[1], ids[3], ids[5]], ()),
+            ([ids[3], ids[7], ids[5]], ()),
+            ([ids[3], ids[2], ids[7]], ()),
+            ([ids[2], ids[6], ids[7]], ()),
+            ([ids[2], ids[0], ids[6]], ()),
+            ([ids[0], ids[4], ids[6]], ()),
+            ([ids[4], ids[5], ids[7]], ()),
+            ([ids[4], ids[7], ids[6]], ()),
+        ]);
+
+        let (mut result, _, _) = mesh.constrained_delaunay_tets(|| ());
+        let inserted = result.refine_tets(vec![], 2.0, 0.01);
+        assert!(inserted > 0);
+
+        let volumes = result
+            .tet_ids()
+            .map(|tet| {
+                let [v0, v1, v2, v3] = tet.vertices();
+                let p0 = result.position(v0);
+                // Positive tets have the fourth vertex below the first three
+                -(result.position(v1) - p0)
+                    .cross(&(result.position(v2) - p0))
+                    .dot(&(result.position(v3) - p0))
+                    / 6.0
+            })
+            .collect::<Vec<_>>();
+        assert!(volumes.iter().all(|volume| *volume > 0.0));
+        assert!(result
+            .tet_ids()
+            .all(|tet| !is_bad_tet(&result, *tet, 2.0, 0.01)));
+        let volume = volumes.iter().sum::<f64>();
+        assert!((volume - 1.0).abs() < 1e-9, "Volume is {}", volume);
+
+        for tri in result
+            .tri_ids()
+            .filter(|tri| !result.contains_tri(tri.twin()))
+        {
+            let on_face = (0..3).any(|axis| {
+                let coord = result.position(tri.0[0])[axis];
+                (coord == 0.0 || coord == 1.0)
+                    && tri.0.iter().all(|v| result.position(*v)[axis] == coord)
+            });
+            assert!(
+                on_face,
+                "Boundary triangle {:?} is not on a face of the cube",
+                tri
+            );
+        }
+    }
+
+    #[test]
+    fn test_refine_tets_interior_constraint() {
+        use crate::mesh2::ComboMesh2;
+        use crate::vertex::{HasPosition, HasPosition3D};
+
+        // Two stacked prisms, with the triangle between them as a constraint
+        let mut mesh =
+            ComboMesh2::<Point3<f64>, (), ()>::with_defaults(Point3::origin, || (), || ());
+        let corners = [(0.0, 0.0), (1.0, 0.0), (0.5, 0.75f64.sqrt())];
+        let ids = mesh.extend_vertices(
+            (0..9)
+                .map(|i| Point3::new(corners[i % 3].0, corners[i % 3].1, (i / 3) as f64))
+                .collect::<Vec<_>>(),
+        );
+        let mut tris = vec![[0, 2, 1], [6, 7, 8]];
+        for k in [0, 3] {
+            for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+                tris.push([i + k, j + k, j + k + 3]);
+                tris.push([i + k, j + k + 3, i + k + 3]);
+            }
+        }
+        mesh.extend_tris(
+            tris.into_iter()
+                .map(|[i, j, l]| ([ids[i], ids[j], ids[l]], ()))
+                .collect::<Vec<_>>(),
+        );
+
+        let (mut result, _, _) = mesh.constrained_delaunay_tets(|| ());
+        let constraints = result
+            .tri_ids()
+            .copied()
+            .filter(|tri| {
+                result.contains_tri(tri.twin())
+                    && tri.0.iter().all(|v| result.position(*v).z == 1.0)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(constraints.len(), 2);
+
+        let inserted = result.refine_tets(constraints, 2.0, 0.01);
+        assert!(inserted > 0);
+        assert!(result
+            .tet_ids()
+            .all(|tet| !is_bad_tet(&result, *tet, 2.0, 0.01)));
+
+        // No tet crosses the constraint, so its pieces cover it.
+        for tet in result.tet_ids() {
+            let z = tet
+                .vertices()
+                .iter()
+                .map(|v| result.position(*v).z)
+                .collect::<Vec<_>>();
+            assert!(
+                z.iter().all(|z| *z >= 1.0) || z.iter().all(|z| *z <= 1.0),
+                "Tet {:?} crosses the constraint",
+                tet
+            );
+        }
+        let area = result
+            .tri_ids()
+            .filter(|tri| {
+                **tri == tri.undirected() && tri.0.iter().all(|v| result.position(*v).z == 1.0)
+            })
+            .map(|tri| {
+                let p0 = result.position(tri.0[0]);
+                (result.position(tri.0[1]) - p0)
+                    .cross(&(result.position(tri.0[2]) - p0))
+                    .norm()
+                    / 2.0
+            })
+            .sum::<f64>();
+        assert!(
+            (area - 0.75f64.sqrt() / 2.0).abs() < 1e-9,
+            "Area is {}",
+            area
+        );
     }
 }
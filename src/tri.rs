@@ -1,7 +1,8 @@
 //! Traits and structs related to triangles
 
+use float_ord::FloatOrd;
 use fnv::{FnvHashMap, FnvHashSet};
-use nalgebra::{allocator::Allocator, DefaultAllocator};
+use nalgebra::{allocator::Allocator, dimension::U2, DefaultAllocator, Point2};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map;
@@ -9,17 +10,22 @@ use std::convert::{TryFrom, TryInto};
 use std::iter::Map;
 use typenum::{Bit, B0, B1};
 
+use crate::edge::check_new_edge;
+use crate::error::MeshError;
+use crate::exact;
+use crate::io::obj::{Obj, ObjAttributes, ObjCorner, ObjFace};
 use crate::iter::{IteratorExt, MapWith};
 use crate::private::{Key, Lock};
-use crate::vertex::VertexId;
+use crate::triangulate::index_fn;
+use crate::vertex::{Skeleton, VertexId};
 use crate::{
     edge::{Edge, IntoEdges, Link},
     vertex::{HasVertices, IntoVertices},
 };
 use crate::{
     edge::{EdgeId, EdgeWalker, HasEdges, VertexEdgesOut},
-    tet::{HasTets, TetWalker},
-    vertex::{HasPosition, HasPositionDim, HasPositionPoint, Position},
+    tet::{HasTets, TetWalker, WithTets},
+    vertex::{HasPosition, HasPosition2D, HasPositionDim, HasPositionPoint, Position},
 };
 
 /// An triangle id is just the triangle's vertices in winding order,
@@ -30,13 +36,13 @@ use crate::{
 pub struct TriId(pub(crate) [VertexId; 3]);
 
 impl TryFrom<[VertexId; 3]> for TriId {
-    type Error = &'static str;
+    type Error = MeshError;
 
     fn try_from(mut v: [VertexId; 3]) -> Result<Self, Self::Error> {
         v = Self::canonicalize(v);
 
         if v[0] == v[1] || v[2] == v[0] || v[1] == v[2] {
-            Err("Vertices are not allowed to be the same")
+            Err(MeshError::DegenerateSimplex(v.to_vec()))
         } else {
             Ok(TriId(v))
         }
@@ -214,6 +220,26 @@ pub trait WithTris<V, E, F> {
     type WithTris: HasVertices<V = V> + HasEdges<E = E> + HasTris<F = F>;
 }
 
+/// The 1-skeleton is the vertices and edges.
+impl<M: HasTris> Skeleton<1> for M
+where
+    M::V: Clone,
+    M::E: Clone,
+{
+    type Skeleton = M::WithoutTris;
+
+    fn skeleton_r<L: Lock>(&self) -> Self::Skeleton {
+        let mut mesh = M::WithoutTris::from_ve_r::<_, _, Key>(
+            self.vertices().map(|(id, v)| (*id, v.clone())),
+            self.edges().map(|(id, e)| (*id, e.clone())),
+            self.default_v_r::<Key>(),
+            self.default_e_r::<Key>(),
+        );
+        *mesh.next_vertex_id_mut::<Key>() = self.next_vertex_id::<Key>();
+        mesh
+    }
+}
+
 /// For simplicial complexes that can have triangles
 pub trait HasTris: HasEdges<HigherE = B1> {
     type Tri: Tri<F = Self::F, Mwb = Self::MwbF, Higher = Self::HigherF>;
@@ -272,43 +298,64 @@ pub trait HasTris: HasEdges<HigherE = B1> {
     fn default_f_r<L: Lock>(&self) -> fn() -> Self::F;
 
     #[doc(hidden)]
-    #[cfg(feature = "obj")]
-    fn obj_with_tris<L: Lock>(&self, data: &mut obj::ObjData, v_inv: &FnvHashMap<VertexId, usize>) {
+    fn obj_with_tris<L: Lock>(&self, obj: &mut Obj, v_inv: &FnvHashMap<VertexId, usize>) {
         // Triangles
-        data.objects[0].groups[0]
-            .polys
-            .extend(self.tri_ids().map(|tri| {
-                obj::SimplePolygon(vec![
-                    obj::IndexTuple(v_inv[&tri.0[0]], None, None),
-                    obj::IndexTuple(v_inv[&tri.0[1]], None, None),
-                    obj::IndexTuple(v_inv[&tri.0[2]], None, None),
-                ])
-            }));
+        obj.faces.extend(self.tri_ids().map(|tri| {
+            ObjFace {
+                corners: tri
+                    .0
+                    .iter()
+                    .map(|v| ObjCorner {
+                        position: v_inv[v],
+                        uv: None,
+                        normal: None,
+                    })
+                    .collect(),
+                group: None,
+            }
+        }));
 
         // Isolated edges
-        data.objects[0].groups[0].polys.extend(
+        obj.lines.extend(
             self.edge_ids()
-                .flat_map(|e| self.edge_vertex_opps(*e).next().map(|_| e.undirected()))
+                .filter(|e| {
+                    self.edge_vertex_opps(**e).next().is_none()
+                        && self.edge_vertex_opps(e.twin()).next().is_none()
+                })
+                .map(|e| e.undirected())
                 .collect::<FnvHashSet<_>>()
                 .into_iter()
-                .map(|edge| {
-                    obj::SimplePolygon(vec![
-                        obj::IndexTuple(v_inv[&edge.0[0]], None, None),
-                        obj::IndexTuple(v_inv[&edge.0[1]], None, None),
-                    ])
-                }),
+                .map(|edge| vec![v_inv[&edge.0[0]], v_inv[&edge.0[1]]]),
         );
 
-        self.obj_with_tris_higher::<Key>(data, v_inv);
+        self.obj_with_tris_higher::<Key>(obj, v_inv);
     }
 
     #[doc(hidden)]
-    #[cfg(feature = "obj")]
-    fn obj_with_tris_higher<L: Lock>(
-        &self,
-        data: &mut obj::ObjData,
-        v_inv: &FnvHashMap<VertexId, usize>,
-    );
+    fn obj_with_tris_higher<L: Lock>(&self, obj: &mut Obj, v_inv: &FnvHashMap<VertexId, usize>);
+
+    /// Adds the faces of OBJ data as fan-triangulated triangles.
+    /// Triangles that are already there are skipped,
+    /// and ones that conflict with the mesh's manifoldness get recorded in the attributes.
+    #[doc(hidden)]
+    fn obj_add_tris<L: Lock>(
+        &mut self,
+        obj: &Obj,
+        ids: &[VertexId],
+        attributes: &mut ObjAttributes,
+    ) {
+        for (tri, group) in obj.face_tris(ids) {
+            match self.try_add_tri(tri, self.default_tri()) {
+                Ok(()) => {
+                    if let Some(group) = group {
+                        attributes.tri_groups.insert(tri, obj.groups[group].clone());
+                    }
+                }
+                Err(MeshError::DuplicateId(_)) => {}
+                Err(_) => attributes.skipped_tris.push(tri),
+            }
+        }
+    }
 
     #[doc(hidden)]
     fn flip12_tri_higher<EI: TryInto<EdgeId>, FI: TryInto<TriId>, L: Lock, C: FnMut(&mut Self)>(&mut self, edge: EI, vertex: VertexId, tri: FI, callback: C);
@@ -496,6 +543,95 @@ pub trait HasTris: HasEdges<HigherE = B1> {
         }
     }
 
+    /// Splits a triangle into 3 triangles with `vertex` in the middle,
+    /// or reports why that can't be done.
+    /// This checks that the triangle exists and that the new ones can replace it
+    /// without replacing or removing anything else, but it doesn't check the geometry.
+    fn try_flip13<FI>(&mut self, tri: FI, vertex: VertexId) -> Result<(), MeshError>
+    where
+        FI: TryInto<TriId>,
+        MeshError: From<FI::Error>,
+        Self::F: Clone,
+    {
+        let tri = tri.try_into()?;
+        if !self.contains_vertex(vertex) {
+            return Err(MeshError::MissingVertex(vertex));
+        }
+        if tri.contains_vertex(vertex) {
+            return Err(MeshError::DegenerateSimplex(vec![
+                tri.0[0], tri.0[1], tri.0[2], vertex,
+            ]));
+        }
+        let removed = [tri, tri.twin()]
+            .iter()
+            .copied()
+            .filter(|tri| self.contains_tri(*tri))
+            .collect::<Vec<_>>();
+        if removed.is_empty() {
+            return Err(MeshError::MissingSimplex(tri.into()));
+        }
+        for tri in &removed {
+            for added in &[
+                [tri.0[0], tri.0[1], vertex],
+                [tri.0[1], tri.0[2], vertex],
+                [tri.0[2], tri.0[0], vertex],
+            ] {
+                check_new_tri_after_removing(self, TriId::from_valid(*added), &removed)?;
+            }
+        }
+        self.flip13(tri, vertex);
+        Ok(())
+    }
+
+    /// Flips away the edge connecting the first 2 vertices,
+    /// creating an edge connecting the last 2 vertices.
+    /// [v1, v2, vp] and [v2, v1, vn] must be triangles.
+    /// This sets custom values to their default.
+    /// This method is pretty much unchecked.
+    fn flip22(&mut self, v1: VertexId, v2: VertexId, vp: VertexId, vn: VertexId)
+    where
+        Self: HasTris<MwbF = B1>,
+    {
+        self.remove_tri(TriId::from_valid([v1, v2, vp])).unwrap();
+        self.remove_tri(TriId::from_valid([v2, v1, vn])).unwrap();
+        self.add_tri(TriId::from_valid([v1, vn, vp]), self.default_tri());
+        self.add_tri(TriId::from_valid([vn, v2, vp]), self.default_tri());
+    }
+
+    /// Flips away the edge connecting the first 2 vertices,
+    /// creating an edge connecting the last 2 vertices,
+    /// or reports why that can't be done.
+    /// This checks that the triangles to flip exist and that the new ones can replace them
+    /// without replacing or removing anything else, but it doesn't check the geometry.
+    fn try_flip22(
+        &mut self,
+        v1: VertexId,
+        v2: VertexId,
+        vp: VertexId,
+        vn: VertexId,
+    ) -> Result<(), MeshError>
+    where
+        Self: HasTris<MwbF = B1>,
+    {
+        let removed = [
+            TriId::try_from([v1, v2, vp])?,
+            TriId::try_from([v2, v1, vn])?,
+        ];
+        for tri in &removed {
+            if !self.contains_tri(*tri) {
+                return Err(MeshError::MissingSimplex((*tri).into()));
+            }
+        }
+        for tri in &[
+            TriId::try_from([v1, vn, vp])?,
+            TriId::try_from([vn, v2, vp])?,
+        ] {
+            check_new_tri_after_removing(self, *tri, &removed)?;
+        }
+        self.flip22(v1, v2, vp, vn);
+        Ok(())
+    }
+
     /// Adds a triangle to the mesh. Vertex order is important!
     /// If the triangle was already there, this replaces the value.
     /// Adds in the required edges if they aren't there already.
@@ -582,6 +718,34 @@ pub trait HasTris: HasEdges<HigherE = B1> {
         })
     }
 
+    /// Adds a triangle to the mesh,
+    /// or reports why it can't be added without touching the rest of the mesh.
+    /// Unlike `add_tri`, this fails instead of replacing an existing triangle
+    /// or removing the triangles in the way in a mwb tri mesh.
+    fn try_add_tri<FI>(&mut self, vertices: FI, value: Self::F) -> Result<(), MeshError>
+    where
+        FI: TryInto<TriId>,
+        MeshError: From<FI::Error>,
+    {
+        let id = vertices.try_into()?;
+        check_new_tri(self, id)?;
+        self.add_tri(id, value);
+        Ok(())
+    }
+
+    /// Extends the triangle list with an iterator,
+    /// stopping at the first triangle that can't be added.
+    /// The triangles before that one stay added.
+    fn try_extend_tris<FI, I>(&mut self, iter: I) -> Result<(), MeshError>
+    where
+        FI: TryInto<TriId>,
+        MeshError: From<FI::Error>,
+        I: IntoIterator<Item = (FI, Self::F)>,
+    {
+        iter.into_iter()
+            .try_for_each(|(id, value)| self.try_add_tri(id, value))
+    }
+
     /// Removes an triangle from the mesh and returns the value that was there,
     /// or None if there was nothing there.
     /// Removes the edges that are part of the triangle if they are part of no other triangles
@@ -696,6 +860,91 @@ pub trait HasTris: HasEdges<HigherE = B1> {
         }
     }
 
+    /// Turns this mesh into an edge mesh, keeping the ids and values of the vertices and edges.
+    fn into_without_tris(self) -> Self::WithoutTris
+    where
+        Self: Sized,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let default_e = self.default_e_r::<Key>();
+        let next_vertex_id = self.next_vertex_id::<Key>();
+        let (vertices, edges, _) = self.into_vef_r::<Key>();
+        let mut mesh =
+            Self::WithoutTris::from_ve_r::<_, _, Key>(vertices, edges, default_v, default_e);
+        *mesh.next_vertex_id_mut::<Key>() = next_vertex_id;
+        mesh
+    }
+
+    /// Turns this tri mesh into a tet mesh with no tetrahedrons, keeping all ids and values.
+    fn into_with_tets<T>(self, default_t: fn() -> T) -> Self::WithTets
+    where
+        Self: Sized
+            + HasTris<HigherF = B0>
+            + WithTets<<Self as HasVertices>::V, <Self as HasEdges>::E, <Self as HasTris>::F, T>,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let default_e = self.default_e_r::<Key>();
+        let default_f = self.default_f_r::<Key>();
+        let next_vertex_id = self.next_vertex_id::<Key>();
+        let (vertices, edges, tris) = self.into_vef_r::<Key>();
+        let mut mesh = Self::WithTets::from_veft_r::<_, _, _, _, Key>(
+            vertices,
+            edges,
+            tris,
+            vec![],
+            default_v,
+            default_e,
+            default_f,
+            default_t,
+        );
+        *mesh.next_vertex_id_mut::<Key>() = next_vertex_id;
+        mesh
+    }
+
+    /// Gets the boundary of this mesh as loops of the edges that are part of a triangle
+    /// while their twins aren't. The edges of a loop are in order,
+    /// each starting where the last one ends,
+    /// and go the same way around as their triangles.
+    /// Where the boundary touches itself at a vertex, the loops through it are split arbitrarily.
+    fn boundary_loops(&self) -> Vec<Vec<EdgeId>> {
+        let mut edges_out = FnvHashMap::<VertexId, Vec<EdgeId>>::default();
+        let mut edges = vec![];
+        for (id, _) in self.edges() {
+            if self.edge_vertex_opps(*id).next().is_some()
+                && self.edge_vertex_opps(id.twin()).next().is_none()
+            {
+                edges_out.entry(id.source()).or_default().push(*id);
+                edges.push(*id);
+            }
+        }
+        edges.sort_unstable();
+
+        let mut loops = vec![];
+        for start in edges {
+            let out = edges_out.get_mut(&start.source()).unwrap();
+            if let Some(pos) = out.iter().position(|e| *e == start) {
+                out.swap_remove(pos);
+            } else {
+                continue;
+            }
+
+            let mut boundary_loop = vec![start];
+            let mut vertex = start.target();
+            while vertex != start.source() {
+                match edges_out.get_mut(&vertex).and_then(|out| out.pop()) {
+                    Some(edge) => {
+                        boundary_loop.push(edge);
+                        vertex = edge.target();
+                    }
+                    None => break,
+                }
+            }
+            loops.push(boundary_loop);
+        }
+
+        loops
+    }
+
     /// Gets a triangle walker that starts at the given edge.
     /// Returns None if the edge has no triangle.
     fn tri_walker_from_edge<EI: TryInto<EdgeId>>(&self, edge: EI) -> Option<TriWalker<Self>> {
@@ -722,6 +971,41 @@ pub trait HasTris: HasEdges<HigherE = B1> {
     }
 }
 
+/// Checks that a triangle can be added without replacing or removing anything.
+pub(crate) fn check_new_tri<M: HasTris + ?Sized>(mesh: &M, id: TriId) -> Result<(), MeshError> {
+    check_new_tri_after_removing(mesh, id, &[])
+}
+
+/// Checks that a triangle can be added without replacing or removing anything
+/// once the triangles in `removed` are gone.
+fn check_new_tri_after_removing<M: HasTris + ?Sized>(
+    mesh: &M,
+    id: TriId,
+    removed: &[TriId],
+) -> Result<(), MeshError> {
+    if let Some(v) = id.0.iter().find(|v| !mesh.contains_vertex(**v)) {
+        return Err(MeshError::MissingVertex(*v));
+    }
+    if mesh.contains_tri(id) && !removed.contains(&id) {
+        return Err(MeshError::DuplicateId(id.into()));
+    }
+
+    for edge in &id.edges() {
+        if !mesh.contains_edge(*edge) {
+            check_new_edge(mesh, *edge)?;
+        } else if <M::MwbF as Bit>::BOOL {
+            let kept = mesh
+                .edge_vertex_opps(*edge)
+                .map(|opp| TriId::from_valid([edge.0[0], edge.0[1], opp]))
+                .find(|tri| !removed.contains(tri));
+            if let Some(tri) = kept {
+                return Err(MeshError::NonManifold(tri.into()));
+            }
+        }
+    }
+    Ok(())
+}
+
 /// A walker for navigating a simplicial complex by triangle
 #[derive(Debug)]
 pub struct TriWalker<'a, M: ?Sized>
@@ -995,6 +1279,68 @@ macro_rules! impl_index_tri {
     };
 }
 
+/// Where a point is relative to a triangulation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TriLocation {
+    /// Strictly inside a triangle
+    Tri(TriId),
+    /// On an edge, but not on its vertices.
+    /// The edge is the edge of a triangle the point is in.
+    Edge(EdgeId),
+    /// At a vertex
+    Vertex(VertexId),
+    /// Outside the triangulation
+    Outside,
+}
+
+/// Gets where a point is in a triangle, or some edge of it the point is strictly past.
+/// `start` picks which edge to check first, so walks don't go in circles.
+fn point_location_in_tri<M>(
+    mesh: &M,
+    tri: TriId,
+    point: Point2<f64>,
+    start: usize,
+) -> Result<TriLocation, EdgeId>
+where
+    M: HasTris + HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    let edges = tri.edges_and_opp();
+    let mut on = vec![];
+
+    for i in 0..3 {
+        let (edge, opp) = edges[(i + start) % 3];
+        let [a, b] = [index_fn(mesh, edge.0[0]), index_fn(mesh, edge.0[1])];
+        let side = exact::orient_2d(a, b, point.coords);
+        let opp_side = exact::orient_2d(a, b, index_fn(mesh, opp));
+        let inside = if opp_side == 0.0 {
+            // Flat triangle
+            sim::orient_2d(mesh, index_fn, edge.0[0], edge.0[1], opp)
+        } else {
+            opp_side > 0.0
+        };
+
+        if side == 0.0 {
+            on.push(edge);
+        } else if (side > 0.0) != inside {
+            return Err(edge);
+        }
+    }
+
+    match on[..] {
+        [] => Ok(TriLocation::Tri(tri)),
+        [edge] => Ok(TriLocation::Edge(edge)),
+        // The point can be on all 3 lines of a flat triangle without being in it
+        _ => on[0]
+            .vertices()
+            .iter()
+            .find(|v| on.iter().all(|edge| edge.0.contains(*v)))
+            .map(|v| TriLocation::Vertex(*v))
+            .ok_or(on[0]),
+    }
+}
+
 /// For concrete simplicial complexes with triangles
 pub trait HasPositionAndTris: HasTris + HasPosition
 where
@@ -1010,6 +1356,56 @@ where
         let v2 = self.position(tri.0[2]);
         [v0, v1, v2]
     }
+
+    /// Finds where a point is in this triangulation by walking
+    /// from triangle to triangle toward it, using exact orientation tests.
+    /// The walk starts at `hint` if it's given and exists,
+    /// and at the closest of a few triangles sampled evenly across the mesh otherwise.
+    /// Returns None if there are no triangles.
+    ///
+    /// The triangulation should be convex, like a Delaunay one.
+    /// Otherwise the walk can leave it on the way to the point and report it outside.
+    ///
+    /// This isn't just `locate_point` because meshes with tetrahedrons also have triangles,
+    /// so it would clash with `HasPositionAndTets::locate_point_in_tets`.
+    fn locate_point_in_tris(&self, point: Point2<f64>, hint: Option<TriId>) -> Option<TriLocation>
+    where
+        Self: Sized + HasPosition2D,
+        Self::V: Position<Dim = U2>,
+    {
+        let distance_squared =
+            |tri: &TriId| FloatOrd((self.position(tri.0[0]) - point).norm_squared());
+
+        // Jump
+        let num_samples = (self.num_tris() as f64).cbrt().ceil() as usize;
+        let mut tri = hint.filter(|tri| self.contains_tri(*tri)).or_else(|| {
+            // Samples spread over all the tris, not just the first ones
+            let stride = (self.num_tris() / num_samples.max(1)).max(1);
+            self.tri_ids()
+                .step_by(stride)
+                .take(num_samples)
+                .copied()
+                .min_by_key(distance_squared)
+        })?;
+
+        // Walk
+        for step in 0..=self.num_tris() {
+            match point_location_in_tri(self, tri, point, step) {
+                Ok(location) => return Some(location),
+                Err(edge) => match self.edge_tris(edge.twin()).next() {
+                    Some(next) => tri = next,
+                    None => return Some(TriLocation::Outside),
+                },
+            }
+        }
+
+        // The walk went in circles, so the triangulation isn't Delaunay
+        Some(
+            self.tri_ids()
+                .find_map(|tri| point_location_in_tri(self, *tri, point, 0).ok())
+                .unwrap_or(TriLocation::Outside),
+        )
+    }
 }
 
 impl<M: HasTris + HasPosition> HasPositionAndTris for M
@@ -1264,8 +1660,12 @@ macro_rules! impl_has_tris {
         }
 
         crate::if_b0! { $higher =>
-            #[cfg(feature = "obj")]
-            fn obj_with_tris_higher<L: crate::private::Lock>(&self, _: &mut obj::ObjData, _: &fnv::FnvHashMap<crate::vertex::VertexId, usize>) {}
+            fn obj_with_tris_higher<L: crate::private::Lock>(
+                &self,
+                _: &mut crate::io::obj::Obj,
+                _: &fnv::FnvHashMap<crate::vertex::VertexId, usize>,
+            ) {
+            }
 
             fn flip12_tri_higher<
                 EI: std::convert::TryInto<crate::edge::EdgeId>, FI: std::convert::TryInto<crate::tri::TriId>, L: crate::private::Lock, C: FnMut(&mut Self)
@@ -1273,9 +1673,12 @@ macro_rules! impl_has_tris {
         }
 
         crate::if_b1! { $higher =>
-            #[cfg(feature = "obj")]
-            fn obj_with_tris_higher<L: crate::private::Lock>(&self, data: &mut obj::ObjData, v_inv: &fnv::FnvHashMap<crate::vertex::VertexId, usize>) {
-                self.obj_with_tets::<crate::private::Key>(data, v_inv);
+            fn obj_with_tris_higher<L: crate::private::Lock>(
+                &self,
+                obj: &mut crate::io::obj::Obj,
+                v_inv: &fnv::FnvHashMap<crate::vertex::VertexId, usize>,
+            ) {
+                self.obj_with_tets::<crate::private::Key>(obj, v_inv);
             }
 
             fn flip12_tri_higher<
@@ -0,0 +1,1282 @@
+use crate::{
+    edge::{EdgeId, HasEdges},
+    exact, iter,
+    tetrahedralize::RecoveryError,
+    tri::{HasTris, TriId},
+    vertex::{HasPosition2D, HasVertices, Position, VertexId},
+};
+use float_ord::FloatOrd;
+use fnv::{FnvHashMap, FnvHashSet};
+use nalgebra::{dimension::U2, Point1, Point2, Vector2};
+use simplicity as sim;
+use std::collections::VecDeque;
+use std::fmt;
+use typenum::B1;
+
+/// A mesh along with a map from split edges to the vertices that split them.
+pub type WithSplitEdges<M> = (M, FnvHashMap<EdgeId, VertexId>);
+
+/// Why a Delaunay triangulation failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DelaunayTrisError {
+    /// Some vertex has a coordinate that is infinite or NaN
+    NonFinitePosition(VertexId),
+    /// Some vertex couldn't be inserted, which means the predicates failed
+    VertexNotInserted(VertexId),
+}
+
+impl fmt::Display for DelaunayTrisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFinitePosition(v) => write!(f, "vertex {:?} has a non-finite position", v),
+            Self::VertexNotInserted(v) => write!(f, "vertex {:?} could not be inserted", v),
+        }
+    }
+}
+
+impl std::error::Error for DelaunayTrisError {}
+
+/// Why a constrained Delaunay triangulation failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConstrainedTrisError {
+    /// The Delaunay triangulation of the vertices failed
+    Delaunay(DelaunayTrisError),
+    /// Recovering the edges failed
+    Recovery(RecoveryError),
+}
+
+impl fmt::Display for ConstrainedTrisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Delaunay(err) => write!(f, "Delaunay triangulation failed: {}", err),
+            Self::Recovery(err) => write!(f, "edge recovery failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConstrainedTrisError {}
+
+impl From<DelaunayTrisError> for ConstrainedTrisError {
+    fn from(err: DelaunayTrisError) -> Self {
+        Self::Delaunay(err)
+    }
+}
+
+impl From<RecoveryError> for ConstrainedTrisError {
+    fn from(err: RecoveryError) -> Self {
+        Self::Recovery(err)
+    }
+}
+
+pub(crate) fn index_fn<M>(mesh: &M, i: VertexId) -> Vector2<f64>
+where
+    M: HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    mesh.position(i).coords
+}
+
+/// Modified in-circle test to deal with the ghost vertex.
+/// `m` is the point to test the in-circle of; it cannot be the ghost.
+fn in_circle_with_ghosts<M>(mesh: &M, tri: TriId, m: VertexId, ghost: VertexId) -> bool
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    if tri.contains_vertex(ghost) {
+        let edge = tri.opp_edge(ghost);
+        let orient = exact::orient_2d(
+            index_fn(mesh, edge.0[0]),
+            index_fn(mesh, edge.0[1]),
+            index_fn(mesh, m),
+        );
+        if orient != 0.0 {
+            return orient > 0.0;
+        }
+
+        // On the line of the hull edge, the ghost tri's circumcircle is the hull edge,
+        // which is where the circumcircle of the tri on the other side meets the line.
+        // Using that tri's in-circle test keeps the two tests consistent,
+        // instead of making a flat tri with the hull edge.
+        mesh.edge_tri(edge.twin())
+            .map_or(false, |tri| in_circle(mesh, tri, m))
+    } else {
+        in_circle(mesh, tri, m)
+    }
+}
+
+/// Whether vertex `m` is in the circumcircle of a counterclockwise tri.
+/// Ties get broken by perturbing the lifted coordinate of each vertex,
+/// by more for vertices with smaller ids, so all the tests agree on one perturbed input.
+/// Unlike perturbing the positions, which is what `simplicity`'s in-circle test does,
+/// that never makes a flat tri Delaunay, so cocircular vertices don't leave slivers of zero area.
+fn in_circle<M>(mesh: &M, tri: TriId, m: VertexId) -> bool
+where
+    M: HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    let vertices = [tri.0[0], tri.0[1], tri.0[2], m];
+    let positions = vertices.map(|v| index_fn(mesh, v));
+    let det = exact::in_circle(positions[0], positions[1], positions[2], positions[3]);
+    if det != 0.0 {
+        return det > 0.0;
+    }
+
+    // The derivative of the determinant with respect to the lifted coordinate of row r
+    // is (-1)^r times the orientation of the other 3 points.
+    // All of those are 0 only if the tri is flat, and a flat tri has no circumcircle.
+    let mut order = [0, 1, 2, 3];
+    order.sort_by_key(|r| vertices[*r]);
+    order
+        .iter()
+        .find_map(|r| {
+            let others = (0..4)
+                .filter(|j| j != r)
+                .map(|j| positions[j])
+                .collect::<Vec<_>>();
+            let orient = exact::orient_2d(others[0], others[1], others[2]);
+            let cofactor = if r % 2 == 0 { orient } else { -orient };
+            Some(cofactor > 0.0).filter(|_| cofactor != 0.0)
+        })
+        .unwrap_or(false)
+}
+
+/// Iterates over the triangles sharing an edge with some triangle.
+fn adjacent_tris<M>(mesh: &M, tri: TriId) -> impl Iterator<Item = TriId> + '_
+where
+    M: HasTris<MwbF = B1>,
+{
+    tri.edges()
+        .to_vec()
+        .into_iter()
+        .flat_map(move |edge| mesh.edge_tri(edge.twin()))
+}
+
+/// Checks that the vertices of a mesh have finite positions, which the predicates need.
+fn check_finite<M>(mesh: &M) -> Result<(), DelaunayTrisError>
+where
+    M: HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    match mesh
+        .vertex_ids()
+        .find(|v| !mesh.position(**v).iter().all(|x| x.is_finite()))
+    {
+        Some(v) => Err(DelaunayTrisError::NonFinitePosition(*v)),
+        None => Ok(()),
+    }
+}
+
+/// Finds a tri whose circumcircle contains the new vertex.
+/// A vertex at the same position as another is in none of them if the tie is broken against it,
+/// in which case there is none and it's left without tris.
+/// Fails if the mesh has no tris or the predicates fail to find one.
+fn find_tri_to_delete<M>(
+    mesh: &M,
+    new_vertex: VertexId,
+    ghost: VertexId,
+) -> Result<Option<TriId>, DelaunayTrisError>
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    // Look for closest vertex to the new vertex to add
+    let mut vertex = mesh
+        .tri_ids()
+        .next()
+        .ok_or(DelaunayTrisError::VertexNotInserted(new_vertex))?
+        .0[0];
+    while let Some(closer) = mesh
+        .vertex_targets(vertex)
+        .filter(|target| {
+            mesh.distance_squared(*target, new_vertex) < mesh.distance_squared(vertex, new_vertex)
+        })
+        .min_by_key(|target| FloatOrd(mesh.distance_squared(*target, new_vertex)))
+    {
+        vertex = closer;
+    }
+
+    // The new vertex is in the circumcircle of some tri on that vertex.
+    // If not, there's a floating-point error and we search further.
+    let tri = iter::bfs(
+        mesh.vertex_tris(vertex),
+        |tri| adjacent_tris(mesh, *tri),
+        |_| true,
+    )
+    .find(|tri| in_circle_with_ghosts(mesh, *tri, new_vertex, ghost));
+
+    match tri {
+        Some(tri) => Ok(Some(tri)),
+        None if mesh.position(vertex) == mesh.position(new_vertex) => Ok(None),
+        None => Err(DelaunayTrisError::VertexNotInserted(new_vertex)),
+    }
+}
+
+/// The tris whose circumcircles contain the new vertex.
+/// Fails like `find_tri_to_delete`.
+fn tris_to_delete<'a, M>(
+    mesh: &'a M,
+    new_vertex: VertexId,
+    ghost: VertexId,
+) -> Result<impl Iterator<Item = TriId> + 'a, DelaunayTrisError>
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    Ok(iter::bfs(
+        find_tri_to_delete(mesh, new_vertex, ghost)?,
+        move |tri| adjacent_tris(mesh, *tri),
+        move |tri| in_circle_with_ghosts(mesh, *tri, new_vertex, ghost),
+    ))
+}
+
+/// The edges of the convex hull of the vertices of a mesh, going counterclockwise.
+/// Uses Andrew's monotone chain algorithm.
+/// Orientation is exact instead of perturbed like in the rest of this module,
+/// because Simulation of Simplicity can't report collinear vertices,
+/// and this needs to find them to leave out vertices in the middle of hull edges.
+pub(crate) fn convex_hull_edges<M>(mesh: &M) -> Vec<EdgeId>
+where
+    M: HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    if mesh.num_vertices() < 3 {
+        return vec![];
+    }
+
+    let mut v_ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+    v_ids.sort_by_key(|v| {
+        let pos = mesh.position(*v);
+        (FloatOrd(pos.x), FloatOrd(pos.y), *v)
+    });
+
+    // Lower hull, then upper hull, each turning strictly left at every vertex,
+    // so vertices in the middle of hull edges are left out
+    let mut hull: Vec<VertexId> = vec![];
+    let add = |hull: &mut Vec<VertexId>, v: VertexId, min_len: usize| {
+        while hull.len() >= min_len
+            && exact::orient_2d(
+                index_fn(mesh, hull[hull.len() - 2]),
+                index_fn(mesh, hull[hull.len() - 1]),
+                index_fn(mesh, v),
+            ) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(v);
+    };
+    for v in &v_ids {
+        add(&mut hull, *v, 2);
+    }
+    let min_len = hull.len() + 1;
+    for v in v_ids.iter().rev().skip(1) {
+        add(&mut hull, *v, min_len);
+    }
+    hull.pop();
+
+    // Collinear vertices leave just the 2 ends, which don't enclose anything
+    if hull.len() < 3 {
+        return vec![];
+    }
+
+    (0..hull.len())
+        .map(|i| EdgeId([hull[i], hull[(i + 1) % hull.len()]]))
+        .collect()
+}
+
+/// Finds the indexes of 3 vertices that aren't collinear, looking from the end of the list.
+/// There are none if there are fewer than 3 vertices or they're all collinear.
+fn find_first_tri<M>(mesh: &M, v_ids: &[VertexId]) -> Option<[usize; 3]>
+where
+    M: HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    let pos = |i: usize| index_fn(mesh, v_ids[i]);
+    let i0 = v_ids.len().checked_sub(1)?;
+    let i1 = (0..i0).rev().find(|i| pos(*i) != pos(i0))?;
+    let i2 = (0..i0)
+        .rev()
+        .find(|i| exact::orient_2d(pos(i0), pos(i1), pos(*i)) != 0.0)?;
+    Some([i0, i1, i2])
+}
+
+/// Implementation of the Bowyer-Watson algorithm,
+/// with ghost triangles 👻 (https://people.eecs.berkeley.edu/~jrs/meshpapers/delnotes.pdf, section 3.4)
+/// to avoid the concave triangulation problem that happens with a super triangle.
+/// If there are fewer than 3 vertices or they're all collinear, there are no tris.
+/// Vertices at the same position as another are left without tris.
+/// Fails if some position isn't finite or the predicates fail.
+pub(crate) fn delaunay_tris<M>(mut mesh: M) -> Result<M, DelaunayTrisError>
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    check_finite(&mesh)?;
+    let mut v_ids = mesh.vertex_ids().copied().collect::<Vec<_>>();
+
+    // It takes 3 vertices that aren't collinear to make a tri
+    let [v0, mut v1, mut v2] = match find_first_tri(&mesh, &v_ids) {
+        Some(indexes) => indexes.map(|i| v_ids[i]),
+        None => return Ok(mesh),
+    };
+    v_ids.retain(|v| ![v0, v1, v2].contains(v));
+
+    // Ghost vertex
+    let ghost = mesh.add_with_position(Point1::new(f64::INFINITY).xx());
+
+    // First tri
+    let [p0, p1, p2] = [v0, v1, v2].map(|v| index_fn(&mesh, v));
+    if exact::orient_2d(p0, p1, p2) < 0.0 {
+        std::mem::swap(&mut v1, &mut v2);
+    }
+    let first = TriId::from_valid([v0, v1, v2]);
+    mesh.add_tri([v0, v1, v2], mesh.default_tri());
+
+    // Ghost tris
+    for edge in &first.edges() {
+        mesh.add_tri([edge.0[1], edge.0[0], ghost], mesh.default_tri());
+    }
+
+    while let Some(vertex) = v_ids.pop() {
+        let to_delete = match tris_to_delete(&mesh, vertex, ghost) {
+            Ok(tris) => tris.collect::<Vec<_>>(),
+            Err(err) => {
+                mesh.remove_vertex(ghost);
+                return Err(err);
+            }
+        };
+
+        // Get boundary
+        let edges = to_delete
+            .iter()
+            .flat_map(|tri| tri.edges().to_vec())
+            .collect::<FnvHashSet<_>>();
+        let boundary = edges
+            .iter()
+            .copied()
+            .filter(|edge| !edges.contains(&edge.twin()))
+            .collect::<Vec<_>>();
+
+        // Retriangulate region
+        mesh.remove_tris(to_delete);
+        mesh.extend_tris(
+            boundary
+                .into_iter()
+                .map(|edge| {
+                    (
+                        TriId::from_valid([edge.0[0], edge.0[1], vertex]),
+                        mesh.default_tri(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    mesh.remove_vertex(ghost);
+    Ok(mesh)
+}
+
+/// Whether a vertex lies exactly on the segment between 2 other vertices,
+/// not counting the endpoints.
+fn segment_contains_vertex<M>(mesh: &M, v0: VertexId, v1: VertexId, vertex: VertexId) -> bool
+where
+    M: HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    let [p0, p1, p] = [v0, v1, vertex].map(|v| index_fn(mesh, v));
+    // Once the vertex is on the line, it's on the segment if it's between the ends in each coordinate
+    exact::orient_2d(p0, p1, p) == 0.0
+        && p != p0
+        && p != p1
+        && (0..2).all(|i| p0[i].min(p1[i]) <= p[i] && p[i] <= p0[i].max(p1[i]))
+}
+
+/// What the segment between 2 vertices runs into on its way from one to the other
+enum Crossing {
+    /// The edges crossing the segment, in order, each oriented from the right side of the segment to the left side
+    Edges(Vec<EdgeId>),
+    /// The first vertex found lying exactly on the segment
+    Vertex(VertexId),
+}
+
+/// Gets the edges crossing the segment between 2 vertices, in order from `v0` to `v1`,
+/// or the first vertex found lying exactly on the segment, if any.
+/// Fails if the walk along the segment leaves the triangulation, which means the predicates failed.
+fn edges_crossing_segment<M>(
+    mesh: &M,
+    v0: VertexId,
+    v1: VertexId,
+) -> Result<Crossing, RecoveryError>
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    if let Some(vertex) = mesh
+        .vertex_targets(v0)
+        .find(|vertex| segment_contains_vertex(mesh, v0, v1, *vertex))
+    {
+        return Ok(Crossing::Vertex(vertex));
+    }
+
+    let not_traced = RecoveryError::EdgeNotTraced(EdgeId([v0, v1]));
+    let mut edge = mesh
+        .vertex_tris(v0)
+        .map(|tri| tri.opp_edge(v0))
+        .find(|edge| {
+            sim::orient_2d(mesh, index_fn, v0, edge.0[0], v1)
+                && sim::orient_2d(mesh, index_fn, v0, v1, edge.0[1])
+        })
+        .ok_or(not_traced)?;
+    let mut crossing = vec![];
+
+    loop {
+        crossing.push(edge);
+        let [right, left] = edge.0;
+        let opp = mesh
+            .edge_vertex_opp(EdgeId([left, right]))
+            .ok_or(not_traced)?;
+
+        if opp == v1 {
+            return Ok(Crossing::Edges(crossing));
+        } else if segment_contains_vertex(mesh, v0, v1, opp) {
+            return Ok(Crossing::Vertex(opp));
+        }
+
+        edge = if sim::orient_2d(mesh, index_fn, v0, v1, opp) {
+            EdgeId([right, opp])
+        } else {
+            EdgeId([opp, left])
+        };
+    }
+}
+
+/// Whether splitting an edge at a point with `flip12` keeps the tris on both sides counterclockwise.
+/// `opps` are the vertices opposite the edge and its twin.
+fn can_split_edge_at<M>(mesh: &M, edge: EdgeId, opps: [VertexId; 2], point: Point2<f64>) -> bool
+where
+    M: HasPosition2D,
+    M::V: Position<Dim = U2>,
+{
+    let [a, b] = edge.0.map(|v| index_fn(mesh, v));
+    let [vp, vn] = opps.map(|v| index_fn(mesh, v));
+    let p = point.coords;
+    exact::orient_2d(a, p, vp) > 0.0
+        && exact::orient_2d(p, b, vp) > 0.0
+        && exact::orient_2d(b, p, vn) > 0.0
+        && exact::orient_2d(p, a, vn) > 0.0
+}
+
+/// Recover the edges of `edge_mesh` in the triangulation by flipping away the edges in the way.
+/// Edges get split at vertices lying exactly on them and at intersections with each other.
+/// Returns a map from split edges to the vertices that split them.
+///
+/// Fails if 2 edges cross where rounding can't put a vertex inside the triangles around the crossing,
+/// or if the predicates fail.
+pub(crate) fn recover_edges<M, EM>(
+    mesh: &mut M,
+    edge_mesh: &mut EM,
+) -> Result<FnvHashMap<EdgeId, VertexId>, RecoveryError>
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2> + Clone,
+    M::E: Clone,
+    EM: HasVertices<V = M::V> + HasEdges,
+    EM::E: Clone,
+{
+    let mut to_recover = edge_mesh.edge_ids().copied().collect::<Vec<_>>();
+    let mut split_map = FnvHashMap::default();
+
+    while let Some(edge) = to_recover.pop() {
+        if mesh.contains_edge(edge)
+            || mesh.contains_edge(edge.twin())
+            || !edge_mesh.contains_edge(edge)
+        {
+            continue;
+        }
+        let [v0, v1] = edge.0;
+
+        let split = match edges_crossing_segment(mesh, v0, v1)? {
+            Crossing::Vertex(vertex) => Some(vertex),
+
+            Crossing::Edges(crossing) => {
+                if let Some(other) = crossing
+                    .iter()
+                    .copied()
+                    .find(|e| edge_mesh.contains_edge(*e) || edge_mesh.contains_edge(e.twin()))
+                {
+                    // Constrained edges cross. Split both at the intersection.
+                    // It gets rounded, so make sure it's still inside the tris it splits,
+                    // trying it as a point on either edge.
+                    let opps = [
+                        mesh.edge_vertex_opp(other)
+                            .ok_or(RecoveryError::EdgeNotTraced(edge))?,
+                        mesh.edge_vertex_opp(other.twin())
+                            .ok_or(RecoveryError::EdgeNotTraced(edge))?,
+                    ];
+                    let [p0, p1] = [mesh.position(v0), mesh.position(v1)];
+                    let [q0, q1] = [mesh.position(other.0[0]), mesh.position(other.0[1])];
+                    let [d, e] = [p1 - p0, q1 - q0];
+                    let position = [
+                        p0 + d * ((q0 - p0).perp(&e) / d.perp(&e)),
+                        q0 + e * ((q0 - p0).perp(&d) / d.perp(&e)),
+                    ]
+                    .iter()
+                    .copied()
+                    .find(|p| can_split_edge_at(mesh, other, opps, *p))
+                    .ok_or(RecoveryError::CrossingNotSplit(edge, other))?;
+
+                    let value = mesh.default_vertex().with_position(position);
+                    let vertex = mesh.add_vertex(value.clone());
+                    mesh.flip12(other, vertex);
+
+                    edge_mesh.add_vertex_with_id(vertex, value);
+                    let other = if edge_mesh.contains_edge(other) {
+                        other
+                    } else {
+                        other.twin()
+                    };
+                    edge_mesh.flip12(other, vertex);
+                    split_map.insert(other, vertex);
+                    to_recover.push(EdgeId([other.0[0], vertex]));
+                    to_recover.push(EdgeId([vertex, other.0[1]]));
+
+                    Some(vertex)
+                } else {
+                    let mut crossing = crossing.into_iter().collect::<VecDeque<_>>();
+                    // Some crossing edge can always be flipped, so going through all of them
+                    // without a flip means the predicates failed
+                    let mut num_unflipped = 0;
+
+                    while let Some(cross) = crossing.pop_front() {
+                        if num_unflipped > crossing.len() {
+                            return Err(RecoveryError::EdgeNotFlipped(edge));
+                        }
+                        let [right, left] = cross.0;
+                        let vp = mesh
+                            .edge_vertex_opp(EdgeId([right, left]))
+                            .ok_or(RecoveryError::EdgeNotTraced(edge))?;
+                        let vn = mesh
+                            .edge_vertex_opp(EdgeId([left, right]))
+                            .ok_or(RecoveryError::EdgeNotTraced(edge))?;
+
+                        // Flippable only if the 2 triangles form a convex quadrilateral
+                        if sim::orient_2d(mesh, index_fn, vp, vn, right)
+                            != sim::orient_2d(mesh, index_fn, vp, vn, left)
+                        {
+                            mesh.flip22(right, left, vp, vn);
+                            num_unflipped = 0;
+
+                            if ![v0, v1].contains(&vp)
+                                && ![v0, v1].contains(&vn)
+                                && sim::orient_2d(mesh, index_fn, v0, v1, vp)
+                                    != sim::orient_2d(mesh, index_fn, v0, v1, vn)
+                            {
+                                crossing.push_back(if sim::orient_2d(mesh, index_fn, v0, v1, vn) {
+                                    EdgeId([vp, vn])
+                                } else {
+                                    EdgeId([vn, vp])
+                                });
+                            }
+                        } else {
+                            crossing.push_back(cross);
+                            num_unflipped += 1;
+                        }
+                    }
+
+                    None
+                }
+            }
+        };
+
+        if let Some(vertex) = split {
+            edge_mesh.flip12(edge, vertex);
+            split_map.insert(edge, vertex);
+            to_recover.push(EdgeId([v0, vertex]));
+            to_recover.push(EdgeId([vertex, v1]));
+        }
+    }
+
+    Ok(split_map)
+}
+
+/// Flips edges not in `edge_mesh` until the triangulation is constrained Delaunay.
+fn make_delaunay<M, EM>(mesh: &mut M, edge_mesh: &EM)
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2>,
+    EM: HasEdges,
+{
+    let mut to_check = mesh.edge_ids().copied().collect::<Vec<_>>();
+
+    while let Some(edge) = to_check.pop() {
+        if edge_mesh.contains_edge(edge) || edge_mesh.contains_edge(edge.twin()) {
+            continue;
+        }
+
+        if let (Some(vp), Some(vn)) = (
+            mesh.edge_vertex_opp(edge),
+            mesh.edge_vertex_opp(edge.twin()),
+        ) {
+            let [v1, v2] = edge.0;
+            if in_circle(mesh, TriId::from_valid([v1, v2, vp]), vn) {
+                mesh.flip22(v1, v2, vp, vn);
+                to_check.extend(vec![
+                    EdgeId([v1, vn]),
+                    EdgeId([vn, v2]),
+                    EdgeId([v2, vp]),
+                    EdgeId([vp, v1]),
+                ]);
+            }
+        }
+    }
+}
+
+/// Remove all triangles that are outside the boundary marked by `edge_mesh`.
+/// The region inside must be on the left of the edges of `edge_mesh`.
+pub(crate) fn remove_external_tris<M, EM>(mesh: &mut M, edge_mesh: &EM)
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2>,
+    EM: HasEdges,
+{
+    // External tris along the boundary
+    let external_tris = edge_mesh
+        .edge_ids()
+        .flat_map(|edge| mesh.edge_tri(edge.twin()));
+
+    // Full BFS over the triangles to determine which ones are external
+    let external_tris = iter::bfs(
+        external_tris,
+        |tri| {
+            tri.edges()
+                .to_vec()
+                .into_iter()
+                .filter(|edge| !edge_mesh.contains_edge(edge.twin()))
+                .flat_map(|edge| mesh.edge_tri(edge.twin()))
+        },
+        |_| true,
+    )
+    .collect::<Vec<_>>();
+
+    mesh.remove_tris(external_tris);
+}
+
+/// Triangulates the inside of the closed boundary marked by the edges of `edge_mesh`,
+/// keeping the triangulation Delaunay wherever the edges allow.
+/// Returns the triangle mesh and a map from split edges to the vertices that split them.
+pub(crate) fn constrained_delaunay_tris<M, EM>(
+    mesh: M,
+    mut edge_mesh: EM,
+) -> Result<WithSplitEdges<M>, ConstrainedTrisError>
+where
+    M: HasTris<MwbF = B1> + HasPosition2D,
+    M::V: Position<Dim = U2> + Clone,
+    M::E: Clone,
+    EM: HasVertices<V = M::V> + HasEdges,
+    EM::E: Clone,
+{
+    let mut mesh = delaunay_tris(mesh)?;
+    if mesh.num_tris() == 0 {
+        return Ok((mesh, FnvHashMap::default()));
+    }
+
+    let split_map = recover_edges(&mut mesh, &mut edge_mesh)?;
+    make_delaunay(&mut mesh, &edge_mesh);
+    remove_external_tris(&mut mesh, &edge_mesh);
+
+    Ok((mesh, split_map))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryInto;
+    use std::fmt::Debug;
+    use std::hash::Hash;
+
+    use fnv::FnvHashSet;
+    use nalgebra::Point2;
+
+    use super::*;
+    use crate::vertex::{HasPosition, HasVertices};
+    use crate::{mesh2::MwbComboMesh2, ComboMesh0, ComboMesh1};
+
+    #[track_caller]
+    fn assert_tris_m<
+        V,
+        E,
+        F: Clone + Debug + Eq + Hash,
+        FI: TryInto<TriId>,
+        I: IntoIterator<Item = (FI, F)>,
+    >(
+        mesh: &MwbComboMesh2<V, E, F>,
+        tris: I,
+    ) {
+        let result = mesh
+            .tris()
+            .map(|(id, f)| (*id, f.clone()))
+            .collect::<FnvHashSet<_>>();
+        let expect = tris
+            .into_iter()
+            .map(|(vertices, f)| (vertices.try_into().ok().unwrap(), f))
+            .collect::<FnvHashSet<_>>();
+
+        assert_eq!(result, expect);
+        assert_eq!(mesh.num_tris(), expect.len());
+    }
+
+    #[test]
+    fn test_in_circle_ghost() {
+        let mut mesh =
+            MwbComboMesh2::<Point2<f64>, (), ()>::with_defaults(Point2::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+            Point2::new(0.6, 0.6),
+        ]);
+        let tri = TriId::from_valid([ids[0], ids[1], ids[2]]);
+        mesh.add_tri(tri, ());
+
+        assert!(!in_circle_with_ghosts(&mesh, tri, ids[3], ids[0]));
+        assert!(in_circle_with_ghosts(&mesh, tri, ids[3], ids[1]));
+        assert!(in_circle_with_ghosts(&mesh, tri, ids[3], ids[2]));
+    }
+
+    #[test]
+    fn test_in_circle_no_ghost() {
+        let mut mesh =
+            MwbComboMesh2::<Point2<f64>, (), ()>::with_defaults(Point2::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+            Point2::new(0.6, 0.6),
+        ]);
+        let tri = TriId::from_valid([ids[0], ids[1], ids[2]]);
+        mesh.add_tri(tri, ());
+
+        assert!(in_circle_with_ghosts(&mesh, tri, ids[3], VertexId(4)));
+    }
+
+    #[test]
+    fn test_in_circle_tie() {
+        let mut mesh =
+            MwbComboMesh2::<Point2<f64>, (), ()>::with_defaults(Point2::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(0.0, 1.0),
+        ]);
+
+        // The vertex with the smallest id gets lifted the most,
+        // so the square gets split along the diagonal that avoids it, and the tests agree on that
+        let tri = |[i0, i1, i2]: [usize; 3]| TriId::from_valid([ids[i0], ids[i1], ids[i2]]);
+        assert!(!in_circle(&mesh, tri([1, 2, 3]), ids[0]));
+        assert!(!in_circle(&mesh, tri([3, 0, 1]), ids[2]));
+        assert!(in_circle(&mesh, tri([0, 1, 2]), ids[3]));
+        assert!(in_circle(&mesh, tri([2, 3, 0]), ids[1]));
+    }
+
+    #[test]
+    fn test_tris_to_delete() {
+        let mut mesh =
+            MwbComboMesh2::<Point2<f64>, (), ()>::with_defaults(Point2::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+            Point2::new(1.2, 1.2),
+            Point1::new(f64::INFINITY).xx(),
+            Point2::new(0.4, 0.4),
+            Point2::new(0.5, -0.1),
+            Point2::new(-1.0, -0.2),
+        ]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[1], ids[2]], ()),
+            ([ids[3], ids[2], ids[1]], ()),
+            ([ids[4], ids[1], ids[0]], ()),
+            ([ids[4], ids[0], ids[2]], ()),
+            ([ids[4], ids[2], ids[3]], ()),
+            ([ids[4], ids[3], ids[1]], ()),
+        ]);
+
+        // In convex hull
+        let result = tris_to_delete(&mesh, ids[5], ids[4])
+            .unwrap()
+            .collect::<FnvHashSet<_>>();
+        assert_eq!(
+            result,
+            vec![
+                TriId::from_valid([ids[0], ids[1], ids[2]]),
+                TriId::from_valid([ids[3], ids[2], ids[1]]),
+            ]
+            .into_iter()
+            .collect::<FnvHashSet<_>>()
+        );
+
+        // Remove both solid triangles and ghost triangles
+        let result = tris_to_delete(&mesh, ids[6], ids[4])
+            .unwrap()
+            .collect::<FnvHashSet<_>>();
+        assert_eq!(
+            result,
+            vec![
+                TriId::from_valid([ids[0], ids[1], ids[2]]),
+                TriId::from_valid([ids[4], ids[1], ids[0]]),
+            ]
+            .into_iter()
+            .collect::<FnvHashSet<_>>()
+        );
+
+        // Remove only ghost triangles
+        let result = tris_to_delete(&mesh, ids[7], ids[4])
+            .unwrap()
+            .collect::<FnvHashSet<_>>();
+        assert_eq!(
+            result,
+            vec![
+                TriId::from_valid([ids[4], ids[1], ids[0]]),
+                TriId::from_valid([ids[4], ids[0], ids[2]]),
+            ]
+            .into_iter()
+            .collect::<FnvHashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_delaunay_tris_empty() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        mesh.extend_vertices(vec![Point2::new(0.0, 0.0), Point2::new(1.0, 0.0)]);
+
+        let result = mesh.clone().delaunay_tris(|| (), || ());
+        assert_eq!(
+            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
+            result.vertex_ids().collect::<FnvHashSet<_>>(),
+        );
+        assert_tris_m(&result, vec![] as Vec<(TriId, ())>);
+    }
+
+    #[test]
+    fn test_delaunay_tris_single() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+        ]);
+
+        let result = mesh.clone().delaunay_tris(|| (), || ());
+        assert_eq!(
+            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
+            result.vertex_ids().collect::<FnvHashSet<_>>(),
+        );
+        assert_tris_m(
+            &result,
+            vec![(TriId::from_valid([ids[0], ids[1], ids[2]]), ())],
+        );
+    }
+
+    #[test]
+    fn test_delaunay_tris_multiple() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 1.0),
+            Point2::new(0.0, 1.0),
+            Point2::new(1.0, 0.4),
+        ]);
+
+        let result = mesh.clone().delaunay_tris(|| (), || ());
+        assert_eq!(
+            mesh.vertex_ids().collect::<FnvHashSet<_>>(),
+            result.vertex_ids().collect::<FnvHashSet<_>>(),
+        );
+        assert_tris_m(
+            &result,
+            vec![
+                (TriId::from_valid([ids[0], ids[1], ids[4]]), ()),
+                (TriId::from_valid([ids[1], ids[2], ids[4]]), ()),
+                (TriId::from_valid([ids[2], ids[3], ids[4]]), ()),
+                (TriId::from_valid([ids[3], ids[0], ids[4]]), ()),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 2.0),
+            Point2::new(0.0, 2.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(0.5, 1.5),
+        ]);
+        let hull = mesh.convex_hull(|| ());
+
+        // The vertex in the middle of the bottom edge is left out
+        assert_eq!(
+            hull.vertex_ids().copied().collect::<FnvHashSet<_>>(),
+            vec![ids[0], ids[2], ids[3], ids[4]]
+                .into_iter()
+                .collect::<FnvHashSet<_>>()
+        );
+        assert_eq!(
+            hull.edge_ids().copied().collect::<FnvHashSet<_>>(),
+            vec![
+                [ids[0], ids[2]],
+                [ids[2], ids[3]],
+                [ids[3], ids[4]],
+                [ids[4], ids[0]]
+            ]
+            .into_iter()
+            .map(EdgeId)
+            .collect::<FnvHashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_convex_hull_collinear() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        mesh.extend_vertices((0..5).map(|i| Point2::new(i as f64, 2.0 * i as f64)));
+        let hull = mesh.convex_hull(|| ());
+        assert_eq!(hull.num_vertices(), 0);
+        assert_eq!(hull.num_edges(), 0);
+    }
+
+    #[test]
+    fn test_convex_hull_scattered() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        mesh.extend_vertices((0..100).map(|i| {
+            let i = i as f64;
+            Point2::new((i * 0.7).sin() * i, (i * 1.3).cos() * 50.0)
+        }));
+        let hull = mesh.clone().convex_hull(|| ());
+
+        // Every vertex is on the left of every hull edge, and the hull is one loop
+        for edge in hull.edge_ids() {
+            for v in mesh.vertex_ids().filter(|v| !edge.contains_vertex(**v)) {
+                assert!(sim::orient_2d(&mesh, index_fn, edge.0[0], edge.0[1], *v));
+            }
+        }
+        assert_eq!(hull.num_edges(), hull.num_vertices());
+        let start = *hull.vertex_ids().next().unwrap();
+        let mut vertex = start;
+        for _ in 0..hull.num_edges() {
+            vertex = hull.vertex_targets(vertex).next().unwrap();
+        }
+        assert_eq!(vertex, start);
+    }
+
+    #[test]
+    fn test_locate_point() {
+        use crate::tri::{HasPositionAndTris, TriLocation};
+
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 1.0),
+            Point2::new(0.0, 1.0),
+            Point2::new(1.0, 0.4),
+        ]);
+        let result = mesh.delaunay_tris(|| (), || ());
+        let near = TriId::from_valid([ids[0], ids[1], ids[4]]);
+        let far = TriId::from_valid([ids[2], ids[3], ids[4]]);
+
+        for hint in [None, Some(far)] {
+            assert_eq!(
+                result.locate_point_in_tris(Point2::new(1.0, 0.1), hint),
+                Some(TriLocation::Tri(near))
+            );
+            assert_eq!(
+                result.locate_point_in_tris(Point2::new(2.0, 0.0), hint),
+                Some(TriLocation::Vertex(ids[1]))
+            );
+            assert_eq!(
+                result.locate_point_in_tris(Point2::new(3.0, 3.0), hint),
+                Some(TriLocation::Outside)
+            );
+
+            match result.locate_point_in_tris(Point2::new(1.0, 0.0), hint) {
+                Some(TriLocation::Edge(edge)) => {
+                    assert_eq!(edge.undirected(), EdgeId([ids[0], ids[1]]).undirected())
+                }
+                location => panic!("Expected a hull edge, got {:?}", location),
+            }
+            match result.locate_point_in_tris(Point2::new(0.5, 0.2), hint) {
+                Some(TriLocation::Edge(edge)) => {
+                    assert_eq!(edge.undirected(), EdgeId([ids[0], ids[4]]).undirected())
+                }
+                location => panic!("Expected an inner edge, got {:?}", location),
+            }
+        }
+    }
+
+    #[test]
+    fn test_delaunay_tris_empty_circumcircles() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        mesh.extend_vertices((0..100).map(|i| {
+            Point2::new(
+                (i as f64 * 0.7).sin() * i as f64,
+                (i as f64 * 1.3).cos() * 50.0,
+            )
+        }));
+
+        let result = mesh.delaunay_tris(|| (), || ());
+        for tri in result.tri_ids() {
+            for vertex in result.vertex_ids() {
+                assert!(
+                    tri.contains_vertex(*vertex) || !in_circle(&result, *tri, *vertex),
+                    "Vertex {:?} is in the circumcircle of {:?}",
+                    vertex,
+                    tri
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_delaunay_tris_same_position() {
+        // One of the vertices at the same position is left without tris
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(0.0, 1.0),
+        ]);
+        let result = mesh.delaunay_tris(|| (), || ());
+        assert_eq!(result.num_tris(), 1);
+    }
+
+    #[test]
+    fn test_delaunay_tris_collinear() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        mesh.extend_vertices((0..5).map(|i| Point2::new(i as f64, 2.0 * i as f64)));
+        let result = mesh.delaunay_tris(|| (), || ());
+        assert_eq!(result.num_vertices(), 5);
+        assert_eq!(result.num_tris(), 0);
+    }
+
+    #[test]
+    fn test_segment_contains_vertex() {
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(0.5, 1.5),
+            Point2::new(0.25, 0.75),
+            Point2::new(1.0, 3.0),
+            Point2::new(0.1, 0.3),
+            Point2::new(0.03, 0.09),
+        ]);
+
+        assert!(segment_contains_vertex(&mesh, ids[0], ids[1], ids[2]));
+        assert!(!segment_contains_vertex(&mesh, ids[0], ids[1], ids[0]));
+        assert!(!segment_contains_vertex(&mesh, ids[0], ids[1], ids[3]));
+        // Rounding makes this look collinear, but it isn't
+        assert!(!segment_contains_vertex(&mesh, ids[0], ids[4], ids[5]));
+    }
+
+    #[test]
+    fn test_try_delaunay_tris_non_finite() {
+        let positions = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(f64::NAN, 0.5),
+            Point2::new(0.0, 1.0),
+        ];
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        let ids = mesh.extend_vertices(positions.clone());
+        assert_eq!(
+            mesh.try_delaunay_tris(|| (), || ()).err(),
+            Some(DelaunayTrisError::NonFinitePosition(ids[2]))
+        );
+
+        let mut edges = ComboMesh1::<Point2<f64>, ()>::with_defaults(Point2::origin, || ());
+        edges.extend_vertices(positions);
+        assert!(matches!(
+            edges.try_constrained_delaunay_tris(|| ()),
+            Err(ConstrainedTrisError::Delaunay(
+                DelaunayTrisError::NonFinitePosition(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_delaunay_tris_grid() {
+        // Collinear hull vertices and cocircular squares everywhere
+        let mut mesh = ComboMesh0::<Point2<f64>>::with_defaults(Point2::origin);
+        mesh.extend_vertices((0..16).map(|i| Point2::new((i % 4) as f64, (i / 4) as f64)));
+
+        let result = mesh.delaunay_tris(|| (), || ());
+        assert_eq!(result.num_tris(), 18);
+        for tri in result.tri_ids() {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| index_fn(&result, tri.0[i]));
+            assert!(
+                exact::orient_2d(p0, p1, p2) > 0.0,
+                "{:?} is not counterclockwise",
+                tri
+            );
+        }
+    }
+
+    #[test]
+    fn test_constrained_delaunay_tris_hole() {
+        let mut mesh = ComboMesh1::<Point2<f64>, ()>::with_defaults(Point2::origin, || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(4.0, 0.0),
+            Point2::new(4.0, 4.0),
+            Point2::new(0.0, 4.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(1.0, 3.0),
+            Point2::new(3.0, 3.0),
+            Point2::new(3.0, 1.0),
+            Point2::new(2.0, 0.9),
+            Point2::new(2.0, 1.1),
+            Point2::new(2.0, 3.0),
+        ]);
+        mesh.extend_edges(vec![
+            // Outer boundary, counterclockwise
+            ([ids[0], ids[1]], ()),
+            ([ids[1], ids[2]], ()),
+            ([ids[2], ids[3]], ()),
+            ([ids[3], ids[0]], ()),
+            // Hole, clockwise
+            ([ids[4], ids[5]], ()),
+            ([ids[5], ids[6]], ()),
+            ([ids[6], ids[7]], ()),
+            ([ids[7], ids[4]], ()),
+        ]);
+
+        let (result, split_map) = mesh.clone().constrained_delaunay_tris(|| ());
+
+        assert_eq!(
+            split_map,
+            vec![(EdgeId([ids[5], ids[6]]), ids[10])]
+                .into_iter()
+                .collect::<FnvHashMap<_, _>>()
+        );
+
+        let mut constraints = mesh.edge_ids().copied().collect::<FnvHashSet<_>>();
+        constraints.remove(&EdgeId([ids[5], ids[6]]));
+        constraints.insert(EdgeId([ids[5], ids[10]]));
+        constraints.insert(EdgeId([ids[10], ids[6]]));
+        for edge in &constraints {
+            assert!(
+                result.contains_edge(*edge),
+                "Edge {:?} was not recovered",
+                edge
+            );
+        }
+
+        // Constrained Delaunay
+        for edge in result.edge_ids() {
+            if let (Some(vp), Some(vn)) = (
+                result.edge_vertex_opp(*edge),
+                result.edge_vertex_opp(edge.twin()),
+            ) {
+                assert!(
+                    constraints.contains(edge)
+                        || constraints.contains(&edge.twin())
+                        || !in_circle(&result, TriId::from_valid([edge.0[0], edge.0[1], vp]), vn),
+                    "Edge {:?} is not locally Delaunay",
+                    edge
+                );
+            }
+        }
+
+        let area = result
+            .tri_ids()
+            .map(|tri| {
+                let [v0, v1, v2] = tri.vertices();
+                let p0 = result.position(v0);
+                (result.position(v1) - p0).perp(&(result.position(v2) - p0)) / 2.0
+            })
+            .sum::<f64>();
+        assert!((area - 12.0).abs() < 1e-9, "Area is {}", area);
+    }
+
+    #[test]
+    fn test_recover_edges_crossing() {
+        let mut mesh = ComboMesh1::<Point2<f64>, ()>::with_defaults(Point2::origin, || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 2.0),
+            Point2::new(0.0, 2.0),
+        ]);
+        mesh.extend_edges(vec![([ids[0], ids[2]], ()), ([ids[1], ids[3]], ())]);
+
+        let mut result = mesh.clone().delaunay_tris(|| (), || ());
+        let split_map = recover_edges(&mut result, &mut mesh).unwrap();
+
+        assert_eq!(split_map.len(), 2);
+        let vertex = split_map[&EdgeId([ids[0], ids[2]])];
+        assert_eq!(split_map[&EdgeId([ids[1], ids[3]])], vertex);
+        assert_eq!(result.position(vertex), Point2::new(1.0, 1.0));
+        for id in &ids {
+            assert!(result.contains_edge([*id, vertex]) || result.contains_edge([vertex, *id]));
+        }
+        assert_eq!(result.num_tris(), 4);
+    }
+
+    #[test]
+    fn test_recover_edges_crossing_rounded() {
+        // The crossing can't be represented exactly
+        let mut mesh = ComboMesh1::<Point2<f64>, ()>::with_defaults(Point2::origin, || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(3.0, 1.0),
+            Point2::new(0.1, 0.7),
+            Point2::new(2.9, 0.3),
+            Point2::new(1.3, -1.0),
+            Point2::new(1.7, 2.0),
+        ]);
+        mesh.extend_edges(vec![([ids[0], ids[1]], ()), ([ids[2], ids[3]], ())]);
+
+        let mut result = mesh.clone().delaunay_tris(|| (), || ());
+        let split_map = recover_edges(&mut result, &mut mesh).unwrap();
+
+        assert_eq!(split_map.len(), 2);
+        for tri in result.tri_ids() {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| index_fn(&result, tri.0[i]));
+            assert!(
+                exact::orient_2d(p0, p1, p2) > 0.0,
+                "{:?} is not counterclockwise",
+                tri
+            );
+        }
+        for edge in mesh.edge_ids() {
+            assert!(
+                result.contains_edge(*edge) || result.contains_edge(edge.twin()),
+                "{:?} was not recovered",
+                edge
+            );
+        }
+    }
+
+    #[test]
+    fn test_can_split_edge_at() {
+        let mut mesh =
+            MwbComboMesh2::<Point2<f64>, (), ()>::with_defaults(Point2::origin, || (), || ());
+        let ids = mesh.extend_vertices(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(1.0, -1.0),
+        ]);
+        let edge = EdgeId([ids[0], ids[1]]);
+        let opps = [ids[2], ids[3]];
+
+        assert!(can_split_edge_at(&mesh, edge, opps, Point2::new(1.0, 0.0)));
+        assert!(can_split_edge_at(&mesh, edge, opps, Point2::new(0.5, 1e-3)));
+        assert!(!can_split_edge_at(&mesh, edge, opps, Point2::new(1.0, 1.0)));
+        assert!(!can_split_edge_at(&mesh, edge, opps, Point2::new(2.5, 0.0)));
+    }
+}
@@ -0,0 +1,549 @@
+//! Consistency checks for the internal structure of meshes.
+//! These are meant for test suites and debugging; a mesh built through the public API should always pass.
+
+use fnv::FnvHashMap;
+use nalgebra::dimension::U3;
+use std::hash::Hash;
+use typenum::Bit;
+
+use crate::edge::{Edge, EdgeId, HasEdges, Link};
+use crate::error::SimplexId;
+use crate::exact;
+use crate::private::Key;
+use crate::tet::{HasTets, Tet, TetId};
+use crate::tetrahedralize::index_fn;
+use crate::tri::{HasTris, Tri, TriId};
+use crate::vertex::{HasPosition3D, Position, Vertex, VertexId};
+
+/// Something wrong with the structure of a mesh
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Violation {
+    /// `simplex` has a face that isn't in the mesh.
+    MissingFace { simplex: SimplexId, face: SimplexId },
+    /// `around` doesn't point to one of the simplices attached to it,
+    /// or points to one even though none are attached.
+    BrokenHead { around: SimplexId },
+    /// The link of `simplex` around `around` points to a simplex that isn't attached there,
+    /// or to one that doesn't link back to it.
+    BrokenLink {
+        simplex: SimplexId,
+        around: SimplexId,
+    },
+    /// The links around `around` form more than one ring, so some attached simplices are unreachable.
+    SplitRing { around: SimplexId },
+    /// In a mwb mesh, `simplex` is attached to the oriented simplex `around`, which already has a simplex.
+    NonManifold {
+        simplex: SimplexId,
+        around: SimplexId,
+    },
+    /// `simplex` isn't stored in canonical form, so it isn't the twin of its own twin
+    /// and lookups through twins miss it.
+    BrokenTwin(SimplexId),
+    /// `simplex` and `neighbor` are the only two simplices on a face,
+    /// but they wind the same way around it instead of opposite ways.
+    InconsistentOrientation {
+        simplex: SimplexId,
+        neighbor: SimplexId,
+    },
+    /// A tetrahedron with a negative orientation.
+    InvertedTet(TetId),
+    /// A tetrahedron whose vertices lie on a plane, so it has no volume.
+    FlatTet(TetId),
+}
+
+/// The violations found by validating a mesh
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Everything wrong with the mesh, in the order the checks found it.
+    /// Empty for a valid mesh.
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Whether no violations were found
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Checks a ring of simplices attached to the simplex `around`, where each simplex is given by its opposite vertex.
+/// `head` is the opposite vertex stored in `around`, which is `empty` if nothing is attached.
+/// `link` gets the link of an attached simplex, and is `None` for mwb meshes, which don't store links.
+fn check_ring(
+    report: &mut ValidationReport,
+    around: SimplexId,
+    head: VertexId,
+    empty: VertexId,
+    members: &[VertexId],
+    link: Option<&dyn Fn(VertexId) -> Link<VertexId>>,
+    simplex: impl Fn(VertexId) -> SimplexId,
+) {
+    if members.is_empty() {
+        if head != empty {
+            report.violations.push(Violation::BrokenHead { around });
+        }
+        return;
+    }
+    if !members.contains(&head) {
+        report.violations.push(Violation::BrokenHead { around });
+        return;
+    }
+
+    let link = match link {
+        Some(link) => link,
+        None => {
+            for member in members.iter().filter(|m| **m != head) {
+                report.violations.push(Violation::NonManifold {
+                    simplex: simplex(*member),
+                    around,
+                });
+            }
+            return;
+        }
+    };
+
+    let mut broken = false;
+    for member in members {
+        let Link { prev, next } = link(*member);
+        if !members.contains(&prev)
+            || !members.contains(&next)
+            || link(prev).next != *member
+            || link(next).prev != *member
+        {
+            report.violations.push(Violation::BrokenLink {
+                simplex: simplex(*member),
+                around,
+            });
+            broken = true;
+        }
+    }
+
+    // Walking from the head has to visit every attached simplex
+    if !broken {
+        let mut len = 1;
+        let mut curr = link(head).next;
+        while curr != head && len <= members.len() {
+            curr = link(curr).next;
+            len += 1;
+        }
+        if len != members.len() {
+            report.violations.push(Violation::SplitRing { around });
+        }
+    }
+}
+
+/// Groups items by a key.
+fn group_by<K: Eq + Hash, T>(items: impl Iterator<Item = (K, T)>) -> FnvHashMap<K, Vec<T>> {
+    let mut groups = FnvHashMap::<K, Vec<T>>::default();
+    for (key, item) in items {
+        groups.entry(key).or_default().push(item);
+    }
+    groups
+}
+
+/// Checks that pairs of simplices sharing a face wind opposite ways around it.
+/// `opps` has the opposite vertices of the simplices attached to each oriented face.
+/// Faces with more than two simplices attached have no consistent orientation to check.
+fn check_orientations<F: Copy + Eq + Hash>(
+    report: &mut ValidationReport,
+    opps: &FnvHashMap<F, Vec<VertexId>>,
+    twin: impl Fn(F) -> F,
+    simplex: impl Fn(F, VertexId) -> SimplexId,
+) {
+    for (face, members) in opps {
+        if members.len() == 2 && !opps.contains_key(&twin(*face)) {
+            report.violations.push(Violation::InconsistentOrientation {
+                simplex: simplex(*face, members[0]),
+                neighbor: simplex(*face, members[1]),
+            });
+        }
+    }
+}
+
+/// Checks the edges of a mesh and the edge rings around its vertices.
+pub(crate) fn check_edges<M: HasEdges + ?Sized>(mesh: &M, report: &mut ValidationReport) {
+    for edge in mesh.edge_ids() {
+        for v in &edge.0 {
+            if !mesh.contains_vertex(*v) {
+                report.violations.push(Violation::MissingFace {
+                    simplex: (*edge).into(),
+                    face: (*v).into(),
+                });
+            }
+        }
+    }
+
+    let mwb = <M::MwbE as Bit>::BOOL;
+    let targets = group_by(mesh.edge_ids().map(|e| (e.0[0], e.0[1])));
+    let sources = group_by(mesh.edge_ids().map(|e| (e.0[1], e.0[0])));
+    let edges = mesh.edges_r::<Key>();
+
+    for (id, vertex) in mesh.vertices_r::<Key>() {
+        let id = *id;
+        let target_link = |t: VertexId| {
+            edges
+                .get(&EdgeId([id, t]))
+                .map_or(Link::new(id, id), |e| e.links::<Key>()[0])
+        };
+        check_ring(
+            report,
+            id.into(),
+            vertex.target::<Key>(),
+            id,
+            targets.get(&id).map_or(&[], |t| &t[..]),
+            if mwb { None } else { Some(&target_link) },
+            |t| EdgeId([id, t]).into(),
+        );
+
+        let source_link = |s: VertexId| {
+            edges
+                .get(&EdgeId([s, id]))
+                .map_or(Link::new(id, id), |e| e.links::<Key>()[1])
+        };
+        check_ring(
+            report,
+            id.into(),
+            vertex.source::<Key>(),
+            id,
+            sources.get(&id).map_or(&[], |s| &s[..]),
+            if mwb { None } else { Some(&source_link) },
+            |s| EdgeId([s, id]).into(),
+        );
+    }
+}
+
+/// Checks the triangles of a mesh and the triangle rings around its edges.
+pub(crate) fn check_tris<M: HasTris + ?Sized>(mesh: &M, report: &mut ValidationReport) {
+    check_edges(mesh, report);
+
+    for tri in mesh.tri_ids() {
+        if TriId::from_valid(tri.0) != *tri {
+            report.violations.push(Violation::BrokenTwin((*tri).into()));
+        }
+        for edge in &tri.edges() {
+            if !mesh.contains_edge(*edge) {
+                report.violations.push(Violation::MissingFace {
+                    simplex: (*tri).into(),
+                    face: (*edge).into(),
+                });
+            }
+        }
+    }
+
+    let mwb = <M::MwbF as Bit>::BOOL;
+    let opps = group_by(mesh.tri_ids().flat_map(|tri| tri.edges_and_opp().to_vec()));
+    let tris = mesh.tris_r::<Key>();
+
+    for (edge, e) in mesh.edges_r::<Key>() {
+        let edge = *edge;
+        let link = |opp: VertexId| {
+            let tri = TriId::from_valid([edge.0[0], edge.0[1], opp]);
+            tris.get(&tri)
+                .map_or(Link::new(opp, opp), |f| f.link::<Key>(tri, edge))
+        };
+        check_ring(
+            report,
+            edge.into(),
+            e.tri_opp::<Key>(),
+            edge.0[0],
+            opps.get(&edge).map_or(&[], |o| &o[..]),
+            if mwb { None } else { Some(&link) },
+            |opp| TriId::from_valid([edge.0[0], edge.0[1], opp]).into(),
+        );
+    }
+
+    // In a mwb mesh, two triangles winding the same way are already non-manifold
+    if !mwb {
+        check_orientations(report, &opps, EdgeId::twin, |edge, opp| {
+            TriId::from_valid([edge.0[0], edge.0[1], opp]).into()
+        });
+    }
+}
+
+/// Checks the tetrahedrons of a mesh and the tetrahedron rings around its triangles.
+pub(crate) fn check_tets<M: HasTets + ?Sized>(mesh: &M, report: &mut ValidationReport) {
+    check_tris(mesh, report);
+
+    for tet in mesh.tet_ids() {
+        if TetId::from_valid(tet.0) != *tet {
+            report.violations.push(Violation::BrokenTwin((*tet).into()));
+        }
+        for tri in &tet.tris() {
+            if !mesh.contains_tri(*tri) {
+                report.violations.push(Violation::MissingFace {
+                    simplex: (*tet).into(),
+                    face: (*tri).into(),
+                });
+            }
+        }
+    }
+
+    let mwb = <M::MwbT as Bit>::BOOL;
+    let opps = group_by(mesh.tet_ids().flat_map(|tet| tet.tris_and_opp().to_vec()));
+    let tets = mesh.tets_r::<Key>();
+
+    for (tri, f) in mesh.tris_r::<Key>() {
+        let tri = *tri;
+        let link = |opp: VertexId| {
+            let tet = TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], opp]);
+            tets.get(&tet)
+                .map_or(Link::new(opp, opp), |t| t.link::<Key>(tet, tri))
+        };
+        check_ring(
+            report,
+            tri.into(),
+            f.tet_opp::<Key>(),
+            tri.0[0],
+            opps.get(&tri).map_or(&[], |o| &o[..]),
+            if mwb { None } else { Some(&link) },
+            |opp| TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], opp]).into(),
+        );
+    }
+
+    // In a mwb mesh, two tetrahedrons winding the same way are already non-manifold
+    if !mwb {
+        check_orientations(report, &opps, TriId::twin, |tri, opp| {
+            TetId::from_valid([tri.0[0], tri.0[1], tri.0[2], opp]).into()
+        });
+    }
+}
+
+/// Checks that the tetrahedrons of a positioned mesh have positive orientations,
+/// using the exact orientation test so flat tetrahedrons get caught.
+pub(crate) fn check_tet_orientations<M>(mesh: &M, report: &mut ValidationReport)
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    for tet in mesh.tet_ids() {
+        let [p0, p1, p2, p3] = tet.0.map(|v| index_fn(mesh, v));
+        let orient = exact::orient_3d(p0, p1, p2, p3);
+        if orient == 0.0 {
+            report.violations.push(Violation::FlatTet(*tet));
+        } else if orient < 0.0 {
+            report.violations.push(Violation::InvertedTet(*tet));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::mesh1::MwbComboMesh1;
+    use crate::mesh3::MwbComboMesh3;
+    use crate::tet::HasPositionAndTets;
+    use crate::vertex::{HasPosition3D, HasVertices};
+    use crate::{ComboMesh0, ComboMesh1, ComboMesh2, ComboMesh3};
+
+    #[test]
+    fn test_validate_valid() {
+        let mut mesh = ComboMesh1::<usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.extend_edges(vec![
+            ([ids[0], ids[1]], 1),
+            ([ids[0], ids[2]], 2),
+            ([ids[1], ids[0]], 3),
+            ([ids[3], ids[0]], 4),
+        ]);
+        assert_eq!(mesh.validate(), ValidationReport::default());
+
+        let mut mesh = MwbComboMesh1::<usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.extend_edges(vec![
+            ([ids[0], ids[1]], 1),
+            ([ids[1], ids[2]], 2),
+            ([ids[2], ids[0]], 3),
+        ]);
+        assert_eq!(mesh.validate(), ValidationReport::default());
+
+        // 3 triangles on one edge
+        let mut mesh = ComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[1], ids[2]], 1),
+            ([ids[0], ids[1], ids[3]], 2),
+            ([ids[0], ids[1], ids[4]], 3),
+            ([ids[1], ids[0], ids[4]], 4),
+        ]);
+        assert_eq!(mesh.validate(), ValidationReport::default());
+
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let mut rng = Pcg64::seed_from_u64(7);
+        mesh.extend_vertices((0..200).map(|_| Point3::new(rng.gen(), rng.gen(), rng.gen())));
+        let mesh = mesh.delaunay_tets(|| (), || (), || ());
+        assert_eq!(mesh.validate(), ValidationReport::default());
+        assert_eq!(mesh.validate_with_positions(), ValidationReport::default());
+    }
+
+    #[test]
+    fn test_validate_broken_links() {
+        let mut mesh = ComboMesh1::<usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.extend_edges(vec![
+            ([ids[0], ids[1]], 1),
+            ([ids[0], ids[2]], 2),
+            ([ids[0], ids[3]], 3),
+        ]);
+
+        mesh.edges_r_mut::<Key>()
+            .get_mut(&EdgeId([ids[0], ids[1]]))
+            .unwrap()
+            .links_mut::<Key>()[0]
+            .next = ids[1];
+        let report = mesh.validate();
+        assert!(report.violations.contains(&Violation::BrokenLink {
+            simplex: EdgeId([ids[0], ids[1]]).into(),
+            around: ids[0].into(),
+        }));
+
+        let mut mesh = ComboMesh1::<usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.add_edge([ids[0], ids[1]], 1);
+        *mesh.vertices_r_mut::<Key>()[ids[2]].target_mut::<Key>() = ids[1];
+        assert_eq!(
+            mesh.validate().violations,
+            vec![Violation::BrokenHead {
+                around: ids[2].into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_non_manifold() {
+        let mut mesh = MwbComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.add_tet([ids[0], ids[1], ids[2], ids[3]], 1);
+        assert_eq!(mesh.validate(), ValidationReport::default());
+
+        // Sneak in a tet on the same oriented tri, which add_tet would prevent
+        let tet =
+            mesh.tets_r::<Key>()[&TetId::from_valid([ids[0], ids[1], ids[2], ids[3]])].clone();
+        let id = TetId::from_valid([ids[0], ids[1], ids[2], ids[4]]);
+        mesh.tets_r_mut::<Key>().insert(id, tet);
+        let report = mesh.validate();
+        assert!(report.violations.contains(&Violation::NonManifold {
+            simplex: id.into(),
+            around: TriId::from_valid([ids[0], ids[1], ids[2]]).into(),
+        }));
+        assert!(report.violations.contains(&Violation::MissingFace {
+            simplex: id.into(),
+            face: TriId::from_valid([ids[1], ids[4], ids[2]]).into(),
+        }));
+    }
+
+    #[test]
+    fn test_validate_broken_twin() {
+        let mut mesh = ComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9]);
+        mesh.add_tri([ids[0], ids[1], ids[2]], 1);
+        let tri = TriId::from_valid([ids[0], ids[1], ids[2]]);
+
+        // Store the same triangle under a rotation of its canonical id
+        let value = mesh.tris_r_mut::<Key>().remove(&tri).unwrap();
+        let id = TriId([tri.0[1], tri.0[2], tri.0[0]]);
+        mesh.tris_r_mut::<Key>().insert(id, value);
+        assert!(mesh
+            .validate()
+            .violations
+            .contains(&Violation::BrokenTwin(id.into())));
+    }
+
+    #[test]
+    fn test_validate_inconsistent_orientation() {
+        let mut mesh = ComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[1], ids[2]], 1),
+            ([ids[1], ids[0], ids[3]], 2),
+        ]);
+        assert_eq!(mesh.validate(), ValidationReport::default());
+
+        let mut mesh = ComboMesh2::<usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2]);
+        mesh.extend_tris(vec![
+            ([ids[0], ids[1], ids[2]], 1),
+            ([ids[0], ids[1], ids[3]], 2),
+        ]);
+        let report = mesh.validate();
+        assert_eq!(report.violations.len(), 1);
+        match report.violations[0] {
+            Violation::InconsistentOrientation { simplex, neighbor } => {
+                let mut pair = [simplex, neighbor];
+                let expected = [
+                    SimplexId::from(TriId::from_valid([ids[0], ids[1], ids[2]])),
+                    SimplexId::from(TriId::from_valid([ids[0], ids[1], ids[3]])),
+                ];
+                if pair[0] != expected[0] {
+                    pair.swap(0, 1);
+                }
+                assert_eq!(pair, expected);
+            }
+            violation => panic!("Unexpected violation {:?}", violation),
+        }
+
+        let mut mesh = ComboMesh3::<usize, usize, usize, usize>::default();
+        let ids = mesh.extend_vertices(vec![3, 6, 9, 2, 5]);
+        mesh.extend_tets(vec![
+            ([ids[0], ids[1], ids[2], ids[3]], 1),
+            ([ids[0], ids[1], ids[2], ids[4]], 2),
+        ]);
+        assert_eq!(mesh.validate().violations.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_inverted_tet() {
+        let mut mesh = ComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            Point3::origin,
+            || (),
+            || (),
+            || (),
+        );
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ]);
+        let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|i| index_fn(&mesh, ids[i]));
+        let tet = if exact::orient_3d(p0, p1, p2, p3) > 0.0 {
+            TetId::from_valid([ids[1], ids[0], ids[2], ids[3]])
+        } else {
+            TetId::from_valid([ids[0], ids[1], ids[2], ids[3]])
+        };
+        mesh.add_tet(tet, ());
+
+        assert_eq!(mesh.validate(), ValidationReport::default());
+        assert_eq!(
+            mesh.validate_with_positions().violations,
+            vec![Violation::InvertedTet(tet)]
+        );
+    }
+
+    #[test]
+    fn test_validate_flat_tet() {
+        let mut mesh = ComboMesh3::<Point3<f64>, (), (), ()>::with_defaults(
+            Point3::origin,
+            || (),
+            || (),
+            || (),
+        );
+        let ids = mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+        ]);
+        let tet = TetId::from_valid([ids[0], ids[1], ids[2], ids[3]]);
+        mesh.add_tet(tet, ());
+
+        assert_eq!(mesh.validate(), ValidationReport::default());
+        assert_eq!(
+            mesh.validate_with_positions().violations,
+            vec![Violation::FlatTet(tet)]
+        );
+    }
+}
@@ -2,17 +2,25 @@
 
 use alga::general::{JoinSemilattice, MeetSemilattice};
 use fnv::FnvHashMap;
+use fnv::FnvHashSet;
 use idmap::{table::DenseEntryTable, OrderedIdMap};
 use nalgebra::allocator::Allocator;
-use nalgebra::dimension::U3;
-use nalgebra::{DefaultAllocator, DimName, Point};
+use nalgebra::dimension::{U2, U3};
+use nalgebra::{DefaultAllocator, DimName, Point, Point3};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{iter::Map, path::Path};
 use typenum::{Bit, B0, B1};
 
+use crate::edge::{HasEdges, WithEdges};
+use crate::io::obj::{Obj, ObjAttributes};
 use crate::private::{Key, Lock};
 use crate::tet::{HasTets, WithTets};
+use crate::tetrahedralize::{
+    ConstrainedTetsError, DelaunayTetsBuilder, DelaunayTetsError, WithSteinerPoints,
+};
+use crate::tri::{HasTris, TriId, WithTris};
+use crate::triangulate::{ConstrainedTrisError, DelaunayTrisError, WithSplitEdges};
 
 pub(crate) type PositionDim<P> = <P as Position>::Dim;
 pub(crate) type PositionPoint<P> = Point<f64, PositionDim<P>>;
@@ -196,11 +204,14 @@ pub trait HasVertices {
     fn default_v_r<L: Lock>(&self) -> fn() -> Self::V;
 
     #[doc(hidden)]
-    #[cfg(feature = "obj")]
-    fn obj_with_vertices_higher<L: Lock>(
-        &self,
-        data: &mut obj::ObjData,
-        v_inv: &FnvHashMap<VertexId, usize>,
+    fn obj_with_vertices_higher<L: Lock>(&self, obj: &mut Obj, v_inv: &FnvHashMap<VertexId, usize>);
+
+    #[doc(hidden)]
+    fn obj_add_vertices_higher<L: Lock>(
+        &mut self,
+        obj: &Obj,
+        ids: &[VertexId],
+        attributes: &mut ObjAttributes,
     );
 
     /// Gets the default value of a vertex.
@@ -330,6 +341,41 @@ pub trait HasVertices {
         self.clear_vertices_higher::<Key>();
         self.vertices_r_mut::<Key>().clear();
     }
+
+    /// Turns this vertex mesh into an edge mesh with no edges, keeping all ids and values.
+    fn into_with_edges<E>(self, default_e: fn() -> E) -> Self::WithEdges
+    where
+        Self: Sized + HasVertices<HigherV = B0> + WithEdges<<Self as HasVertices>::V, E>,
+        Self::WithEdges: HasEdges<HigherE = B0>,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let next_vertex_id = self.next_vertex_id::<Key>();
+        let mut mesh = Self::WithEdges::from_ve_r::<_, _, Key>(
+            self.into_v_r::<Key>(),
+            vec![],
+            default_v,
+            default_e,
+        );
+        *mesh.next_vertex_id_mut::<Key>() = next_vertex_id;
+        mesh
+    }
+
+    /// Gets the `K`-skeleton of this mesh, which has the vertices, edges, and so on
+    /// up to dimension `K`, with the same ids and values.
+    fn skeleton<const K: usize>(&self) -> <Self as Skeleton<K>>::Skeleton
+    where
+        Self: Skeleton<K>,
+    {
+        self.skeleton_r::<Key>()
+    }
+}
+
+/// For simplicial complexes with a `K`-skeleton of a lower dimension than the complex.
+pub trait Skeleton<const K: usize>: HasVertices {
+    type Skeleton: HasVertices<V = Self::V>;
+
+    #[doc(hidden)]
+    fn skeleton_r<L: Lock>(&self) -> Self::Skeleton;
 }
 
 /// For concrete simplicial complexes
@@ -389,13 +435,157 @@ where
 {
 }
 
+/// For 2D concrete simplicial complexes
+pub trait HasPosition2D: HasPosition
+where
+    Self::V: Position<Dim = U2>,
+    DefaultAllocator: Allocator<f64, HasPositionDim<Self>>,
+{
+    /// Turns this mesh into a Delaunay triangulation of its vertices.
+    /// There are no triangles if there are fewer than 3 vertices or they're all collinear.
+    /// A vertex at the same position as another is left without triangles.
+    ///
+    /// # Panics
+    /// Panics if some position isn't finite or the predicates fail.
+    /// Use `try_delaunay_tris` to get an error instead.
+    fn delaunay_tris<E, F>(
+        self,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+    ) -> <Self::WithTris as HasTris>::WithMwbF
+    where
+        Self: Sized,
+        Self: WithTris<<Self as HasVertices>::V, E, F>,
+        <Self::WithTris as HasTris>::WithMwbF: HasTris<HigherF = B0>,
+    {
+        self.try_delaunay_tris(default_edge, default_tri)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Turns this mesh into a Delaunay triangulation of its vertices, like `delaunay_tris`,
+    /// or reports why some vertex couldn't be inserted.
+    fn try_delaunay_tris<E, F>(
+        self,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+    ) -> Result<<Self::WithTris as HasTris>::WithMwbF, DelaunayTrisError>
+    where
+        Self: Sized,
+        Self: WithTris<<Self as HasVertices>::V, E, F>,
+        <Self::WithTris as HasTris>::WithMwbF: HasTris<HigherF = B0>,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let mesh = <Self::WithTris as HasTris>::WithMwbF::from_vef_r::<_, _, _, Key>(
+            self.into_v_r::<Key>(),
+            vec![],
+            vec![],
+            default_v,
+            default_edge,
+            default_tri,
+        );
+
+        crate::triangulate::delaunay_tris(mesh)
+    }
+
+    /// Turns this mesh into a constrained Delaunay triangulation of the region
+    /// its edges enclose. The region must be on the left of the edges,
+    /// so outer boundaries go counterclockwise and hole boundaries go clockwise.
+    /// Edges that the Delaunay triangulation misses get recovered by flipping,
+    /// and get split at vertices lying on them and where they cross each other.
+    ///
+    /// Returns the triangle mesh and a map from split edges to the vertices that split them.
+    ///
+    /// # Panics
+    /// Panics if some position isn't finite, if 2 edges cross too close to other vertices
+    /// to split them there, or if the predicates fail.
+    /// Use `try_constrained_delaunay_tris` to get an error instead.
+    fn constrained_delaunay_tris<F>(
+        self,
+        default_tri: fn() -> F,
+    ) -> WithSplitEdges<<Self::WithTris as HasTris>::WithMwbF>
+    where
+        Self: Sized + Clone + HasEdges,
+        Self: WithTris<<Self as HasVertices>::V, <Self as HasEdges>::E, F>,
+        <Self::WithTris as HasTris>::WithMwbF: HasTris<HigherF = B0>,
+        Self::V: Clone,
+        Self::E: Clone,
+    {
+        self.try_constrained_delaunay_tris(default_tri)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Turns this mesh into a constrained Delaunay triangulation of the region its edges enclose,
+    /// like `constrained_delaunay_tris`, or reports why it failed.
+    fn try_constrained_delaunay_tris<F>(
+        self,
+        default_tri: fn() -> F,
+    ) -> Result<WithSplitEdges<<Self::WithTris as HasTris>::WithMwbF>, ConstrainedTrisError>
+    where
+        Self: Sized + Clone + HasEdges,
+        Self: WithTris<<Self as HasVertices>::V, <Self as HasEdges>::E, F>,
+        <Self::WithTris as HasTris>::WithMwbF: HasTris<HigherF = B0>,
+        Self::V: Clone,
+        Self::E: Clone,
+    {
+        let default_v = self.default_v_r::<Key>();
+        let mesh = <Self::WithTris as HasTris>::WithMwbF::from_vef_r::<_, _, _, Key>(
+            self.clone().into_v_r::<Key>(),
+            vec![],
+            vec![],
+            default_v,
+            self.default_e_r::<Key>(),
+            default_tri,
+        );
+
+        crate::triangulate::constrained_delaunay_tris(mesh, self)
+    }
+
+    /// Gets the convex hull of the vertices of this mesh as a loop of edges going counterclockwise.
+    /// Vertices not on the hull are left out, and so are vertices in the middle of hull edges.
+    /// There are no edges or vertices if there are fewer than 3 vertices or they're all collinear.
+    fn convex_hull<E>(self, default_edge: fn() -> E) -> Self::WithEdges
+    where
+        Self: Sized,
+        Self: WithEdges<<Self as HasVertices>::V, E>,
+        Self::WithEdges: HasEdges<HigherE = B0>,
+    {
+        let edges = crate::triangulate::convex_hull_edges(&self);
+        let hull = edges
+            .iter()
+            .map(|edge| edge.0[0])
+            .collect::<FnvHashSet<_>>();
+        let default_v = self.default_v_r::<Key>();
+
+        Self::WithEdges::from_ve_r::<_, _, Key>(
+            self.into_v_r::<Key>().filter(|(v, _)| hull.contains(v)),
+            edges.into_iter().map(|edge| (edge, default_edge())),
+            default_v,
+            default_edge,
+        )
+    }
+}
+
+impl<M> HasPosition2D for M
+where
+    M: HasVertices,
+    Self::V: Position<Dim = U2>,
+    DefaultAllocator: Allocator<f64, HasPositionDim<Self>>,
+{
+}
+
 /// For 3D concrete simplicial complexes
 pub trait HasPosition3D: HasPosition
 where
     Self::V: Position<Dim = U3>,
     DefaultAllocator: Allocator<f64, HasPositionDim<Self>>,
 {
-    /// Turns this mesh into a Delaunay tetrahedralization of its vertices
+    /// Turns this mesh into a Delaunay tetrahedralization of its vertices.
+    /// There are no tets if there are fewer than 4 vertices or they're all coplanar.
+    /// Of vertices with the same position, only one is part of any tet.
+    /// Use `DelaunayTetsBuilder` for more options.
+    ///
+    /// # Panics
+    /// Panics if some position isn't finite. Use `try_delaunay_tets` to get an error instead.
     fn delaunay_tets<E, F, T>(
         self,
         default_edge: fn() -> E,
@@ -405,72 +595,335 @@ where
     where
         Self: Sized,
         Self: WithTets<<Self as HasVertices>::V, E, F, T>,
+    {
+        DelaunayTetsBuilder::new().build(self, default_edge, default_tri, default_tet)
+    }
+
+    /// Turns this mesh into a Delaunay tetrahedralization of its vertices,
+    /// or reports why its vertices don't span a tetrahedralization.
+    /// Use `DelaunayTetsBuilder` for more options.
+    fn try_delaunay_tets<E, F, T>(
+        self,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> Result<<Self::WithTets as HasTets>::WithMwbT, DelaunayTetsError>
+    where
+        Self: Sized,
+        Self: WithTets<<Self as HasVertices>::V, E, F, T>,
+    {
+        DelaunayTetsBuilder::new().try_build(self, default_edge, default_tri, default_tet)
+    }
+
+    /// Turns this mesh into a weighted Delaunay (regular) tetrahedralization of its vertices,
+    /// where `weight` gives the squared radius of a vertex.
+    /// Redundant vertices, whose power cells are empty, are kept but are not part of any tet.
+    /// Use `DelaunayTetsBuilder` for more options.
+    ///
+    /// # Panics
+    /// Panics if some position isn't finite.
+    /// Use `try_weighted_delaunay_tets` to get an error instead.
+    fn weighted_delaunay_tets<E, F, T>(
+        self,
+        weight: impl Fn(&Self::V) -> f64,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> <Self::WithTets as HasTets>::WithMwbT
+    where
+        Self: Sized,
+        Self: WithTets<<Self as HasVertices>::V, E, F, T>,
+    {
+        DelaunayTetsBuilder::new().build_weighted(
+            self,
+            weight,
+            default_edge,
+            default_tri,
+            default_tet,
+        )
+    }
+
+    /// Turns this mesh into a weighted Delaunay (regular) tetrahedralization of its vertices,
+    /// or reports why its vertices don't span a tetrahedralization.
+    /// Use `DelaunayTetsBuilder` for more options.
+    fn try_weighted_delaunay_tets<E, F, T>(
+        self,
+        weight: impl Fn(&Self::V) -> f64,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> Result<<Self::WithTets as HasTets>::WithMwbT, DelaunayTetsError>
+    where
+        Self: Sized,
+        Self: WithTets<<Self as HasVertices>::V, E, F, T>,
+    {
+        DelaunayTetsBuilder::new().try_build_weighted(
+            self,
+            weight,
+            default_edge,
+            default_tri,
+            default_tet,
+        )
+    }
+
+    /// Turns this mesh into a Delaunay tetrahedralization of its vertices using multiple threads.
+    /// Gives the same tets as `delaunay_tets`.
+    ///
+    /// # Panics
+    /// Panics if some position isn't finite.
+    /// Use `try_delaunay_tets_parallel` to get an error instead.
+    #[cfg(feature = "rayon")]
+    fn delaunay_tets_parallel<E, F, T>(
+        self,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> <Self::WithTets as HasTets>::WithMwbT
+    where
+        Self: Sized,
+        Self: WithTets<<Self as HasVertices>::V, E, F, T>,
+        <Self::WithTets as HasTets>::WithMwbT: Sync,
+    {
+        DelaunayTetsBuilder::new().build_parallel(self, default_edge, default_tri, default_tet)
+    }
+
+    /// Turns this mesh into a Delaunay tetrahedralization of its vertices using multiple threads,
+    /// or reports why its vertices don't span a tetrahedralization.
+    #[cfg(feature = "rayon")]
+    fn try_delaunay_tets_parallel<E, F, T>(
+        self,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+        default_tet: fn() -> T,
+    ) -> Result<<Self::WithTets as HasTets>::WithMwbT, DelaunayTetsError>
+    where
+        Self: Sized,
+        Self: WithTets<<Self as HasVertices>::V, E, F, T>,
+        <Self::WithTets as HasTets>::WithMwbT: Sync,
+    {
+        DelaunayTetsBuilder::new().try_build_parallel(self, default_edge, default_tri, default_tet)
+    }
+
+    /// Gets the convex hull of the vertices of this mesh
+    /// as a closed surface of outward-pointing triangles.
+    /// Vertices not on the hull are left out.
+    /// There are no triangles if there are fewer than 4 vertices or they're all coplanar.
+    ///
+    /// # Panics
+    /// Panics if some position isn't finite. Use `try_convex_hull` to get an error instead.
+    fn convex_hull<E, F>(self, default_edge: fn() -> E, default_tri: fn() -> F) -> Self::WithTris
+    where
+        Self: Sized,
+        Self: WithTris<<Self as HasVertices>::V, E, F>,
+        Self::WithTris: HasTris<HigherF = B0>,
+    {
+        self.try_convex_hull(default_edge, default_tri)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Gets the convex hull of the vertices of this mesh
+    /// as a closed surface of outward-pointing triangles, or reports why it couldn't be found.
+    /// Vertices not on the hull are left out.
+    /// There are no triangles if there are fewer than 4 vertices or they're all coplanar.
+    fn try_convex_hull<E, F>(
+        self,
+        default_edge: fn() -> E,
+        default_tri: fn() -> F,
+    ) -> Result<Self::WithTris, DelaunayTetsError>
+    where
+        Self: Sized,
+        Self: WithTris<<Self as HasVertices>::V, E, F>,
+        Self::WithTris: HasTris<HigherF = B0>,
+    {
+        let tris = crate::tetrahedralize::convex_hull_tris(&self)?;
+        let hull = tris
+            .iter()
+            .flat_map(|tri| tri.0.to_vec())
+            .collect::<FnvHashSet<_>>();
+        let default_v = self.default_v_r::<Key>();
+
+        Ok(Self::WithTris::from_vef_r::<_, _, _, Key>(
+            self.into_v_r::<Key>().filter(|(v, _)| hull.contains(v)),
+            vec![],
+            tris.into_iter().map(|tri| (tri, default_tri())),
+            default_v,
+            default_edge,
+            default_tri,
+        ))
+    }
+
+    /// Turns this closed triangle surface into a constrained Delaunay tetrahedralization
+    /// of the region it encloses. The triangles must point outward.
+    /// Edges and triangles of the surface that the Delaunay tetrahedralization
+    /// misses get recovered by adding Steiner points.
+    ///
+    /// Returns the tet mesh, a map from split edges to the Steiner points that split them,
+    /// and a map from split triangles to the Steiner points that split them.
+    ///
+    /// # Panics
+    /// Panics if recovering the surface takes an absurd number of Steiner points,
+    /// if the predicates fail, or if some position isn't finite.
+    /// Use `try_constrained_delaunay_tets` to get an error instead.
+    fn constrained_delaunay_tets<T>(
+        self,
+        default_tet: fn() -> T,
+    ) -> WithSteinerPoints<<Self::WithTets as HasTets>::WithMwbT>
+    where
+        Self: Sized + Clone + HasTris,
+        Self: WithTets<<Self as HasVertices>::V, <Self as HasEdges>::E, <Self as HasTris>::F, T>,
+        Self::V: Clone,
+        Self::E: Clone,
+        Self::F: Clone,
+    {
+        self.try_constrained_delaunay_tets(default_tet)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Turns this closed triangle surface into a constrained Delaunay tetrahedralization
+    /// of the region it encloses, like `constrained_delaunay_tets`,
+    /// or reports why the surface couldn't be recovered.
+    fn try_constrained_delaunay_tets<T>(
+        self,
+        default_tet: fn() -> T,
+    ) -> Result<WithSteinerPoints<<Self::WithTets as HasTets>::WithMwbT>, ConstrainedTetsError>
+    where
+        Self: Sized + Clone + HasTris,
+        Self: WithTets<<Self as HasVertices>::V, <Self as HasEdges>::E, <Self as HasTris>::F, T>,
+        Self::V: Clone,
+        Self::E: Clone,
+        Self::F: Clone,
     {
         let default_v = self.default_v_r::<Key>();
         let mesh = <Self::WithTets as HasTets>::WithMwbT::from_veft_r::<_, _, _, _, Key>(
-            self.into_v_r::<Key>(),
+            self.clone().into_v_r::<Key>(),
             vec![],
             vec![],
             vec![],
             default_v,
-            default_edge,
-            default_tri,
+            self.default_e_r::<Key>(),
+            self.default_f_r::<Key>(),
             default_tet,
         );
 
-        crate::tetrahedralize::delaunay_tets(mesh)
+        crate::tetrahedralize::constrained_delaunay_tets(mesh, self)
     }
 
-    /// Converts this mesh into OBJ data.
-    /// Edges lose their directedness.
+    /// Inserts a vertex at some position into this Delaunay tet mesh and returns its id.
+    /// Only the tets whose circumspheres contain the position get replaced,
+    /// as well as the hull triangles it sees if it's outside the hull,
+    /// so the mesh stays Delaunay without getting rebuilt.
+    ///
+    /// If the mesh has no tets yet, all its vertices get tetrahedralized
+    /// once there are at least 4 of them that aren't all coplanar.
     ///
     /// # Panics
-    /// Panics if this is a tet mesh, because those and OBJ don't mix.
-    #[cfg(feature = "obj")]
-    fn to_obj(&self) -> obj::ObjData {
-        let vertices = self.vertex_ids().copied().collect::<Vec<_>>();
-        let vertices_inv = vertices
-            .iter()
-            .enumerate()
-            .map(|(i, v)| (*v, i))
-            .collect::<FnvHashMap<_, _>>();
+    /// Panics if some position isn't finite.
+    /// Use `try_insert_vertex_delaunay` to get an error instead.
+    fn insert_vertex_delaunay(&mut self, position: Point3<f64>) -> VertexId
+    where
+        Self: Sized + HasTets,
+    {
+        self.try_insert_vertex_delaunay(position)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        let mut data = obj::ObjData {
-            position: vertices
-                .into_iter()
-                .map(|v| {
-                    let point = self.position(v);
-                    [point.x as f32, point.y as f32, point.z as f32]
-                })
-                .collect::<Vec<_>>(),
+    /// Inserts a vertex at some position into this Delaunay tet mesh and returns its id,
+    /// like `insert_vertex_delaunay`, or reports why it couldn't be inserted.
+    /// If there's an error, the vertex doesn't get added.
+    fn try_insert_vertex_delaunay(
+        &mut self,
+        position: Point3<f64>,
+    ) -> Result<VertexId, DelaunayTetsError>
+    where
+        Self: Sized + HasTets,
+    {
+        crate::tetrahedralize::insert_vertex_delaunay(self, position)
+    }
 
-            texture: vec![],
-            normal: vec![],
+    /// Removes a vertex from this Delaunay tet mesh and returns its value.
+    /// The hole it leaves gets retetrahedralized so the mesh stays Delaunay.
+    /// If the vertex's neighbors are coplanar or there are fewer than 4 of them,
+    /// the hole is flat and no tets replace it.
+    ///
+    /// # Panics
+    /// Panics if some position isn't finite,
+    /// or if the hole can't be filled because the predicates failed.
+    /// Use `try_remove_vertex_delaunay` to get an error instead.
+    fn remove_vertex_delaunay(&mut self, vertex: VertexId) -> Option<Self::V>
+    where
+        Self: Sized + HasTets,
+    {
+        self.try_remove_vertex_delaunay(vertex)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-            objects: vec![obj::Object {
-                name: "Mesh".to_owned(),
-                groups: vec![obj::Group {
-                    name: "Group".to_owned(),
-                    index: 0,
-                    material: None,
-                    polys: vec![],
-                }],
-            }],
+    /// Removes a vertex from this Delaunay tet mesh and returns its value,
+    /// like `remove_vertex_delaunay`, or reports why the hole couldn't be retetrahedralized.
+    /// If there's an error, the mesh is left unchanged.
+    fn try_remove_vertex_delaunay(
+        &mut self,
+        vertex: VertexId,
+    ) -> Result<Option<Self::V>, DelaunayTetsError>
+    where
+        Self: Sized + HasTets,
+    {
+        crate::tetrahedralize::remove_vertex_delaunay(self, vertex)
+    }
 
-            material_libs: vec![],
-        };
+    /// Refines this tet mesh with Delaunay refinement by inserting vertices
+    /// until no tet has a circumradius-to-shortest-edge ratio over `max_radius_edge_ratio`
+    /// or a volume over `max_volume`.
+    /// The boundary triangles of the mesh, the interior triangles in `constraints`
+    /// and their edges are kept as constraints;
+    /// the ones that would get encroached on get split first.
+    /// Orientation doesn't matter for `constraints`, and triangles that aren't interior triangles
+    /// of the mesh are ignored.
+    /// Ratios at or below 2 may not terminate, in which case refinement
+    /// stops after inserting an absurd number of vertices.
+    ///
+    /// The mesh should be a Delaunay tetrahedralization, e.g. from `constrained_delaunay_tets`,
+    /// with positively oriented tets. For a constrained one, pass the triangles
+    /// of its input that are inside the mesh, or the pieces they got split into.
+    ///
+    /// Returns the number of vertices inserted.
+    fn refine_tets<I>(
+        &mut self,
+        constraints: I,
+        max_radius_edge_ratio: f64,
+        max_volume: f64,
+    ) -> usize
+    where
+        Self: Sized + HasTets<MwbT = B1>,
+        I: IntoIterator<Item = TriId>,
+    {
+        crate::tetrahedralize::refine_tets(self, constraints, max_radius_edge_ratio, max_volume)
+    }
 
-        self.obj_with_vertices_higher::<Key>(&mut data, &vertices_inv);
-        data
+    /// Converts this mesh into OBJ data of the `obj` crate, with positions rounded to `f32`.
+    /// Edges lose their directedness.
+    ///
+    /// # Panics
+    /// Panics if this is a tet mesh, because those and OBJ don't mix.
+    #[cfg(feature = "obj")]
+    #[deprecated(note = "use `Obj::from_mesh`, which keeps `f64` positions")]
+    fn to_obj(&self) -> obj::ObjData
+    where
+        Self: Sized,
+    {
+        (&Obj::from_mesh(self)).into()
     }
 
-    /// Writes this mesh as an OBJ file.
+    /// Writes this mesh as an OBJ file. See `io::obj::save_obj`,
+    /// which this wraps, keeping the error type of the `obj` crate.
     ///
     /// # Panics
     /// Panics if this is a tet mesh, because those and OBJ don't mix.
     #[cfg(feature = "obj")]
-    fn write_obj<P: AsRef<Path>>(&self, path: P) -> Result<(), obj::ObjError> {
-        self.to_obj().save(path)
+    fn write_obj<P: AsRef<Path>>(&self, path: P) -> Result<(), obj::ObjError>
+    where
+        Self: Sized,
+    {
+        crate::io::obj::save_obj(self, path).map_err(obj::ObjError::Io)
     }
 }
 
@@ -675,14 +1128,38 @@ macro_rules! impl_has_vertices {
         }
 
         crate::if_b0! { $higher =>
-            #[cfg(feature = "obj")]
-            fn obj_with_vertices_higher<L: crate::private::Lock>(&self, _: &mut obj::ObjData, _: &fnv::FnvHashMap<crate::vertex::VertexId, usize>) {}
+            fn obj_with_vertices_higher<L: crate::private::Lock>(
+                &self,
+                _: &mut crate::io::obj::Obj,
+                _: &fnv::FnvHashMap<crate::vertex::VertexId, usize>,
+            ) {
+            }
+
+            fn obj_add_vertices_higher<L: crate::private::Lock>(
+                &mut self,
+                _: &crate::io::obj::Obj,
+                _: &[crate::vertex::VertexId],
+                _: &mut crate::io::obj::ObjAttributes,
+            ) {
+            }
         }
 
         crate::if_b1! { $higher =>
-            #[cfg(feature = "obj")]
-            fn obj_with_vertices_higher<L: crate::private::Lock>(&self, data: &mut obj::ObjData, v_inv: &fnv::FnvHashMap<crate::vertex::VertexId, usize>) {
-                self.obj_with_edges::<crate::private::Key>(data, v_inv);
+            fn obj_with_vertices_higher<L: crate::private::Lock>(
+                &self,
+                obj: &mut crate::io::obj::Obj,
+                v_inv: &fnv::FnvHashMap<crate::vertex::VertexId, usize>,
+            ) {
+                self.obj_with_edges::<crate::private::Key>(obj, v_inv);
+            }
+
+            fn obj_add_vertices_higher<L: crate::private::Lock>(
+                &mut self,
+                obj: &crate::io::obj::Obj,
+                ids: &[crate::vertex::VertexId],
+                attributes: &mut crate::io::obj::ObjAttributes,
+            ) {
+                self.obj_add_edges::<crate::private::Key>(obj, ids, attributes);
             }
         }
     };
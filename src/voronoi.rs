@@ -0,0 +1,434 @@
+//! Voronoi and power diagrams, the duals of Delaunay and weighted Delaunay tetrahedralizations
+
+use fnv::{FnvHashMap, FnvHashSet};
+use nalgebra::{dimension::U3, Point3, Vector3};
+
+use crate::edge::EdgeId;
+use crate::tet::{HasTets, TetId};
+use crate::vertex::{HasPosition3D, Position, VertexId};
+
+/// A convex polyhedral cell of a Voronoi or power diagram, dual to a vertex of a tetrahedralization
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    /// The vertex whose cell this is
+    pub vertex: VertexId,
+    /// The corners of the cell, dual to the tetrahedrons around the vertex
+    pub positions: Vec<Point3<f64>>,
+    /// The faces of the cell, dual to the edges on the vertex, except for faces from clipping.
+    /// Each is a loop of indexes into `positions` that goes counterclockwise seen from outside the cell.
+    pub faces: Vec<Vec<usize>>,
+}
+
+/// The point with the same power distance to all the vertices of a tet.
+/// This is the circumcenter if the weights are all the same.
+pub(crate) fn tet_orthocenter<M>(
+    mesh: &M,
+    tet: TetId,
+    weight: impl Fn(VertexId) -> f64,
+) -> Point3<f64>
+where
+    M: HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let p0 = mesh.position(tet.0[0]);
+    let w0 = weight(tet.0[0]);
+    let a = mesh.position(tet.0[1]) - p0;
+    let b = mesh.position(tet.0[2]) - p0;
+    let c = mesh.position(tet.0[3]) - p0;
+    let la = a.norm_squared() - weight(tet.0[1]) + w0;
+    let lb = b.norm_squared() - weight(tet.0[2]) + w0;
+    let lc = c.norm_squared() - weight(tet.0[3]) + w0;
+    p0 + (b.cross(&c) * la + c.cross(&a) * lb + a.cross(&b) * lc) / (2.0 * a.dot(&b.cross(&c)))
+}
+
+/// Whether a vertex is surrounded by tets
+fn is_interior<M: HasTets>(mesh: &M, vertex: VertexId) -> bool {
+    let mut tets = mesh.vertex_tets(vertex).peekable();
+    tets.peek().is_some()
+        && tets.all(|tet| {
+            tet.tris()
+                .iter()
+                .filter(|tri| tri.contains_vertex(vertex))
+                .all(|tri| mesh.tri_tets(tri.twin()).next().is_some())
+        })
+}
+
+/// The cells of the vertices surrounded by tets, with corners at the given centers of the tets
+pub(crate) fn cells<M, C>(mesh: &M, center: C) -> Vec<Cell>
+where
+    M: HasTets,
+    C: Fn(TetId) -> Point3<f64>,
+{
+    let mut centers = FnvHashMap::default();
+
+    mesh.vertex_ids()
+        .copied()
+        .filter(|vertex| is_interior(mesh, *vertex))
+        .map(|vertex| {
+            let tets = mesh.vertex_tets(vertex).collect::<Vec<_>>();
+            let indexes = tets
+                .iter()
+                .enumerate()
+                .map(|(i, tet)| (*tet, i))
+                .collect::<FnvHashMap<_, _>>();
+            let positions = tets
+                .iter()
+                .map(|tet| *centers.entry(*tet).or_insert_with(|| center(*tet)))
+                .collect();
+
+            let targets = tets
+                .iter()
+                .flat_map(|tet| tet.0.to_vec())
+                .filter(|v| *v != vertex)
+                .collect::<FnvHashSet<_>>();
+            let faces = targets
+                .into_iter()
+                .map(|target| {
+                    let edge = EdgeId([vertex, target]);
+                    // Tet [vertex, target, a, b] is followed by tet [vertex, target, b, c].
+                    // That goes clockwise seen from the target, so it gets reversed.
+                    let next = mesh
+                        .edge_edge_opps(edge)
+                        .map(|opp| (opp.0[0], opp.0[1]))
+                        .collect::<FnvHashMap<_, _>>();
+                    let start = *next.keys().next().unwrap();
+                    let mut face = vec![];
+                    let mut a = start;
+                    loop {
+                        let b = next[&a];
+                        face.push(indexes[&TetId::from_valid([vertex, target, a, b])]);
+                        a = b;
+                        if a == start {
+                            break;
+                        }
+                    }
+                    face.reverse();
+                    face
+                })
+                .collect();
+
+            Cell {
+                vertex,
+                positions,
+                faces,
+            }
+        })
+        .collect()
+}
+
+/// Clips a cell to the half-space where `normal · x <= offset`.
+/// Returns None if nothing is left.
+fn clip(cell: Cell, normal: Vector3<f64>, offset: f64) -> Option<Cell> {
+    let sides = cell
+        .positions
+        .iter()
+        .map(|p| normal.dot(&p.coords) - offset)
+        .collect::<Vec<_>>();
+    if sides.iter().all(|side| *side <= 0.0) {
+        return Some(cell);
+    }
+    if sides.iter().all(|side| *side > 0.0) {
+        return None;
+    }
+
+    let mut positions = vec![];
+    let indexes = sides
+        .iter()
+        .enumerate()
+        .map(|(i, side)| {
+            (*side <= 0.0).then(|| {
+                positions.push(cell.positions[i]);
+                positions.len() - 1
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Where edges cross the plane, computed the same way for both faces on an edge
+    let mut crossings = FnvHashMap::default();
+    let mut crossing = |i: usize, j: usize| {
+        let (inside, outside) = if sides[i] <= 0.0 { (i, j) } else { (j, i) };
+        if sides[inside] == 0.0 {
+            return indexes[inside].unwrap();
+        }
+        *crossings.entry((inside, outside)).or_insert_with(|| {
+            let t = sides[inside] / (sides[inside] - sides[outside]);
+            positions.push(
+                cell.positions[inside] + (cell.positions[outside] - cell.positions[inside]) * t,
+            );
+            positions.len() - 1
+        })
+    };
+
+    // The cap goes backwards along the new edges of the clipped faces
+    let mut cap_next = FnvHashMap::default();
+    let mut faces = cell
+        .faces
+        .iter()
+        .flat_map(|face| {
+            let mut clipped = vec![];
+            let (mut exit, mut entry) = (None, None);
+            for (k, i) in face.iter().enumerate() {
+                let j = face[(k + 1) % face.len()];
+                if let Some(index) = indexes[*i] {
+                    clipped.push(index);
+                }
+                if (sides[*i] <= 0.0) != (sides[j] <= 0.0) {
+                    let index = crossing(*i, j);
+                    clipped.push(index);
+                    if sides[*i] <= 0.0 {
+                        exit = Some(index);
+                    } else {
+                        entry = Some(index);
+                    }
+                }
+            }
+            if let (Some(exit), Some(entry)) = (exit, entry) {
+                cap_next.insert(entry, exit);
+            }
+
+            clipped.dedup();
+            if clipped.len() > 1 && clipped.first() == clipped.last() {
+                clipped.pop();
+            }
+            Some(clipped).filter(|clipped| clipped.len() >= 3)
+        })
+        .collect::<Vec<_>>();
+
+    cap_next.retain(|entry, exit| entry != exit);
+    if let Some(start) = cap_next.keys().next().copied() {
+        let mut cap = vec![start];
+        while let Some(next) = cap_next
+            .get(cap.last().unwrap())
+            .filter(|next| **next != start)
+        {
+            if cap.len() > cap_next.len() {
+                break;
+            }
+            cap.push(*next);
+        }
+        if cap.len() >= 3 {
+            faces.push(cap);
+        }
+    }
+
+    Some(Cell {
+        vertex: cell.vertex,
+        positions,
+        faces,
+    })
+    .filter(|cell| !cell.faces.is_empty())
+}
+
+/// A box as a cell
+fn box_cell(vertex: VertexId, [min, max]: [Point3<f64>; 2]) -> Cell {
+    let positions = (0..8)
+        .map(|i| {
+            Point3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        })
+        .collect();
+    let faces = vec![
+        vec![0, 4, 6, 2],
+        vec![1, 3, 7, 5],
+        vec![0, 1, 5, 4],
+        vec![2, 6, 7, 3],
+        vec![0, 2, 3, 1],
+        vec![4, 5, 7, 6],
+    ];
+    Cell {
+        vertex,
+        positions,
+        faces,
+    }
+}
+
+/// Clips a cell to a box
+fn clip_to_box(cell: Cell, [min, max]: [Point3<f64>; 2]) -> Option<Cell> {
+    (0..3).try_fold(cell, |cell, i| {
+        let axis = Vector3::ith(i, 1.0);
+        clip(clip(cell, axis, max[i])?, -axis, -min[i])
+    })
+}
+
+/// The cells of the Voronoi diagram of a Delaunay tetrahedralization, clipped to a box.
+/// Cells of vertices on the hull are unbounded, so those get made by clipping the box instead.
+pub(crate) fn voronoi_cells<M>(mesh: &M, bounds: [Point3<f64>; 2]) -> Vec<Cell>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let interior = cells(mesh, |tet| tet_orthocenter(mesh, tet, |_| 0.0));
+    let interior_vertices = interior
+        .iter()
+        .map(|cell| cell.vertex)
+        .collect::<FnvHashSet<_>>();
+
+    let hull = mesh
+        .vertex_ids()
+        .copied()
+        .filter(|vertex| {
+            !interior_vertices.contains(vertex) && mesh.vertex_tets(*vertex).next().is_some()
+        })
+        .flat_map(|vertex| {
+            let position = mesh.position(vertex);
+            let neighbors = mesh
+                .vertex_tets(vertex)
+                .flat_map(|tet| tet.0.to_vec())
+                .filter(|v| *v != vertex)
+                .collect::<FnvHashSet<_>>();
+            neighbors
+                .into_iter()
+                .try_fold(box_cell(vertex, bounds), |cell, neighbor| {
+                    let neighbor = mesh.position(neighbor);
+                    let normal = neighbor - position;
+                    clip(
+                        cell,
+                        normal,
+                        normal.dot(&nalgebra::center(&position, &neighbor).coords),
+                    )
+                })
+        })
+        .collect::<Vec<_>>();
+
+    interior
+        .into_iter()
+        .flat_map(|cell| clip_to_box(cell, bounds))
+        .chain(hull)
+        .collect()
+}
+
+/// The cells of the power diagram of a weighted Delaunay tetrahedralization
+pub(crate) fn power_cells<M>(mesh: &M, weight: impl Fn(&M::V) -> f64) -> Vec<Cell>
+where
+    M: HasTets + HasPosition3D,
+    M::V: Position<Dim = U3>,
+{
+    let weights = mesh
+        .vertices()
+        .map(|(id, v)| (*id, weight(v)))
+        .collect::<FnvHashMap<_, _>>();
+    cells(mesh, |tet| tet_orthocenter(mesh, tet, |v| weights[&v]))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::tet::HasPositionAndTets;
+    use crate::vertex::{HasPosition, HasPosition3D, HasVertices};
+    use crate::ComboMesh0;
+
+    /// Signed volume of a cell, positive if the faces point outward
+    fn volume(cell: &Cell) -> f64 {
+        let origin = cell.positions[0];
+        cell.faces
+            .iter()
+            .flat_map(|face| {
+                let p0 = cell.positions[face[0]] - origin;
+                face.windows(2).skip(1).map(move |w| {
+                    let (p1, p2) = (cell.positions[w[0]] - origin, cell.positions[w[1]] - origin);
+                    p0.dot(&p1.cross(&p2)) / 6.0
+                })
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_power_cells_voronoi() {
+        let mut mesh = ComboMesh0::<(Point3<f64>, f64)>::with_defaults(|| (Point3::origin(), 0.0));
+        let mut rng = Pcg64::seed_from_u64(77);
+        mesh.extend_vertices((0..100).map(|_| (Point3::new(rng.gen(), rng.gen(), rng.gen()), 0.0)));
+        let mesh = mesh.weighted_delaunay_tets(|v| v.1, || (), || (), || ());
+
+        // Without weights, the corners of a cell are at least as close to its vertex as to any other
+        for cell in mesh.power_cells(|v| v.1) {
+            let position = mesh.position(cell.vertex);
+            for corner in &cell.positions {
+                let distance = (corner - position).norm_squared();
+                assert!(mesh.vertex_ids().all(
+                    |v| (corner - mesh.position(*v)).norm_squared() >= distance * (1.0 - 1e-9)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_voronoi_cells_tile_box() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        let mut rng = Pcg64::seed_from_u64(31);
+        mesh.extend_vertices((0..200).map(|_| Point3::new(rng.gen(), rng.gen(), rng.gen())));
+        let mesh = mesh.delaunay_tets(|| (), || (), || ());
+
+        let bounds = [Point3::new(-0.5, -0.5, -0.5), Point3::new(1.5, 1.5, 1.5)];
+        let cells = mesh.voronoi_cells(bounds);
+        assert_eq!(cells.len(), 200);
+
+        for cell in &cells {
+            assert!(volume(cell) > 0.0);
+            for position in &cell.positions {
+                assert!(position
+                    .iter()
+                    .all(|x| (-0.5 - 1e-12..=1.5 + 1e-12).contains(x)));
+            }
+        }
+        let total = cells.iter().map(volume).sum::<f64>();
+        assert!((total - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_voronoi_cells_clipped() {
+        let mut mesh = ComboMesh0::<Point3<f64>>::with_defaults(Point3::origin);
+        mesh.extend_vertices(vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ]);
+        let mesh = mesh.delaunay_tets(|| (), || (), || ());
+
+        // Only the cell of the vertex at the origin reaches into this box
+        let cells =
+            mesh.voronoi_cells([Point3::new(-1.0, -1.0, -1.0), Point3::new(0.25, 0.25, 0.25)]);
+        assert_eq!(cells.len(), 1);
+        assert_eq!(mesh.position(cells[0].vertex), Point3::origin());
+        assert!((volume(&cells[0]) - 1.25f64.powi(3)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_power_cells_outward() {
+        let mut mesh = ComboMesh0::<(Point3<f64>, f64)>::with_defaults(|| (Point3::origin(), 0.0));
+        let mut rng = Pcg64::seed_from_u64(999);
+        mesh.extend_vertices((0..100).map(|_| {
+            (
+                Point3::new(rng.gen(), rng.gen(), rng.gen()),
+                rng.gen::<f64>() * 0.01,
+            )
+        }));
+        let mesh = mesh.weighted_delaunay_tets(|v| v.1, || (), || (), || ());
+
+        let cells = mesh.power_cells(|v| v.1);
+        assert!(!cells.is_empty());
+        for cell in &cells {
+            let neighbors = mesh
+                .vertex_tets(cell.vertex)
+                .flat_map(|tet| tet.0.to_vec())
+                .collect::<FnvHashSet<_>>();
+            assert_eq!(cell.faces.len(), neighbors.len() - 1);
+            assert!(volume(cell) > 0.0);
+        }
+        assert_eq!(
+            cells
+                .iter()
+                .map(|cell| cell.vertex)
+                .collect::<FnvHashSet<_>>()
+                .len(),
+            cells.len()
+        );
+        assert!(mesh.vertices().count() > cells.len());
+    }
+}